@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{load_state, save_state, Activity, Cipher};
+
+/// Notes attached to items, which are shown with the items in later reports
+///
+/// Items are identified by their URL, so notes apply to every period in which the item shows up.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Annotations {
+    notes: BTreeMap<String, Vec<String>>,
+}
+
+impl Annotations {
+    /// Load the notes saved at `path`, if any
+    pub fn load(path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<Self> {
+        load_state(path, cipher)
+    }
+
+    pub fn save(&self, path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<()> {
+        save_state(self, path, cipher)
+    }
+
+    /// Attach `note` to the item at `html_url`, after any earlier notes
+    pub fn add(&mut self, html_url: &str, note: String) {
+        let notes = self.notes.entry(normalize(html_url).to_owned());
+        notes.or_default().push(note);
+    }
+
+    /// Remove the notes for the item at `html_url`, returning whether there were any
+    pub fn clear(&mut self, html_url: &str) -> bool {
+        self.notes.remove(normalize(html_url)).is_some()
+    }
+
+    /// Attach the notes to the items in `activity`
+    pub fn apply(&self, activity: &mut Activity) {
+        for entries in activity.projects.values_mut() {
+            for (html_url, entry) in entries {
+                if let Some(notes) = self.notes.get(html_url) {
+                    entry.notes.clone_from(notes);
+                }
+            }
+        }
+    }
+}
+
+fn normalize(html_url: &str) -> &str {
+    html_url.trim().trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn notes() {
+        let mut activity = Activity::default();
+        let url = "https://github.com/hyperium/hyper/pull/1";
+        let entry = Entry::new("Fix a panic".to_owned(), ItemKind::PullRequest);
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(url.to_owned(), entry);
+
+        let mut annotations = Annotations::default();
+        annotations.add(&format!("{url}/"), "backported to 0.14".to_owned());
+        annotations.add(url, "released in 1.4.1".to_owned());
+        assert!(!annotations.clear("https://github.com/hyperium/hyper/pull/2"));
+        annotations.apply(&mut activity);
+        assert_eq!(
+            activity.projects["hyper"][url].notes,
+            ["backported to 0.14", "released in 1.4.1"]
+        );
+
+        assert!(annotations.clear(url));
+        assert!(annotations.notes.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::html::{items, projects};
+use crate::{Activity, Links, Markup, Renderer, Titles};
+
+/// Renders reports as an Atom feed, with an entry per project
+///
+/// Entry IDs are derived from the period and the project, so that feed readers recognize entries
+/// when the report for a period is regenerated.
+#[derive(Debug)]
+pub struct Atom<'a> {
+    /// The name of the period (like "October 2024")
+    pub period: String,
+    /// Identifies the period in entry IDs (see [`Interval::key()`](crate::Interval::key))
+    pub key: String,
+    /// When the report was generated
+    pub updated: DateTime<Utc>,
+    pub titles: &'a Titles,
+    pub links: &'a Links,
+}
+
+impl Renderer for Atom<'_> {
+    fn render(
+        &self,
+        activity: &Activity,
+        users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        let updated = self.updated.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let authors = users
+            .iter()
+            .map(|user| format!("<author><name>{}</name></author>", XML.text(user)))
+            .collect::<String>();
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(out, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+        writeln!(out, "  <title>{}</title>", XML.text(&self.period))?;
+        writeln!(out, "  <id>urn:tmog-events:{}</id>", XML.text(&self.key))?;
+        writeln!(out, "  <updated>{updated}</updated>")?;
+        writeln!(out, "  {authors}")?;
+        writeln!(out, r#"  <generator>tmog-events</generator>"#)?;
+
+        for project in projects(activity) {
+            // The content is HTML, which is escaped once more to embed it in the feed
+            let content = items(activity, project, self.titles, self.links);
+            let title = format!("{project} — {}", self.period);
+            writeln!(out, "  <entry>")?;
+            writeln!(out, "    <title>{}</title>", XML.text(&title))?;
+            writeln!(
+                out,
+                "    <id>urn:tmog-events:{}:{}</id>",
+                XML.text(&self.key),
+                XML.url(project)
+            )?;
+            writeln!(out, "    <updated>{updated}</updated>")?;
+            writeln!(
+                out,
+                r#"    <content type="html">{}</content>"#,
+                XML.text(&content)
+            )?;
+            writeln!(out, "  </entry>")?;
+        }
+        writeln!(out, "</feed>")?;
+        Ok(())
+    }
+}
+
+/// Escapes text and links for XML (which is the same as for HTML)
+const XML: Markup = Markup::Html;
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn feed() {
+        let mut activity = Activity::default();
+        let url = "https://github.com/hyperium/hyper/pull/1?a&b";
+        let entry = Entry::new("Use <T> & co".to_owned(), ItemKind::PullRequest);
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(url.to_owned(), entry);
+
+        let (titles, links) = (Titles::default(), Links::default());
+        let atom = Atom {
+            period: "March 2024".to_owned(),
+            key: "202403".to_owned(),
+            updated: Utc.with_ymd_and_hms(2024, 4, 1, 12, 0, 0).unwrap(),
+            titles: &titles,
+            links: &links,
+        };
+        let mut out = Vec::new();
+        atom.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let feed = String::from_utf8(out).unwrap();
+        assert!(feed.contains("<updated>2024-04-01T12:00:00Z</updated>"));
+        assert!(feed.contains("<title>hyper — March 2024</title>"));
+        assert!(feed.contains("<id>urn:tmog-events:202403:hyper</id>"));
+        assert!(feed.contains(
+            "&lt;li&gt;&lt;a href=&quot;https://github.com/hyperium/hyper/pull/1?a&amp;amp;b\
+             &quot;&gt;Use &amp;lt;T&amp;gt; &amp;amp; co&lt;/a&gt;&lt;/li&gt;"
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_stream::try_stream;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use gcp_auth::TokenProvider;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::github::{normalize, API_URL, WEB_URL};
+use crate::{Asset, Cost, Details, EventSource, Fetch, GitHub, Http, Interval};
+
+/// The GitHub archive dataset on Google BigQuery (github.com only)
+///
+/// The dataset is partitioned by month; intervals spanning multiple months query all of them.
+pub struct Archive {
+    gcp_project: String,
+    provider: Arc<dyn TokenProvider>,
+    /// Token for the GitHub REST API, used for what the dataset doesn't cover
+    github_token: Option<String>,
+}
+
+impl Archive {
+    /// Queries are billed to `gcp_project`, using credentials from `provider`
+    ///
+    /// Details, current names and snippets are looked up through the REST API instead, which
+    /// is authenticated with `github_token` (if any) for a higher rate limit.
+    pub fn new(
+        gcp_project: String,
+        provider: Arc<dyn TokenProvider>,
+        github_token: Option<String>,
+    ) -> Self {
+        Self {
+            gcp_project,
+            provider,
+            github_token,
+        }
+    }
+
+    /// A client for the GitHub REST API
+    fn github(&self) -> anyhow::Result<GitHub> {
+        GitHub::new(API_URL.to_owned(), self.github_token.clone())
+    }
+
+    /// Stream the (normalized) events selected by `sql`
+    fn events<'a>(&'a self, sql: String, http: &'a Http) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let data = self.query(sql, false, http).await?;
+            let incomplete = match (data.job_complete, &data.page_token) {
+                (true, None) => None,
+                (false, _) => Some(None),
+                (true, Some(_)) => Some(data.total_rows.and_then(|total| total.parse().ok())),
+            };
+
+            for row in data.rows {
+                let [kind, repo, created_at, payload] =
+                    <[Field; 4]>::try_from(row.f).map_err(|fields| {
+                        anyhow::Error::msg(format!("expected 4 fields, got {}", fields.len()))
+                    })?;
+                yield Fetch::Event(normalize(
+                    &kind.value()?,
+                    repo.v.as_deref(),
+                    created_at.value()?.parse()?,
+                    serde_json::from_str(&payload.value()?)?,
+                ));
+            }
+
+            if let Some(total) = incomplete {
+                yield Fetch::Incomplete { total };
+            }
+        })
+    }
+
+    async fn query(
+        &self,
+        sql: String,
+        dry_run: bool,
+        http: &Http,
+    ) -> anyhow::Result<JobsQueryResponse> {
+        info!("requesting token");
+        let token = self
+            .provider
+            .token(&["https://www.googleapis.com/auth/bigquery"])
+            .await?;
+
+        let project = &self.gcp_project;
+        info!(sql, project, dry_run, "querying BigQuery");
+        let url = format!("{BIG_QUERY}/projects/{project}/queries");
+        let _permit = http.acquire(&url).await?;
+        let data = JobsQueryData {
+            query: sql,
+            dry_run,
+        };
+
+        Ok(http
+            .client()
+            .post(&url)
+            .json(&data)
+            .bearer_auth(token.as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JobsQueryResponse>()
+            .await?)
+    }
+}
+
+impl EventSource for Archive {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec![WEB_URL.to_owned()]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        user.to_owned()
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.events(sql(interval, &format!("actor.login = '{user}'")), http)
+    }
+
+    /// Find forks and stars of repositories owned by `user`, and new sponsors of `user`
+    fn received<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        let filter = format!(
+            "(type IN ({RECEIVED}) AND STARTS_WITH(repo.name, '{user}/')) \
+             OR (type = 'SponsorshipEvent' \
+             AND JSON_EXTRACT_SCALAR(payload, '$.sponsorship.sponsorable.login') = '{user}')"
+        );
+        self.events(sql(interval, &filter), http)
+    }
+
+    /// List gists through the REST API, as the archive no longer records them
+    fn snippets<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let github = self.github()?;
+            let mut snippets = github.snippets(user, interval, http);
+            while let Some(snippet) = snippets.next().await {
+                yield snippet?;
+            }
+        })
+    }
+
+    /// Look up the details on github.com (the dataset only has them as of the time of events)
+    fn details<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Details>>> {
+        Box::pin(async move {
+            let github = self.github()?;
+            github.details(html_url, http).await
+        })
+    }
+
+    /// Look up the assets on github.com (the dataset only has their downloads as of the releases)
+    fn assets<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<Asset>>>> {
+        Box::pin(async move {
+            let github = self.github()?;
+            github.assets(html_url, http).await
+        })
+    }
+
+    fn participants<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        Box::pin(async move {
+            let github = self.github()?;
+            github.participants(html_url, http).await
+        })
+    }
+
+    /// Look up the current name on github.com (the dataset has the name at the time of events)
+    fn current_name<'a>(
+        &'a self,
+        repo: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        Box::pin(async move {
+            let github = self.github()?;
+            github.current_name(repo, http).await
+        })
+    }
+
+    /// Ask BigQuery how many bytes the query for `user` would process
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            let sql = sql(interval, &format!("actor.login = '{user}'"));
+            let rsp = self.query(sql, true, http).await?;
+            Ok(Cost {
+                requests: 1,
+                exact: true,
+                bytes_processed: rsp
+                    .total_bytes_processed
+                    .and_then(|bytes| bytes.parse().ok()),
+            })
+        })
+    }
+}
+
+/// Select the events matching `filter` in `interval` from the month tables it overlaps
+fn sql(interval: Interval, filter: &str) -> String {
+    let first = interval.start.format("%Y%m");
+    let last = (interval.end - chrono::Duration::seconds(1)).format("%Y%m");
+    let start = interval.start.format("%FT%TZ");
+    let end = interval.end.format("%FT%TZ");
+    format!(
+        "SELECT type, repo.name, FORMAT_TIMESTAMP('%FT%TZ', created_at), payload \
+         FROM `githubarchive.month.*` WHERE _TABLE_SUFFIX BETWEEN '{first}' AND '{last}' \
+         AND created_at >= TIMESTAMP('{start}') AND created_at < TIMESTAMP('{end}') \
+         AND ({filter}) ORDER BY created_at"
+    )
+}
+
+#[allow(dead_code)] // Helper function for generating formattable JSON
+fn dump(path: impl AsRef<Path>, events: &[String]) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "[\n    ")?;
+    for (i, event) in events.iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",\n    ")?;
+        }
+        write!(writer, "{}", event)?;
+    }
+    write!(writer, "\n]\n")?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobsQueryResponse {
+    job_complete: bool,
+    total_rows: Option<String>,
+    total_bytes_processed: Option<String>,
+    page_token: Option<String>,
+    #[serde(default)]
+    rows: Vec<Row>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Row {
+    f: Vec<Field>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Field {
+    v: Option<String>,
+}
+
+impl Field {
+    fn value(self) -> anyhow::Result<String> {
+        self.v.context("unexpected null value")
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobsQueryData {
+    query: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    dry_run: bool,
+}
+
+const SOURCE: &str = "githubarchive";
+/// Types of events by others relevant to repositories owned by the user
+const RECEIVED: &str = "'ForkEvent', 'WatchEvent'";
+const BIG_QUERY: &str = "https://bigquery.googleapis.com/bigquery/v2";
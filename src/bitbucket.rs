@@ -0,0 +1,334 @@
+use async_stream::try_stream;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{Cost, Event, EventSource, Fetch, Http, Interval, ItemMeta, Review};
+
+/// Bitbucket Cloud
+///
+/// Bitbucket has no per-user event feed: pull requests authored by the user are found across
+/// all repositories, while comments and approvals are only found in the given `repos`
+/// (formatted as `workspace/repository`).
+pub struct Bitbucket {
+    repos: Vec<String>,
+    token: Option<String>,
+}
+
+impl Bitbucket {
+    pub fn new(repos: Vec<String>, token: Option<String>) -> Self {
+        Self { repos, token }
+    }
+}
+
+impl EventSource for Bitbucket {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec![WEB_URL.to_owned()]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        format!("{user}@bitbucket.org")
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let token = self.token.as_deref();
+            let window = window(interval);
+            let url = authored_url(user, &window)?;
+            for pr in paged::<PullRequest>(url, token, http).await? {
+                yield pr.into_event("PullRequestEvent", "opened", None)?;
+            }
+
+            for repo in &self.repos {
+                let mut next = Some(format!(
+                    "{API_URL}/repositories/{repo}/pullrequests/activity?pagelen=50"
+                ));
+                'pages: while let Some(url) = next.take() {
+                    let page = page::<Activity>(&url, token, http).await?;
+                    next = page.next;
+                    for activity in page.values {
+                        let (author, date, kind) = match (&activity.comment, &activity.approval) {
+                            (Some(comment), _) => (&comment.user, comment.created_on, COMMENT),
+                            (None, Some(approval)) => (&approval.user, approval.date, REVIEW),
+                            (None, None) => continue,
+                        };
+
+                        // Activity is returned newest first
+                        if date < interval.start {
+                            break 'pages;
+                        } else if date >= interval.end || !author.is(user) {
+                            continue;
+                        }
+
+                        if let Some(pr) = activity.pull_request {
+                            let review = activity.approval.map(|_| Review {
+                                state: "approved".to_owned(),
+                                html_url: None,
+                                id: None,
+                            });
+                            yield pr.into_event(kind, "created", review)?;
+                        }
+                    }
+                }
+
+                let url = reqwest::Url::parse_with_params(
+                    &format!("{API_URL}/repositories/{repo}/issues"),
+                    [("q", &window)],
+                )?;
+                let issues = match paged::<Issue>(url.into(), token, http).await {
+                    // Repositories without an issue tracker return 404
+                    Err(error) if is_not_found(&error) => continue,
+                    issues => issues?,
+                };
+
+                for issue in issues {
+                    let is_author = issue
+                        .reporter
+                        .as_ref()
+                        .is_some_and(|reporter| reporter.is(user));
+                    let commented = match is_author {
+                        true => false,
+                        false => {
+                            let url = format!(
+                                "{API_URL}/repositories/{repo}/issues/{}/comments",
+                                issue.id
+                            );
+                            paged::<Comment>(url, token, http)
+                                .await?
+                                .iter()
+                                .any(|comment| {
+                                    comment.user.is(user) && interval.contains(comment.created_on)
+                                })
+                        }
+                    };
+
+                    if is_author || commented {
+                        let (kind, action) = match is_author {
+                            true => ("IssuesEvent", "opened"),
+                            false => (COMMENT, "created"),
+                        };
+                        yield Fetch::event(&Event {
+                            kind: Some(kind.to_owned()),
+                            action: Some(action.to_owned()),
+                            issue: Some(ItemMeta {
+                                html_url: issue.links.html.href,
+                                title: issue.title,
+                                created_at: Some(issue.created_on),
+                                closed_at: None,
+                                merged_at: None,
+                                node_id: None,
+                                updated_at: None,
+                                body: None,
+                                user: None,
+                                author_association: None,
+                                pull_request: None,
+                            }),
+                            pull_request: None,
+                            ..Default::default()
+                        })?;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Estimate the number of requests from the first page of authored pull requests
+    ///
+    /// Each of the `repos` needs at least two requests (more for busy repositories).
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            let url = authored_url(user, &window(interval))?;
+            let first = page::<serde_json::Value>(&url, self.token.as_deref(), http).await?;
+            let pages = match (first.size, first.pagelen) {
+                (Some(size), Some(pagelen)) if pagelen > 0 => size.div_ceil(pagelen).max(1),
+                _ => 1,
+            };
+
+            Ok(Cost {
+                requests: pages + 2 * self.repos.len() as u64,
+                exact: self.repos.is_empty() && first.size.is_some(),
+                bytes_processed: None,
+            })
+        })
+    }
+}
+
+/// The URL listing pull requests authored by `user`, filtered by the `window` query
+fn authored_url(user: &str, window: &str) -> anyhow::Result<String> {
+    Ok(reqwest::Url::parse_with_params(
+        &format!("{API_URL}/pullrequests/{user}"),
+        [
+            ("state", "OPEN"),
+            ("state", "MERGED"),
+            ("state", "DECLINED"),
+            ("state", "SUPERSEDED"),
+            ("q", window),
+        ],
+    )?
+    .into())
+}
+
+/// A query matching items which were active within `interval`
+fn window(interval: Interval) -> String {
+    format!(
+        "updated_on >= {} AND created_on < {}",
+        interval.start.format("%Y-%m-%dT%H:%M:%S%:z"),
+        interval.end.format("%Y-%m-%dT%H:%M:%S%:z")
+    )
+}
+
+/// Collect all values from a paginated Bitbucket API response
+async fn paged<T: DeserializeOwned>(
+    url: String,
+    token: Option<&str>,
+    http: &Http,
+) -> anyhow::Result<Vec<T>> {
+    let (mut values, mut next) = (Vec::new(), Some(url));
+    while let Some(url) = next.take() {
+        let page = page::<T>(&url, token, http).await?;
+        values.extend(page.values);
+        next = page.next;
+    }
+
+    Ok(values)
+}
+
+async fn page<T: DeserializeOwned>(
+    url: &str,
+    token: Option<&str>,
+    http: &Http,
+) -> anyhow::Result<Page<T>> {
+    debug!(url, "fetching page");
+    let _permit = http.acquire(url).await?;
+    let mut req = http.client().get(url);
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+
+    Ok(req.send().await?.error_for_status()?.json().await?)
+}
+
+fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .and_then(|error| error.status())
+        .is_some_and(|status| status == reqwest::StatusCode::NOT_FOUND)
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    values: Vec<T>,
+    next: Option<String>,
+    size: Option<u64>,
+    pagelen: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Activity {
+    comment: Option<Comment>,
+    approval: Option<Approval>,
+    pull_request: Option<PullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    user: User,
+    created_on: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Approval {
+    user: User,
+    date: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    title: String,
+    links: Links,
+    created_on: Option<DateTime<Utc>>,
+}
+
+impl PullRequest {
+    /// Normalize into an event of type `kind` (with the given `action` and `review`)
+    fn into_event(self, kind: &str, action: &str, review: Option<Review>) -> anyhow::Result<Fetch> {
+        Fetch::event(&Event {
+            kind: Some(kind.to_owned()),
+            action: Some(action.to_owned()),
+            review,
+            issue: None,
+            pull_request: Some(ItemMeta {
+                html_url: self.links.html.href,
+                title: self.title,
+                created_at: self.created_on,
+                closed_at: None,
+                merged_at: None,
+                node_id: None,
+                updated_at: None,
+                body: None,
+                user: None,
+                author_association: None,
+                pull_request: None,
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    id: u64,
+    title: String,
+    links: Links,
+    reporter: Option<User>,
+    created_on: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Links {
+    html: Link,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    nickname: Option<String>,
+    account_id: Option<String>,
+    uuid: Option<String>,
+}
+
+impl User {
+    /// Whether this user matches `user`, given as nickname, account ID or UUID
+    fn is(&self, user: &str) -> bool {
+        [&self.nickname, &self.account_id, &self.uuid]
+            .into_iter()
+            .any(|id| id.as_deref() == Some(user))
+    }
+}
+
+const SOURCE: &str = "bitbucket";
+const WEB_URL: &str = "https://bitbucket.org";
+const API_URL: &str = "https://api.bitbucket.org/2.0";
+const REVIEW: &str = "PullRequestReviewEvent";
+const COMMENT: &str = "IssueCommentEvent";
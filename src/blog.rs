@@ -0,0 +1,138 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::info;
+
+/// A git repository (like that of a blog) to commit reports to
+#[derive(Debug, Deserialize)]
+pub struct Blog {
+    /// The repository to clone, as a URL or a path
+    pub remote: String,
+    /// Where to keep the clone
+    #[serde(default = "default_dir")]
+    pub dir: PathBuf,
+    /// The path of the report within the repository (with the same placeholders as `output`)
+    pub path: String,
+    /// Open a pull request against this branch instead of pushing to the checked out branch
+    /// (GitHub only)
+    pub pull_request: Option<String>,
+}
+
+impl Blog {
+    /// Commit `report` to `path` (relative to the repository) and push it
+    ///
+    /// With [`Blog::pull_request`] set, the commit goes on `branch` (based on the latest version
+    /// of the target branch), which is force-pushed so that publishing a report again replaces
+    /// the earlier version. Returns `false` if the report was committed before as is.
+    pub fn commit(
+        &self,
+        path: &Path,
+        report: &[u8],
+        message: &str,
+        branch: &str,
+    ) -> anyhow::Result<bool> {
+        match self.dir.exists() {
+            true => self.git(&["fetch", "--quiet", "origin"])?,
+            false => {
+                info!(remote = self.remote, dir = %self.dir.display(), "cloning blog");
+                let dir = self
+                    .dir
+                    .to_str()
+                    .context("blog directory is not valid UTF-8")?;
+                git(None, &["clone", "--quiet", &self.remote, dir])?;
+            }
+        }
+        match &self.pull_request {
+            Some(base) => self.git(&["checkout", "-B", branch, &format!("origin/{base}")])?,
+            None => self.git(&["pull", "--quiet", "--ff-only"])?,
+        }
+
+        let full = self.dir.join(path);
+        if let Some(parent) = full.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&full, report).with_context(|| format!("failed to write {}", full.display()))?;
+
+        let path = path.to_str().context("report path is not valid UTF-8")?;
+        self.git(&["add", "--", path])?;
+        if self.git(&["diff", "--cached", "--quiet"]).is_ok() {
+            return Ok(false);
+        }
+
+        self.git(&["commit", "--quiet", "-m", message])?;
+        match &self.pull_request {
+            Some(_) => self.git(&["push", "--quiet", "--force", "origin", branch])?,
+            None => self.git(&["push", "--quiet", "origin", "HEAD"])?,
+        }
+        info!(path, "committed report");
+        Ok(true)
+    }
+
+    /// The GitHub repository (`owner/name`) of the remote, if it's on github.com
+    pub fn github_repo(&self) -> Option<&str> {
+        let (_, path) = self
+            .remote
+            .split_once("github.com/")
+            .or_else(|| self.remote.split_once("github.com:"))?;
+        let path = path.trim_end_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        (path.matches('/').count() == 1).then_some(path)
+    }
+
+    fn git(&self, args: &[&str]) -> anyhow::Result<()> {
+        git(Some(&self.dir), args)
+    }
+}
+
+/// Run `git` with `args` (in `dir`, if given), failing if it does
+fn git(dir: Option<&Path>, args: &[&str]) -> anyhow::Result<()> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.arg("-C").arg(dir);
+    }
+    // Keep stdout for the report
+    let status = cmd
+        .args(args)
+        .stdout(io::stderr())
+        .status()
+        .context("failed to run git")?;
+    match status.success() {
+        true => Ok(()),
+        false => anyhow::bail!("`git {}` failed ({status})", args.join(" ")),
+    }
+}
+
+fn default_dir() -> PathBuf {
+    PathBuf::from("blog")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn github_repo() {
+        let blog = |remote: &str| Blog {
+            remote: remote.to_owned(),
+            dir: default_dir(),
+            path: String::new(),
+            pull_request: None,
+        };
+        let repo = |remote| blog(remote).github_repo().map(str::to_owned);
+        assert_eq!(
+            repo("git@github.com:djc/blog.git").as_deref(),
+            Some("djc/blog")
+        );
+        assert_eq!(
+            repo("https://github.com/djc/blog/").as_deref(),
+            Some("djc/blog")
+        );
+        assert_eq!(repo("https://gitlab.com/djc/blog"), None);
+        assert_eq!(repo("/srv/git/blog"), None);
+    }
+}
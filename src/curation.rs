@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+use std::{env, fs};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{load_state, save_state, Activity, Cipher};
+
+/// Decisions on which items go into a report, and how (see `tmog-events review` and
+/// `--interactive`)
+///
+/// Items are identified by their URL, so that the decisions still apply when the activity for a
+/// period is collected again.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Curation {
+    /// Items to leave out
+    #[serde(default)]
+    pub dropped: BTreeSet<String>,
+    /// Titles to use instead of those of the items
+    #[serde(default)]
+    pub titles: BTreeMap<String, String>,
+    /// The position of items within their project (see [`Entry::position`](crate::Entry))
+    #[serde(default)]
+    pub positions: BTreeMap<String, usize>,
+}
+
+impl Curation {
+    /// Load the decisions saved at `path`, if any
+    pub fn load(path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<Self> {
+        load_state(path, cipher)
+    }
+
+    pub fn save(&self, path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<()> {
+        save_state(self, path, cipher)
+    }
+
+    /// Leave out, retitle and reorder the items in `activity` as decided
+    pub fn apply(&self, activity: &mut Activity) {
+        for entries in activity.projects.values_mut() {
+            entries.retain(|html_url, _| !self.dropped.contains(html_url));
+            for (html_url, entry) in entries.iter_mut() {
+                if let Some(title) = self.titles.get(html_url) {
+                    entry.title.clone_from(title);
+                }
+                entry.position = self.positions.get(html_url).copied();
+            }
+        }
+        activity.projects.retain(|_, entries| !entries.is_empty());
+    }
+
+    /// Review the items in `activity` (for the period `name`) in the user's editor
+    ///
+    /// The items are listed by project, in a file that is opened with `$VISUAL` or `$EDITOR`.
+    /// Removing an item's line (or starting it with `-`) leaves it out, changing the text after
+    /// the URL changes its title, and moving lines reorders the items within their project.
+    pub fn review(&mut self, activity: &Activity, name: &str) -> anyhow::Result<()> {
+        let path = env::temp_dir().join(format!("tmog-events-review-{}.txt", std::process::id()));
+        fs::write(&path, self.list(activity, name))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_owned());
+        let mut words = editor.split_whitespace();
+        let status = Command::new(words.next().context("no editor configured")?)
+            .args(words)
+            .arg(&path)
+            .status()
+            .with_context(|| format!("failed to run {editor}"))?;
+        let text = fs::read_to_string(&path);
+        let _ = fs::remove_file(&path);
+        if !status.success() {
+            anyhow::bail!("{editor} failed ({status}), keeping the earlier review");
+        }
+
+        self.update(activity, &text?);
+        Ok(())
+    }
+
+    /// Walk through the items in `activity`, asking whether to include each of them
+    ///
+    /// Answers are read from `input`, with prompts written to `output`: `y` includes the item,
+    /// `n` leaves it out, and `e` asks for a new title (and includes it). An empty answer keeps
+    /// the earlier decision, and `q` keeps the earlier decisions for the remaining items.
+    pub fn prompt(
+        &mut self,
+        activity: &Activity,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        let mut line = String::new();
+        for (project, entries) in &activity.projects {
+            for (html_url, entry) in entries {
+                let title = self.titles.get(html_url).unwrap_or(&entry.title);
+                let default = match self.dropped.contains(html_url) {
+                    true => "y/N/e/q",
+                    false => "Y/n/e/q",
+                };
+                writeln!(output, "[{project}] {title}\n  {html_url}")?;
+                let answer = loop {
+                    write!(output, "Include? [{default}] ")?;
+                    output.flush()?;
+                    line.clear();
+                    if input.read_line(&mut line)? == 0 {
+                        // Input ran out, so keep the earlier decisions
+                        return Ok(());
+                    }
+                    match line.trim() {
+                        answer @ ("" | "y" | "n" | "e" | "q") => break answer.to_owned(),
+                        _ => writeln!(output, "Answer y (yes), n (no), e (edit) or q (quit)")?,
+                    }
+                };
+
+                match answer.as_str() {
+                    "y" => {
+                        self.dropped.remove(html_url);
+                    }
+                    "n" => {
+                        self.dropped.insert(html_url.clone());
+                    }
+                    "e" => {
+                        write!(output, "Title: ")?;
+                        output.flush()?;
+                        line.clear();
+                        input.read_line(&mut line)?;
+                        self.dropped.remove(html_url);
+                        match line.trim() {
+                            "" => {}
+                            title if title == entry.title => {
+                                self.titles.remove(html_url);
+                            }
+                            title => {
+                                self.titles.insert(html_url.clone(), title.to_owned());
+                            }
+                        }
+                    }
+                    "q" => return Ok(()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// List the items in `activity` for review, with the decisions made before
+    fn list(&self, activity: &Activity, name: &str) -> String {
+        let mut list = format!("# Items for {name}\n");
+        list.push_str(
+            "#\n# Delete a line (or start it with `-`) to leave the item out, change the text\n\
+             # after the URL to change its title, and move lines to reorder the items within\n\
+             # their project. Lines starting with `#` are ignored.\n",
+        );
+        for (project, entries) in &activity.projects {
+            let mut entries = entries.iter().collect::<Vec<_>>();
+            let position = |html_url: &String| self.positions.get(html_url).copied();
+            entries.sort_by_key(|(html_url, _)| position(html_url).unwrap_or(usize::MAX));
+
+            writeln!(list, "\n# {project}").unwrap();
+            for (html_url, entry) in entries {
+                let dropped = if self.dropped.contains(html_url) {
+                    "- "
+                } else {
+                    ""
+                };
+                let title = self.titles.get(html_url).unwrap_or(&entry.title);
+                writeln!(list, "{dropped}{html_url} {title}").unwrap();
+            }
+        }
+        list
+    }
+
+    /// Update the decisions from the reviewed `list` of the items in `activity`
+    fn update(&mut self, activity: &Activity, list: &str) {
+        let (mut listed, mut kept) = (BTreeMap::new(), 0);
+        for line in list.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (dropped, line) = match line.strip_prefix('-') {
+                Some(line) => (true, line.trim_start()),
+                None => (false, line),
+            };
+            let (html_url, title) = line.split_once(' ').unwrap_or((line, ""));
+            let position = (!dropped).then(|| {
+                kept += 1;
+                kept - 1
+            });
+            listed.insert(html_url, (position, title.trim()));
+        }
+
+        for entries in activity.projects.values() {
+            for (html_url, entry) in entries {
+                self.titles.remove(html_url);
+                self.positions.remove(html_url);
+                let Some(&(position, title)) = listed.get(html_url.as_str()) else {
+                    self.dropped.insert(html_url.clone());
+                    continue;
+                };
+
+                match position {
+                    Some(position) => {
+                        self.dropped.remove(html_url);
+                        self.positions.insert(html_url.clone(), position);
+                    }
+                    None => {
+                        self.dropped.insert(html_url.clone());
+                    }
+                }
+                if !title.is_empty() && title != entry.title {
+                    self.titles.insert(html_url.clone(), title.to_owned());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn reviewed() {
+        let mut activity = Activity::default();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        for n in 1..=3 {
+            let entry = Entry::new(format!("Change {n}"), ItemKind::PullRequest);
+            entries.insert(format!("https://github.com/hyperium/hyper/pull/{n}"), entry);
+        }
+
+        let mut curation = Curation::default();
+        let list = curation.list(&activity, "March 2024");
+        assert!(list.contains("\n# hyper\nhttps://github.com/hyperium/hyper/pull/1 Change 1\n"));
+
+        curation.update(
+            &activity,
+            "# hyper\n\
+             https://github.com/hyperium/hyper/pull/3 Change 3\n\
+             - https://github.com/hyperium/hyper/pull/2 Revert change 2\n\
+             https://github.com/hyperium/hyper/pull/1 Add HTTP/3 support\n",
+        );
+        assert!(curation.list(&activity, "March 2024").contains(
+            "\n# hyper\n\
+             https://github.com/hyperium/hyper/pull/3 Change 3\n\
+             https://github.com/hyperium/hyper/pull/1 Add HTTP/3 support\n\
+             - https://github.com/hyperium/hyper/pull/2 Revert change 2\n"
+        ));
+
+        curation.apply(&mut activity);
+        let entries = &activity.projects["hyper"];
+        assert_eq!(entries.len(), 2);
+        let first = &entries["https://github.com/hyperium/hyper/pull/1"];
+        assert_eq!(first.title, "Add HTTP/3 support");
+        assert_eq!(first.position, Some(1));
+        assert_eq!(
+            entries["https://github.com/hyperium/hyper/pull/3"].position,
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn prompted() {
+        let mut activity = Activity::default();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        for n in 1..=4 {
+            let entry = Entry::new(format!("Change {n}"), ItemKind::PullRequest);
+            entries.insert(format!("https://github.com/hyperium/hyper/pull/{n}"), entry);
+        }
+
+        let mut curation = Curation::default();
+        let mut input = "n\nmaybe\ne\nAdd HTTP/3 support\n\nq\n".as_bytes();
+        let mut output = Vec::new();
+        curation.prompt(&activity, &mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(
+            "[hyper] Change 1\n  https://github.com/hyperium/hyper/pull/1\nInclude? [Y/n/e/q] "
+        ));
+        assert!(output.contains("Answer y (yes), n (no), e (edit) or q (quit)\n"));
+
+        curation.apply(&mut activity);
+        let entries = &activity.projects["hyper"];
+        assert!(!entries.contains_key("https://github.com/hyperium/hyper/pull/1"));
+        let second = &entries["https://github.com/hyperium/hyper/pull/2"];
+        assert_eq!(second.title, "Add HTTP/3 support");
+        assert_eq!(entries.len(), 3);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::html::{self, Html};
+use crate::render::summary;
+use crate::{Activity, Interval, Links, Locale, Markup, Period, Renderer, Titles};
+
+/// Renders the activity of several months as a static site, like for GitHub Pages
+///
+/// The site has an `index.html` linking to a page per month (like `2024-03.html`, as with
+/// `--format html`) and to a page per project (like `projects/hyper.html`), which lists its items
+/// month by month.
+#[derive(Debug)]
+pub struct Dashboard<'a> {
+    pub titles: &'a Titles,
+    pub links: &'a Links,
+    pub locale: Locale,
+}
+
+impl Dashboard<'_> {
+    /// Write the pages for the activity in `months` (newest first) to `dir`
+    pub fn write(
+        &self,
+        dir: &Path,
+        months: &[(Interval, Activity)],
+        users: &[String],
+    ) -> anyhow::Result<()> {
+        let projects_dir = dir.join("projects");
+        fs::create_dir_all(&projects_dir)
+            .with_context(|| format!("failed to create {}", projects_dir.display()))?;
+        let write = |path: &Path, page: &[u8]| {
+            fs::write(path, page).with_context(|| format!("failed to write {}", path.display()))
+        };
+
+        let mut projects = BTreeMap::<&str, Vec<(&Interval, &Activity)>>::new();
+        for (interval, activity) in months {
+            let html = Html {
+                period: Period::Month.name(*interval, self.locale),
+                titles: self.titles,
+                links: self.links,
+            };
+            let mut page = Vec::new();
+            html.render(activity, users, &mut page)?;
+            write(&dir.join(page_name(*interval)), &page)?;
+            for project in html::projects(activity) {
+                projects
+                    .entry(project)
+                    .or_default()
+                    .push((interval, activity));
+            }
+        }
+
+        for (project, months) in &projects {
+            let page = self.project(project, months);
+            let path = projects_dir.join(format!("{}.html", slug(project)));
+            write(&path, page.as_bytes())?;
+        }
+        write(
+            &dir.join("index.html"),
+            self.index(months, &projects, users).as_bytes(),
+        )
+    }
+
+    /// The index, linking to the pages of the `months` and `projects`
+    fn index(
+        &self,
+        months: &[(Interval, Activity)],
+        projects: &BTreeMap<&str, Vec<(&Interval, &Activity)>>,
+        users: &[String],
+    ) -> String {
+        let users = users
+            .iter()
+            .map(|user| Markup::Html.text(user))
+            .collect::<Vec<_>>();
+        let title = format!("Activity by {}", users.join(", "));
+        let mut page = header(&title);
+        page.push_str("<h2>Months</h2>\n<ul>");
+        for (interval, activity) in months {
+            let (name, summary) = (
+                Period::Month.name(*interval, self.locale),
+                summary(activity),
+            );
+            let (name, summary) = (Markup::Html.text(&name), Markup::Html.text(&summary));
+            let link = page_name(*interval);
+            write!(page, r#"<li><a href="{link}">{name}</a>: {summary}</li>"#).unwrap();
+        }
+        page.push_str("</ul>\n<h2>Projects</h2>\n<ul>");
+        for (project, months) in projects {
+            let (link, name) = (slug(project), Markup::Html.text(project));
+            let s = if months.len() == 1 { "" } else { "s" };
+            write!(
+                page,
+                r#"<li><a href="projects/{link}.html">{name}</a> ({} month{s})</li>"#,
+                months.len()
+            )
+            .unwrap();
+        }
+        page.push_str("</ul>\n");
+        page.push_str(FOOTER);
+        page
+    }
+
+    /// The history of `project`, with its items in each of the `months` (newest first)
+    fn project(&self, project: &str, months: &[(&Interval, &Activity)]) -> String {
+        let mut page = header(&Markup::Html.text(project));
+        page.push_str("<p><a href=\"../index.html\">All months and projects</a></p>\n");
+        for (interval, activity) in months {
+            let name = Period::Month.name(**interval, self.locale);
+            let name = Markup::Html.text(&name);
+            let link = page_name(**interval);
+            writeln!(page, r#"<h2><a href="../{link}">{name}</a></h2>"#).unwrap();
+            let items = html::items(activity, project, self.titles, self.links);
+            writeln!(page, "{items}").unwrap();
+        }
+        page.push_str(FOOTER);
+        page
+    }
+}
+
+/// The start of a page with the (escaped) `title`
+fn header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"utf-8\"><title>{title}</title>\
+         </head>\n<body>\n<h1>{title}</h1>\n"
+    )
+}
+
+const FOOTER: &str = "</body>\n</html>\n";
+
+/// The name of the page for the month starting at `interval`, like `2024-03.html`
+fn page_name(interval: Interval) -> String {
+    format!("{}.html", interval.date(interval.start).format("%Y-%m"))
+}
+
+/// The name of a file for `project`, which may have characters that don't belong in a path (like
+/// the `/` of personal scopes)
+fn slug(project: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    project
+        .chars()
+        .map(|c| if safe(c) { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn site() {
+        let month = |month: &str, number: u32| {
+            let mut activity = Activity::default();
+            let url = format!("https://github.com/hyperium/hyper/pull/{number}");
+            let entry = Entry::new(format!("Fix panic {number}"), ItemKind::PullRequest);
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(url, entry);
+            (Interval::month(month).unwrap(), activity)
+        };
+        let months = [month("202403", 2), month("202402", 1)];
+
+        let dir = std::env::temp_dir().join(format!("tmog-events-site-{}", std::process::id()));
+        let (titles, links) = (Titles::default(), Links::default());
+        let dashboard = Dashboard {
+            titles: &titles,
+            links: &links,
+            locale: Locale::default(),
+        };
+        dashboard.write(&dir, &months, &["djc".to_owned()]).unwrap();
+        let read = |path: &str| fs::read_to_string(dir.join(path)).unwrap();
+        let (index, project) = (read("index.html"), read("projects/hyper.html"));
+        let march = read("2024-03.html");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(index.contains(
+            r#"<li><a href="2024-03.html">March 2024</a>: 1 pull request in 1 project</li>"#
+        ));
+        assert!(index.contains(r#"<li><a href="projects/hyper.html">hyper</a> (2 months)</li>"#));
+        let (newer, older) = (
+            project.find("Fix panic 2").unwrap(),
+            project.find("Fix panic 1").unwrap(),
+        );
+        assert!(newer < older);
+        assert!(project.contains(r#"<h2><a href="../2024-02.html">February 2024</a></h2>"#));
+        assert!(march.contains("<h1>March 2024</h1>"));
+        assert_eq!(slug("djc/askama"), "djc-askama");
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::Context;
+use serde_json::Value;
+
+/// The items that differ between two JSON reports (see [`Activity::versioned()`])
+///
+/// Items are identified by their link, so that an item that moved to another project (or among
+/// the snippets) is changed rather than removed and added. Changed items list the fields of their
+/// entry that changed, like `state` or `labels`. The differences are ordered by project and link.
+///
+/// [`Activity::versioned()`]: crate::Activity::versioned
+pub fn diff(old: &str, new: &str) -> anyhow::Result<Vec<Difference>> {
+    let (old, new) = (
+        serde_json::from_str::<Value>(old).context("failed to parse the old report")?,
+        serde_json::from_str::<Value>(new).context("failed to parse the new report")?,
+    );
+    let (old_items, new_items) = (
+        items(&old).context("the old report lists no projects")?,
+        items(&new).context("the new report lists no projects")?,
+    );
+
+    let mut differences = Vec::new();
+    for (html_url, (project, entry)) in &new_items {
+        let change = match old_items.get(html_url) {
+            None => Change::Added,
+            Some((old_project, old_entry)) => {
+                let mut fields = Vec::new();
+                if old_project != project {
+                    fields.push("project".to_owned());
+                }
+                fields.extend(changed(old_entry, entry));
+                match fields.is_empty() {
+                    true => continue,
+                    false => Change::Changed(fields),
+                }
+            }
+        };
+        differences.push(Difference::new(change, project, html_url, entry));
+    }
+    for (html_url, (project, entry)) in &old_items {
+        if !new_items.contains_key(html_url) {
+            differences.push(Difference::new(Change::Removed, project, html_url, entry));
+        }
+    }
+    differences.sort_by(|a, b| (&a.project, &a.html_url).cmp(&(&b.project, &b.html_url)));
+    Ok(differences)
+}
+
+/// An item that was added, removed or changed (see [`diff()`])
+#[derive(Debug, PartialEq, Eq)]
+pub struct Difference {
+    pub change: Change,
+    /// The project of the item (in the new report, if it's in there), or `snippets`
+    pub project: String,
+    pub html_url: String,
+    pub title: String,
+}
+
+impl Difference {
+    fn new(change: Change, project: &str, html_url: &str, entry: &Value) -> Self {
+        let title = entry
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        Self {
+            change,
+            project: project.to_owned(),
+            html_url: html_url.to_owned(),
+            title: title.to_owned(),
+        }
+    }
+}
+
+/// Like `+ hyper: Fix a panic <https://github.com/hyperium/hyper/pull/1>`, with `-` for removed
+/// items and `~` for changed ones (followed by the fields that changed)
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.change {
+            Change::Added => '+',
+            Change::Removed => '-',
+            Change::Changed(_) => '~',
+        };
+        let Self {
+            project,
+            html_url,
+            title,
+            ..
+        } = self;
+        write!(f, "{sign} {project}: {title} <{html_url}>")?;
+        match &self.change {
+            Change::Changed(fields) => write!(f, " ({})", fields.join(", ")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How an item differs (see [`Difference`])
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Only in the new report
+    Added,
+    /// Only in the old report
+    Removed,
+    /// In both reports, with these fields changed
+    Changed(Vec<String>),
+}
+
+/// The entries of the items in `report` by their link, with their project (or `snippets`)
+fn items(report: &Value) -> Option<BTreeMap<&str, (&str, &Value)>> {
+    let mut items = BTreeMap::new();
+    for (project, entries) in report.get("projects")?.as_object()? {
+        for (html_url, entry) in entries.as_object()? {
+            items.insert(html_url.as_str(), (project.as_str(), entry));
+        }
+    }
+    if let Some(snippets) = report.get("snippets").and_then(Value::as_object) {
+        for (html_url, entry) in snippets {
+            items.insert(html_url.as_str(), ("snippets", entry));
+        }
+    }
+    Some(items)
+}
+
+/// The names of the fields that differ between the `old` and `new` entries
+fn changed<'a>(old: &'a Value, new: &'a Value) -> impl Iterator<Item = String> + 'a {
+    let (old, new) = (old.as_object(), new.as_object());
+    let fields = old.into_iter().chain(new).flat_map(|fields| fields.keys());
+    let mut fields = fields.collect::<Vec<_>>();
+    fields.sort();
+    fields.dedup();
+    fields.into_iter().filter_map(move |field| {
+        let value = |entry: Option<&'a serde_json::Map<String, Value>>| entry?.get(field);
+        (value(old) != value(new)).then(|| field.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differences() {
+        let old = serde_json::json!({
+            "schema_version": 1,
+            "projects": {
+                "hyper": {
+                    "https://github.com/hyperium/hyper/pull/1": {
+                        "title": "Fix a panic",
+                        "state": "open",
+                    },
+                    "https://github.com/hyperium/hyper/pull/2": { "title": "Bump serde" },
+                    "https://github.com/hyperium/hyper/pull/3": { "title": "Add a test" },
+                },
+            },
+        });
+        let new = serde_json::json!({
+            "schema_version": 1,
+            "projects": {
+                "h2": {
+                    "https://github.com/hyperium/hyper/pull/3": { "title": "Add a test" },
+                },
+                "hyper": {
+                    "https://github.com/hyperium/hyper/pull/1": {
+                        "title": "Fix a panic",
+                        "state": "merged",
+                        "labels": ["bug"],
+                    },
+                    "https://github.com/hyperium/hyper/pull/4": { "title": "Drop MSRV" },
+                },
+            },
+        });
+
+        let differences = diff(&old.to_string(), &new.to_string()).unwrap();
+        let lines = differences
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "~ h2: Add a test <https://github.com/hyperium/hyper/pull/3> (project)",
+                "~ hyper: Fix a panic <https://github.com/hyperium/hyper/pull/1> (labels, state)",
+                "- hyper: Bump serde <https://github.com/hyperium/hyper/pull/2>",
+                "+ hyper: Drop MSRV <https://github.com/hyperium/hyper/pull/4>",
+            ]
+        );
+        assert!(diff(&new.to_string(), &new.to_string()).unwrap().is_empty());
+        assert!(diff("[]", &new.to_string()).is_err());
+    }
+}
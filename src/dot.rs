@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::{Activity, Renderer};
+
+/// Renders reports as a Graphviz graph of the projects, for a visual overview (like for a
+/// year in review)
+///
+/// Projects are sized by their number of interactions (counting each item at least once). Items
+/// that link to (or are linked from) an item of another project (see [`Entry::references`]) join
+/// their projects by an edge, which is thicker for more such links. Render it with something like
+/// `dot -Tsvg`.
+///
+/// [`Entry::references`]: crate::Entry::references
+#[derive(Debug)]
+pub struct Dot {
+    /// The name of the period (like "October 2024")
+    pub period: String,
+}
+
+impl Renderer for Dot {
+    fn render(
+        &self,
+        activity: &Activity,
+        _users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        // The projects of the items, by their reference (like `hyperium/hyper#1`)
+        let mut items = BTreeMap::new();
+        for (project, entries) in &activity.projects {
+            for html_url in entries.keys() {
+                if let Some(reference) = reference(html_url) {
+                    items.insert(reference, project.as_str());
+                }
+            }
+        }
+
+        let mut edges = BTreeMap::<(&str, &str), u64>::new();
+        for (project, entries) in &activity.projects {
+            let references = entries.values().flat_map(|entry| &entry.references);
+            for other in references.filter_map(|reference| items.get(reference.as_str())) {
+                if other != project {
+                    let edge = match project.as_str() < *other {
+                        true => (project.as_str(), *other),
+                        false => (*other, project.as_str()),
+                    };
+                    *edges.entry(edge).or_default() += 1;
+                }
+            }
+        }
+
+        writeln!(out, "graph activity {{")?;
+        writeln!(out, "  label={};", quoted(&self.period))?;
+        writeln!(out, "  node [shape=circle, fixedsize=true];")?;
+        for (project, entries) in &activity.projects {
+            let interactions = entries
+                .values()
+                .map(|entry| entry.interactions.values().sum::<u64>().max(1))
+                .sum::<u64>();
+            // The area grows with the number of interactions
+            let width = 0.6 + (interactions as f64).sqrt() * 0.3;
+            writeln!(
+                out,
+                "  {} [label={}, width={width:.2}];",
+                quoted(project),
+                quoted(&format!("{project}\n{interactions}"))
+            )?;
+        }
+        for ((a, b), links) in edges {
+            writeln!(out, "  {} -- {} [penwidth={links}];", quoted(a), quoted(b))?;
+        }
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+}
+
+/// `text` as a quoted DOT identifier
+fn quoted(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped.replace('\n', "\\n"))
+}
+
+/// The items that `text` (in `repo`, if known) refers to, as references like `hyperium/hyper#1`
+///
+/// These are links to issues, pull requests and discussions (on any host), `owner/name#1` and,
+/// within a repository, `#1`.
+pub(crate) fn references<'a>(
+    text: &'a str,
+    repo: Option<&'a str>,
+) -> impl Iterator<Item = String> + 'a {
+    let punctuation = |c: char| !c.is_alphanumeric() && !matches!(c, '#' | '/' | '-' | '_' | '.');
+    text.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '<' | '>' | '[' | ']'))
+        .map(move |word| word.trim_matches(punctuation))
+        .filter_map(move |word| {
+            if word.contains("://") {
+                return reference(word);
+            }
+            let (name, number) = word.split_once('#')?;
+            if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            match name {
+                "" => Some(format!("{}#{number}", repo?)),
+                _ => {
+                    let (owner, repo) = name.split_once('/')?;
+                    let valid = |part: &str| !part.is_empty() && !part.contains('/');
+                    (valid(owner) && valid(repo)).then(|| format!("{name}#{number}"))
+                }
+            }
+        })
+}
+
+/// The reference (like `hyperium/hyper#1`) for the link to an issue, pull request or discussion
+/// at `url`, ignoring any anchor
+fn reference(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let path = rest.split(['#', '?']).next()?;
+    let mut segments = path.split('/').skip(1);
+    let (owner, repo, kind, number) = (
+        segments.next()?,
+        segments.next()?,
+        segments.next()?,
+        segments.next()?,
+    );
+    let known = matches!(kind, "issues" | "pull" | "discussions");
+    let number = number.parse::<u64>().ok().filter(|_| known)?;
+    Some(format!("{owner}/{repo}#{number}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn graph() {
+        let found = references(
+            "Fixes #12, see hyperium/h2#3 and (https://github.com/rustls/rustls/issues/9#top). \
+             Not issue#1 or https://github.com/djc/askama/commit/abc",
+            Some("hyperium/hyper"),
+        );
+        assert_eq!(
+            found.collect::<Vec<_>>(),
+            ["hyperium/hyper#12", "hyperium/h2#3", "rustls/rustls#9"]
+        );
+
+        let mut activity = Activity::default();
+        for (project, url, references) in [
+            (
+                "hyper",
+                "https://github.com/hyperium/hyper/pull/1",
+                &["hyperium/h2#2"][..],
+            ),
+            (
+                "hyper",
+                "https://github.com/hyperium/hyper/pull/4",
+                &["hyperium/h2#2"][..],
+            ),
+            ("h2", "https://github.com/hyperium/h2/pull/2", &[][..]),
+        ] {
+            let mut entry = Entry::new("Fix panic".to_owned(), ItemKind::PullRequest);
+            entry
+                .references
+                .extend(references.iter().map(|r| r.to_string()));
+            let entries = activity.projects.entry(project.to_owned()).or_default();
+            entries.insert(url.to_owned(), entry);
+        }
+
+        let dot = Dot {
+            period: "March 2024".to_owned(),
+        };
+        let mut out = Vec::new();
+        dot.render(&activity, &[], &mut out).unwrap();
+        let graph = String::from_utf8(out).unwrap();
+        assert!(graph.starts_with("graph activity {\n  label=\"March 2024\";\n"));
+        assert!(graph.contains("  \"hyper\" [label=\"hyper\\n2\", width=1.02];\n"));
+        assert!(graph.contains("  \"h2\" -- \"hyper\" [penwidth=2];\n"));
+    }
+}
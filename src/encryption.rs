@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use aws_lc_rs::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use aws_lc_rs::pbkdf2;
+
+/// Encrypts the files of the cache at rest, with a key derived from a passphrase (see
+/// [`Collector::with_encryption()`](crate::Collector::with_encryption))
+///
+/// Files are encrypted with AES-256-GCM, under a key derived from the passphrase by PBKDF2 (with
+/// HMAC-SHA256) and a random salt stored at the start of the file. The files written in a single
+/// run share their salt, so that the (deliberately slow) derivation happens once per run rather
+/// than for every file.
+pub struct Cipher {
+    passphrase: String,
+    /// The salt for the files written by this run
+    salt: [u8; SALT_LEN],
+    /// The keys derived so far, by their salt
+    keys: Mutex<HashMap<[u8; SALT_LEN], Arc<LessSafeKey>>>,
+}
+
+impl Cipher {
+    pub fn new(passphrase: String) -> anyhow::Result<Self> {
+        if passphrase.is_empty() {
+            anyhow::bail!("empty passphrase");
+        }
+        let mut salt = [0; SALT_LEN];
+        aws_lc_rs::rand::fill(&mut salt).map_err(|_| anyhow::anyhow!("failed to generate salt"))?;
+        Ok(Self {
+            passphrase,
+            salt,
+            keys: Mutex::default(),
+        })
+    }
+
+    /// Encrypt `plain`, for [`Cipher::open()`]
+    pub(crate) fn seal(&self, plain: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        aws_lc_rs::rand::fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("failed to generate nonce"))?;
+        let mut sealed = Vec::with_capacity(HEADER_LEN + plain.len() + AES_256_GCM.tag_len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&self.salt);
+        sealed.extend_from_slice(&nonce);
+        let mut body = plain.to_vec();
+        self.key(self.salt)?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(MAGIC),
+                &mut body,
+            )
+            .map_err(|_| anyhow::anyhow!("failed to encrypt"))?;
+        sealed.extend_from_slice(&body);
+        Ok(sealed)
+    }
+
+    /// Decrypt `sealed` (as written by [`Cipher::seal()`])
+    pub(crate) fn open(&self, sealed: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if !is_sealed(sealed) || sealed.len() < HEADER_LEN {
+            anyhow::bail!("not encrypted");
+        }
+        let salt = sealed[MAGIC.len()..MAGIC.len() + SALT_LEN].try_into()?;
+        let nonce = Nonce::try_assume_unique_for_key(&sealed[MAGIC.len() + SALT_LEN..HEADER_LEN])
+            .map_err(|_| anyhow::anyhow!("invalid nonce"))?;
+        let mut body = sealed[HEADER_LEN..].to_vec();
+        let plain = self
+            .key(salt)?
+            .open_in_place(nonce, Aad::from(MAGIC), &mut body)
+            .map_err(|_| anyhow::anyhow!("failed to decrypt (is the passphrase right?)"))?;
+        let len = plain.len();
+        body.truncate(len);
+        Ok(body)
+    }
+
+    /// The key derived from the passphrase with `salt`
+    fn key(&self, salt: [u8; SALT_LEN]) -> anyhow::Result<Arc<LessSafeKey>> {
+        let mut keys = self.keys.lock().unwrap();
+        if let Some(key) = keys.get(&salt) {
+            return Ok(key.clone());
+        }
+
+        let mut key = [0; 32];
+        let iterations = NonZeroU32::new(ITERATIONS).context("no iterations")?;
+        let passphrase = self.passphrase.as_bytes();
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            &salt,
+            passphrase,
+            &mut key,
+        );
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| anyhow::anyhow!("failed to derive key"))?;
+        let key = Arc::new(LessSafeKey::new(key));
+        keys.insert(salt, key.clone());
+        Ok(key)
+    }
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cipher").finish_non_exhaustive()
+    }
+}
+
+/// Whether `data` was encrypted (by a [`Cipher`])
+pub(crate) fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Marks encrypted files (and versions their format)
+const MAGIC: &[u8] = b"tmog-events-sealed-v1\n";
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+/// As recommended by OWASP for PBKDF2 with HMAC-SHA256
+const ITERATIONS: u32 = 600_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed() {
+        let cipher = Cipher::new("correct horse battery staple".to_owned()).unwrap();
+        let sealed = cipher.seal(b"[\"hyper\"]").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(5).any(|window| window == b"hyper"));
+        assert_eq!(cipher.open(&sealed).unwrap(), b"[\"hyper\"]");
+
+        // Another run derives the key for the salt of the file again
+        let other = Cipher::new("correct horse battery staple".to_owned()).unwrap();
+        assert_eq!(other.open(&sealed).unwrap(), b"[\"hyper\"]");
+        let wrong = Cipher::new("incorrect horse".to_owned()).unwrap();
+        assert!(wrong.open(&sealed).is_err());
+        assert!(cipher.open(b"[\"hyper\"]").is_err());
+
+        let mut tampered = sealed;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(cipher.open(&tampered).is_err());
+    }
+}
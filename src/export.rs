@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{Activity, Interval, Role, Size};
+
+/// Writes the activity of many months as CSV tables partitioned by month, for loading into a
+/// data warehouse
+///
+/// Each month goes into its own directory (like `month=2024-03/`, as Hive-style partitioning
+/// expects), with an `items.csv` listing the items and a `pushes.csv` listing the commits pushed
+/// to each repository. The months written so far are recorded in `checkpoint.json`, so that an
+/// export that was interrupted picks up at the first month it didn't finish. Months that are
+/// still in progress or are missing events aren't recorded, so that they're written again (with
+/// whatever was added since) on the next run.
+#[derive(Debug)]
+pub struct Export {
+    dir: PathBuf,
+    /// The months exported so far (like `2024-03`)
+    exported: BTreeSet<String>,
+}
+
+impl Export {
+    /// Continue the export in `dir`, if one was started there
+    pub fn open(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(CHECKPOINT);
+        let exported = match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        Ok(Self {
+            dir: dir.to_owned(),
+            exported,
+        })
+    }
+
+    /// Whether the month starting at `interval` was exported already
+    pub fn contains(&self, interval: Interval) -> bool {
+        self.exported.contains(&month(interval))
+    }
+
+    /// Write the tables for the month starting at `interval`, and record it as exported if it
+    /// ended before `now` and none of its events are missing (returning whether it was recorded)
+    ///
+    /// The tables are written under temporary names first, so that a month is never left half
+    /// written.
+    pub fn write(
+        &mut self,
+        interval: Interval,
+        activity: &Activity,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        let month = month(interval);
+        let dir = self.dir.join(format!("month={month}"));
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        for (name, table) in [("items", items(activity)), ("pushes", pushes(activity))] {
+            let (path, partial) = (
+                dir.join(format!("{name}.csv")),
+                dir.join(format!("{name}.csv.partial")),
+            );
+            fs::write(&partial, table)
+                .with_context(|| format!("failed to write {}", partial.display()))?;
+            fs::rename(&partial, &path)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+
+        if interval.end > now || !activity.is_complete() {
+            return Ok(false);
+        }
+        self.exported.insert(month);
+        let path = self.dir.join(CHECKPOINT);
+        let data = serde_json::to_vec_pretty(&self.exported)?;
+        fs::write(&path, data).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(true)
+    }
+}
+
+/// The table of the items in `activity`, one row per item
+fn items(activity: &Activity) -> String {
+    let mut table = String::from(
+        "project,url,kind,title,author,users,authored,reviews,comments,state,merged_by,\
+         additions,deletions,changed_files,first,days\n",
+    );
+    for (project, entries) in &activity.projects {
+        for (html_url, entry) in entries {
+            let role = |role| {
+                let count = entry.interactions.get(&role).copied().unwrap_or_default();
+                count.to_string()
+            };
+            let size = |field: fn(&Size) -> u64| match &entry.size {
+                Some(size) => field(size).to_string(),
+                None => String::new(),
+            };
+            let users = entry.users.iter().cloned().collect::<Vec<_>>();
+            let row = [
+                project.clone(),
+                html_url.clone(),
+                name(entry.kind),
+                entry.title.clone(),
+                entry.author.clone().unwrap_or_default(),
+                users.join(" "),
+                role(Role::Author),
+                role(Role::Review),
+                role(Role::Comment),
+                entry.state.map(name).unwrap_or_default(),
+                entry.merged_by.clone().unwrap_or_default(),
+                size(|size| size.additions),
+                size(|size| size.deletions),
+                size(|size| size.changed_files),
+                entry
+                    .first
+                    .map(|first| first.to_rfc3339())
+                    .unwrap_or_default(),
+                entry.days.len().to_string(),
+            ];
+            row_to(&mut table, &row);
+        }
+    }
+    table
+}
+
+/// The table of the commits pushed in `activity`, one row per repository
+fn pushes(activity: &Activity) -> String {
+    let mut table = String::from("project,repo,commits,users,co_authors\n");
+    for (project, repos) in &activity.pushes {
+        for (repo, pushes) in repos {
+            let users = pushes.users.iter().cloned().collect::<Vec<_>>();
+            let co_authors = pushes.co_authors.iter().cloned().collect::<Vec<_>>();
+            let row = [
+                project.clone(),
+                repo.clone(),
+                pushes.commits.to_string(),
+                users.join(" "),
+                co_authors.join(" "),
+            ];
+            row_to(&mut table, &row);
+        }
+    }
+    table
+}
+
+/// Append a row of `fields` to `table`, quoting fields as RFC 4180 requires
+fn row_to(table: &mut String, fields: &[String]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            table.push(',');
+        }
+        match field.contains([',', '"', '\n', '\r']) {
+            true => write!(table, "\"{}\"", field.replace('"', "\"\"")).unwrap(),
+            false => table.push_str(field),
+        }
+    }
+    table.push('\n');
+}
+
+/// The name of `value` (like `pull_request`), as in JSON reports
+fn name(value: impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// The name of the month starting at `interval`, like `2024-03`
+fn month(interval: Interval) -> String {
+    interval.date(interval.start).format("%Y-%m").to_string()
+}
+
+const CHECKPOINT: &str = "checkpoint.json";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn partitioned() {
+        let mut activity = Activity::default();
+        let mut entry = Entry::new("Fix \"panic\", again".to_owned(), ItemKind::PullRequest);
+        entry.users.insert("djc".to_owned());
+        entry.interactions.insert(Role::Review, 2);
+        let url = "https://github.com/hyperium/hyper/pull/1".to_owned();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(url, entry);
+
+        let dir = std::env::temp_dir().join(format!("tmog-events-export-{}", std::process::id()));
+        let (february, march, april) = (
+            Interval::month("202402").unwrap(),
+            Interval::month("202403").unwrap(),
+            Interval::month("202404").unwrap(),
+        );
+        let now = DateTime::from_timestamp(1713139200, 0).unwrap();
+        let mut export = Export::open(&dir).unwrap();
+        assert!(export.write(march, &activity, now).unwrap());
+        // April is still in progress, and February is missing events
+        assert!(!export.write(april, &activity, now).unwrap());
+        activity.interrupted = true;
+        assert!(!export.write(february, &activity, now).unwrap());
+        let items = fs::read_to_string(dir.join("month=2024-03/items.csv")).unwrap();
+        let pushes = fs::read_to_string(dir.join("month=2024-03/pushes.csv")).unwrap();
+        let in_progress = dir.join("month=2024-04/items.csv").exists();
+        // The checkpoint carries over to the next run
+        let export = Export::open(&dir).unwrap();
+        let exported = [february, march, april].map(|interval| export.contains(interval));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            items.lines().nth(1).unwrap(),
+            "hyper,https://github.com/hyperium/hyper/pull/1,pull_request,\
+             \"Fix \"\"panic\"\", again\",,djc,0,2,0,,,,,,,0"
+        );
+        assert_eq!(pushes, "project,repo,commits,users,co_authors\n");
+        assert!(in_progress);
+        assert_eq!(exported, [false, true, false]);
+    }
+}
@@ -0,0 +1,141 @@
+use serde::Deserialize;
+
+use crate::{BotFilter, Event, EventType, Interval, ItemKind, ItemMeta, RepoFilter, Window};
+
+/// A step of the pipeline that decides which events and items go into the activity
+///
+/// Filters are asked about each event first (without an item, which leaves out pushes that they
+/// reject), and then about each item of the event. The built-in filters are applied in the
+/// configured order (see [`Collector::with_filter_order()`]), followed by any others (see
+/// [`Collector::with_filter()`]); the first to reject an event or item leaves it out.
+///
+/// [`Collector::with_filter_order()`]: crate::Collector::with_filter_order
+/// [`Collector::with_filter()`]: crate::Collector::with_filter
+pub trait Filter: Send + Sync {
+    /// Whether to keep the `candidate`
+    fn keeps(&self, candidate: &Candidate<'_>) -> bool;
+}
+
+/// An event, or one of its items, as seen by a [`Filter`]
+#[derive(Clone, Copy, Debug)]
+pub struct Candidate<'a> {
+    pub event: &'a Event,
+    /// The item of the event being decided on, if any
+    pub item: Option<&'a ItemMeta>,
+    pub kind: ItemKind,
+    /// The repository (`owner/name`) of the event or item, if known
+    pub repo: Option<&'a str>,
+    /// The period being collected
+    pub interval: Interval,
+}
+
+/// The built-in filters, to order them by (see [`Filter`])
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FilterKind {
+    /// The types of events to include (see `--only` and `--skip`)
+    EventTypes,
+    /// The repositories to include (see `--include` and `--exclude`)
+    Repos,
+    /// The timestamp that decides whether an item belongs to the period (see `--window`)
+    Window,
+    /// Items opened by bots (see `--exclude-bots`)
+    Bots,
+}
+
+impl FilterKind {
+    /// The order of the built-in filters, unless configured otherwise
+    pub const DEFAULT: [Self; 4] = [Self::EventTypes, Self::Repos, Self::Window, Self::Bots];
+}
+
+/// Selects events by their type (see [`Collector::with_event_types()`])
+///
+/// [`Collector::with_event_types()`]: crate::Collector::with_event_types
+#[derive(Debug, Default)]
+pub(crate) struct EventTypes {
+    /// Only include these types of events (if not empty)
+    pub(crate) only: Vec<EventType>,
+    /// Leave out these types of events
+    pub(crate) skip: Vec<EventType>,
+}
+
+impl Filter for EventTypes {
+    /// Events of unknown types are only kept if `only` is empty
+    fn keeps(&self, candidate: &Candidate<'_>) -> bool {
+        match EventType::of(candidate.event) {
+            Some(ty) => {
+                (self.only.is_empty() || self.only.contains(&ty)) && !self.skip.contains(&ty)
+            }
+            None => self.only.is_empty(),
+        }
+    }
+}
+
+impl Filter for RepoFilter {
+    fn keeps(&self, candidate: &Candidate<'_>) -> bool {
+        candidate.repo.is_none_or(|repo| self.allows(repo))
+    }
+}
+
+impl Filter for Window {
+    fn keeps(&self, candidate: &Candidate<'_>) -> bool {
+        candidate
+            .item
+            .is_none_or(|item| self.contains(item, candidate.kind, candidate.interval))
+    }
+}
+
+impl Filter for BotFilter {
+    fn keeps(&self, candidate: &Candidate<'_>) -> bool {
+        let user = candidate.item.and_then(|item| item.user.as_ref());
+        !user.is_some_and(|user| self.is_bot(&user.login))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters() {
+        let event = serde_json::from_value::<Event>(serde_json::json!({
+            "type": "IssuesEvent",
+            "repo": { "name": "hyperium/hyper" },
+        }))
+        .unwrap();
+        let item = serde_json::from_value::<ItemMeta>(serde_json::json!({
+            "html_url": "https://github.com/hyperium/hyper/issues/1",
+            "title": "Bump serde",
+            "user": { "login": "dependabot[bot]" },
+        }))
+        .unwrap();
+        let candidate = Candidate {
+            event: &event,
+            item: None,
+            kind: ItemKind::Issue,
+            repo: Some("hyperium/hyper"),
+            interval: Interval::month("202403").unwrap(),
+        };
+
+        let skip = EventTypes {
+            only: Vec::new(),
+            skip: vec![EventType::Issues],
+        };
+        assert!(!skip.keeps(&candidate));
+        assert!(EventTypes::default().keeps(&candidate));
+        let repos = RepoFilter::new(&[], &["hyperium/*".to_owned()]).unwrap();
+        assert!(!repos.keeps(&candidate));
+
+        // Bots and windows are only about items
+        let bots = BotFilter::default();
+        assert!(bots.keeps(&candidate));
+        assert!(Window::Created.keeps(&candidate));
+        let item = Candidate {
+            item: Some(&item),
+            ..candidate
+        };
+        assert!(!bots.keeps(&item));
+        assert!(!Window::Created.keeps(&item));
+        assert!(Window::Event.keeps(&item));
+    }
+}
@@ -0,0 +1,394 @@
+use futures_util::future::{join_all, BoxFuture};
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use serde_json::Value;
+
+use crate::{
+    About, Access, Asset, Cost, Details, EventSource, Fetch, Http, Interval, Pass, Planned,
+};
+
+/// Collects events from several forges at once (like GitHub, Codeberg and GitLab), for a single
+/// report
+///
+/// The events of each forge are tagged with its web URL (see [`Repo::web_url`]), so that links
+/// made from them (like those to tags and pushes) lead to the right forge. Repositories with the
+/// same name on different forges end up in the same project.
+///
+/// [`Repo::web_url`]: crate::Repo::web_url
+pub struct Forges {
+    sources: Vec<Box<dyn EventSource>>,
+    name: String,
+}
+
+impl Forges {
+    pub fn new(sources: Vec<Box<dyn EventSource>>) -> Self {
+        let names = sources.iter().map(|source| source.name());
+        let name = names.collect::<Vec<_>>().join("+");
+        Self { sources, name }
+    }
+
+    /// Merge the streams of all sources, tagging their events
+    fn merged<'a>(
+        &'a self,
+        fetch: impl Fn(&'a dyn EventSource) -> BoxStream<'a, anyhow::Result<Fetch>>,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        let streams = self.sources.iter().map(|source| {
+            let web_url = source.web_urls().swap_remove(0);
+            let tagged = fetch(source.as_ref()).filter_map(move |fetched| {
+                let fetched = match fetched {
+                    Ok(Fetch::Event(event)) => tag(&event, &web_url).map(Fetch::Event),
+                    // The sources' cursors can't be told apart, so fetching starts over instead
+                    Ok(Fetch::Cursor(_)) => return std::future::ready(None),
+                    fetched => fetched,
+                };
+                std::future::ready(Some(fetched))
+            });
+            Box::pin(tagged) as BoxStream<'a, _>
+        });
+        Box::pin(stream::select_all(streams))
+    }
+
+    /// The source whose item links `html_url` starts with
+    fn of(&self, html_url: &str) -> Option<&dyn EventSource> {
+        let source = self.sources.iter().find(|source| {
+            let web_urls = source.web_urls();
+            web_urls.iter().any(|web_url| html_url.starts_with(web_url))
+        });
+        source.map(|source| source.as_ref())
+    }
+}
+
+impl EventSource for Forges {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.web_urls())
+            .collect()
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        let keys = self.sources.iter().map(|source| source.cache_key(user));
+        keys.collect::<Vec<_>>().join("+")
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.merged(|source| source.fetch(user, interval, http))
+    }
+
+    fn received<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.merged(|source| source.received(user, interval, http))
+    }
+
+    fn snippets<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.merged(|source| source.snippets(user, interval, http))
+    }
+
+    fn notifications<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.merged(|source| source.notifications(user, interval, http))
+    }
+
+    fn search<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.merged(|source| source.search(user, interval, http))
+    }
+
+    fn details<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Details>>> {
+        match self.of(html_url) {
+            Some(source) => source.details(html_url, http),
+            None => Box::pin(async { Ok(None) }),
+        }
+    }
+
+    fn assets<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<Asset>>>> {
+        match self.of(html_url) {
+            Some(source) => source.assets(html_url, http),
+            None => Box::pin(async { Ok(None) }),
+        }
+    }
+
+    fn participants<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        match self.of(html_url) {
+            Some(source) => source.participants(html_url, http),
+            None => Box::pin(async { Ok(None) }),
+        }
+    }
+
+    /// Ask each forge in turn, since repositories are only known by their name
+    fn current_name<'a>(
+        &'a self,
+        repo: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        Box::pin(async move {
+            for source in &self.sources {
+                if let Some(name) = source.current_name(repo, http).await? {
+                    return Ok(Some(name));
+                }
+            }
+            Ok(None)
+        })
+    }
+
+    /// Ask each forge in turn, since repositories are only known by their name
+    fn about<'a>(
+        &'a self,
+        repo: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<About>>> {
+        Box::pin(async move {
+            for source in &self.sources {
+                if let Some(about) = source.about(repo, http).await? {
+                    return Ok(Some(about));
+                }
+            }
+            Ok(None)
+        })
+    }
+
+    /// Ask each forge, since the user may be a member of organizations on several
+    fn organizations<'a>(
+        &'a self,
+        user: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        Box::pin(async move {
+            let mut orgs = None::<Vec<String>>;
+            for source in &self.sources {
+                if let Some(found) = source.organizations(user, http).await? {
+                    orgs.get_or_insert_default().extend(found);
+                }
+            }
+            Ok(orgs)
+        })
+    }
+
+    fn check_access<'a>(
+        &'a self,
+        users: &'a [String],
+        private: bool,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Access>> {
+        Box::pin(async move {
+            let checks = self.sources.iter().map(|source| async move {
+                let access = source.check_access(users, private, http).await?;
+                anyhow::Ok((source.name(), access))
+            });
+            let mut access = Access::default();
+            for checked in join_all(checks).await {
+                let (name, checked) = checked?;
+                let warnings = checked.warnings.into_iter();
+                access
+                    .warnings
+                    .extend(warnings.map(|warning| format!("{name}: {warning}")));
+                // The first token to expire is the one to warn about
+                access.expires_at = match (access.expires_at, checked.expires_at) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            Ok(access)
+        })
+    }
+
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            let costs = self
+                .sources
+                .iter()
+                .map(|source| source.estimate(user, interval, http));
+            let mut cost = Cost {
+                requests: 0,
+                exact: true,
+                bytes_processed: None,
+            };
+            for estimated in join_all(costs).await {
+                let estimated = estimated?;
+                cost.requests += estimated.requests;
+                cost.exact &= estimated.exact;
+                if let Some(bytes) = estimated.bytes_processed {
+                    *cost.bytes_processed.get_or_insert(0) += bytes;
+                }
+            }
+            Ok(cost)
+        })
+    }
+
+    fn plan(&self, user: &str, interval: Interval, pass: Pass, http: &Http) -> Vec<Planned> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.plan(user, interval, pass, http))
+            .collect()
+    }
+}
+
+/// Add the `web_url` of the forge to the repository of `event`, if it has one
+fn tag(event: &str, web_url: &str) -> anyhow::Result<String> {
+    let mut event = serde_json::from_str::<Value>(event)?;
+    if let Some(Value::Object(repo)) = event.get_mut("repo") {
+        repo.insert("web_url".to_owned(), Value::from(web_url));
+    }
+    Ok(serde_json::to_string(&event)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::{Collector, Window};
+
+    /// A forge with a single tag in `hyperium/hyper` (and a push, on Codeberg)
+    struct Forge(&'static str);
+
+    impl EventSource for Forge {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn web_urls(&self) -> Vec<String> {
+            vec![format!("https://{}", self.0)]
+        }
+
+        fn cache_key(&self, user: &str) -> String {
+            format!("{user}@{}", self.0)
+        }
+
+        fn fetch<'a>(
+            &'a self,
+            _user: &'a str,
+            _interval: Interval,
+            _http: &'a Http,
+        ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+            let event = |kind: &str, payload: Value| {
+                let mut event = serde_json::json!({
+                    "type": kind,
+                    "repo": { "name": "hyperium/hyper" },
+                    "public": true,
+                    "created_at": "2024-03-01T12:00:00Z",
+                });
+                event
+                    .as_object_mut()
+                    .unwrap()
+                    .extend(payload.as_object().unwrap().clone());
+                Ok(Fetch::Event(event.to_string()))
+            };
+            let tag = serde_json::json!({ "ref": format!("v1-{}", self.0), "ref_type": "tag" });
+            let push = serde_json::json!({ "distinct_size": 1, "before": "a", "head": "b" });
+            let mut events = vec![
+                event("CreateEvent", tag),
+                Ok(Fetch::Cursor("page=2".to_owned())),
+            ];
+            if self.0 == "codeberg.org" {
+                events.push(event("PushEvent", push));
+            }
+            Box::pin(stream::iter(events))
+        }
+
+        fn estimate<'a>(
+            &'a self,
+            _user: &'a str,
+            _interval: Interval,
+            _http: &'a Http,
+        ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+            Box::pin(async {
+                Ok(Cost {
+                    requests: 2,
+                    exact: true,
+                    bytes_processed: None,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn merged() {
+        let forges = Forges::new(vec![
+            Box::new(Forge("github.com")),
+            Box::new(Forge("codeberg.org")),
+        ]);
+        assert_eq!(forges.name(), "github.com+codeberg.org");
+        assert_eq!(forges.cache_key("djc"), "djc@github.com+djc@codeberg.org");
+        let (interval, http) = (
+            Interval::month("202403").unwrap(),
+            Http::new(reqwest::Client::new(), 1, 1),
+        );
+        let fetched = forges
+            .fetch("djc", interval, &http)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        // Cursors are left out
+        assert_eq!(fetched.len(), 3);
+        assert_eq!(
+            forges
+                .estimate("djc", interval, &http)
+                .await
+                .unwrap()
+                .requests,
+            4
+        );
+
+        let collector =
+            Collector::new(Box::new(forges), reqwest::Client::new(), 1, 1).with_cache(false);
+        let activity = collector
+            .collect(interval, &["djc".to_owned()], Window::default())
+            .await
+            .unwrap();
+        // Tags link to the forge they were created on
+        let tags = activity.projects["hyper"].keys().collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            [
+                "https://codeberg.org/hyperium/hyper/releases/tag/v1-codeberg.org",
+                "https://github.com/hyperium/hyper/releases/tag/v1-github.com",
+            ]
+        );
+        let pushes = &activity.pushes["hyper"]["hyperium/hyper"];
+        assert_eq!(pushes.web_url.as_deref(), Some("https://codeberg.org"));
+    }
+}
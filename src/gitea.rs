@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+
+use async_stream::try_stream;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::github::next_link;
+use crate::{Cost, Event, EventSource, Fetch, Http, Interval, ItemMeta, Review, User};
+
+/// The activity feed of the Gitea or Forgejo (e.g. Codeberg) instance at `host`
+///
+/// Activities only reference items by repository and number, so each item is looked up via the
+/// issues API once and normalized into the same shape as GitHub event payloads.
+pub struct Gitea {
+    host: String,
+    token: Option<String>,
+}
+
+impl Gitea {
+    pub fn new(host: String, token: Option<String>) -> Self {
+        Self { host, token }
+    }
+
+    fn activities_url(&self, user: &str) -> String {
+        format!(
+            "https://{}/api/v1/users/{user}/activities/feeds?only-performed-by=true&limit={PAGE_SIZE}",
+            self.host
+        )
+    }
+
+    fn get(&self, http: &Http, url: &str) -> reqwest::RequestBuilder {
+        let req = http.client().get(url);
+        match &self.token {
+            Some(token) => req.header("Authorization", format!("token {token}")),
+            None => req,
+        }
+    }
+}
+
+impl EventSource for Gitea {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec![format!("https://{}", self.host)]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        format!("{user}@{}", self.host)
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let mut next = Some(self.activities_url(user));
+            let mut items = HashMap::<(String, u64), Issue>::new();
+            let mut pages = 0;
+            'pages: while let Some(url) = next.take() {
+                debug!(url, "fetching activities");
+                let (page, headers) = http.page::<Activity>(&url, self.get(http, &url)).await?;
+                next = next_link(&headers);
+
+                if let Some(oldest) = page.iter().map(|event| event.created).min() {
+                    http.metrics().reached(oldest);
+                }
+
+                // Activities are returned newest first
+                for activity in page {
+                    if activity.created < interval.start {
+                        break 'pages;
+                    } else if activity.created >= interval.end {
+                        continue;
+                    }
+
+                    // Whether it is a pull request, and the equivalent GitHub event and action
+                    let (is_pull, event_type, action) = match activity.op_type.as_str() {
+                        "create_issue" => (false, ISSUES, "opened"),
+                        "comment_issue" => (false, COMMENT, "created"),
+                        "close_issue" => (false, ISSUES, "closed"),
+                        "reopen_issue" => (false, ISSUES, "reopened"),
+                        "create_pull_request" => (true, PULL, "opened"),
+                        "comment_pull" => (true, COMMENT, "created"),
+                        "merge_pull_request"
+                        | "auto_merge_pull_request"
+                        | "close_pull_request" => (true, PULL, "closed"),
+                        "reopen_pull_request" => (true, PULL, "reopened"),
+                        "approve_pull_request"
+                        | "reject_pull_request"
+                        | "pull_review_dismissed" => (true, REVIEW, "submitted"),
+                        "pull_request_ready_for_review" => (true, PULL, "ready_for_review"),
+                        op_type => {
+                            debug!(op_type, "skipping activity");
+                            continue;
+                        }
+                    };
+
+                    let review = match activity.op_type.as_str() {
+                        "approve_pull_request" => Some("approved"),
+                        "reject_pull_request" => Some("changes_requested"),
+                        _ => None,
+                    };
+
+                    // The content starts with the item number for all of the above
+                    let (Some(repo), Some(number)) = (
+                        activity.repo,
+                        activity
+                            .content
+                            .split('|')
+                            .next()
+                            .and_then(|number| number.parse::<u64>().ok()),
+                    ) else {
+                        continue;
+                    };
+
+                    let public = !repo.private;
+                    let key = (repo.full_name, number);
+                    let issue = match items.get(&key) {
+                        Some(issue) => issue,
+                        None => {
+                            let url = format!(
+                                "https://{}/api/v1/repos/{}/issues/{number}",
+                                self.host, key.0
+                            );
+                            let _permit = http.acquire(&url).await?;
+                            let issue = http
+                                .send(self.get(http, &url))
+                                .await?
+                                .json::<Issue>()
+                                .await?;
+                            items.entry(key).or_insert(issue)
+                        }
+                    };
+
+                    let item = ItemMeta {
+                        html_url: issue.html_url.clone(),
+                        title: issue.title.clone(),
+                        created_at: Some(issue.created_at),
+                        closed_at: issue.closed_at,
+                        merged_at: issue.pull_request.as_ref().and_then(|pr| pr.merged_at),
+                        node_id: None,
+                        updated_at: None,
+                        body: None,
+                        user: issue.user.clone(),
+                        author_association: None,
+                        pull_request: None,
+                    };
+
+                    yield Fetch::event(&match is_pull {
+                        true => Event {
+                            kind: Some(event_type.to_owned()),
+                            action: Some(action.to_owned()),
+                            created_at: Some(activity.created),
+                            public: Some(public),
+                            review: review.map(|state| Review {
+                                state: state.to_owned(),
+                                html_url: None,
+                                id: None,
+                            }),
+                            issue: None,
+                            pull_request: Some(item),
+                            ..Default::default()
+                        },
+                        false => Event {
+                            kind: Some(event_type.to_owned()),
+                            action: Some(action.to_owned()),
+                            created_at: Some(activity.created),
+                            public: Some(public),
+                            issue: Some(item),
+                            pull_request: None,
+                            ..Default::default()
+                        },
+                    })?;
+                }
+
+                pages += 1;
+                if next.is_some() && http.capped(pages) {
+                    yield Fetch::Incomplete { total: None };
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Estimate the number of requests from the first page of activities
+    ///
+    /// Besides the activity pages, one request is needed per item; the number of items on the
+    /// first page is used as a lower bound.
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            let url = self.activities_url(user);
+            debug!(url, "probing activities");
+            let _permit = http.acquire(&url).await?;
+            let rsp = http.send(self.get(http, &url)).await?;
+            let total = rsp
+                .headers()
+                .get("x-total-count")
+                .and_then(|total| total.to_str().ok()?.parse::<u64>().ok());
+            let activities = rsp.json::<Vec<Activity>>().await?;
+            let reached_start = activities
+                .last()
+                .is_some_and(|activity| activity.created < interval.start);
+            let items = activities
+                .iter()
+                .filter(|activity| interval.contains(activity.created))
+                .filter_map(|activity| {
+                    let repo = activity.repo.as_ref()?;
+                    Some((&repo.full_name, activity.content.split('|').next()?))
+                })
+                .collect::<HashSet<_>>();
+
+            let pages = match (reached_start, total) {
+                (false, Some(total)) => total.div_ceil(PAGE_SIZE),
+                _ => 1,
+            };
+
+            Ok(Cost {
+                requests: pages.max(1) + items.len() as u64,
+                exact: false,
+                bytes_processed: None,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Activity {
+    op_type: String,
+    repo: Option<Repository>,
+    #[serde(default)]
+    content: String,
+    created: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    full_name: String,
+    private: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    html_url: String,
+    title: String,
+    created_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    user: Option<User>,
+    pull_request: Option<PullRequestMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestMeta {
+    merged_at: Option<DateTime<Utc>>,
+}
+
+const SOURCE: &str = "gitea";
+const PAGE_SIZE: u64 = 50;
+const ISSUES: &str = "IssuesEvent";
+const PULL: &str = "PullRequestEvent";
+const REVIEW: &str = "PullRequestReviewEvent";
+const COMMENT: &str = "IssueCommentEvent";
@@ -0,0 +1,1523 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_stream::try_stream;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, ACCEPT, LINK};
+use reqwest::{Method, StatusCode};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use crate::urls::{UrlRule, UrlRules};
+use crate::{
+    About, Access, Asset, Cost, Details, EventSource, Fetch, Gone, Http, Interval, Pass, Planned,
+    Size, State,
+};
+
+/// The GitHub REST events API, which also works for GitHub Enterprise Server
+///
+/// The events API only retains recent events (at most 300, from the last 90 days). If the feed
+/// runs out before reaching the start of the interval, the result is marked as partial.
+pub struct GitHub {
+    api_url: String,
+    web_url: String,
+    host: String,
+    tokens: Tokens,
+    /// Maps the API URLs of items to their web URLs and back
+    urls: UrlRules,
+}
+
+impl GitHub {
+    /// Use the API at `api_url` (`https://api.github.com` or `https://HOST/api/v3`)
+    pub fn new(api_url: String, token: Option<String>) -> anyhow::Result<Self> {
+        let url = reqwest::Url::parse(&api_url)?;
+        Ok(Self {
+            web_url: web_url(&url)?,
+            host: url.host_str().unwrap_or_default().to_owned(),
+            api_url: api_url.trim_end_matches('/').to_owned(),
+            tokens: Tokens {
+                tokens: token.into_iter().collect(),
+                current: AtomicUsize::new(0),
+            },
+            urls: UrlRules::github(Vec::new()),
+        })
+    }
+
+    /// Use `web_url` for item links, instead of the one guessed from the API URL (for GitHub
+    /// Enterprise Server hosts that serve their API elsewhere)
+    pub fn with_web_url(mut self, web_url: String) -> Self {
+        self.web_url = web_url;
+        self
+    }
+
+    /// Map the API URLs of items to their web URLs (and back) by these `rules` first, for the
+    /// items that payloads don't give the web URL of
+    pub fn with_url_rules(mut self, rules: Vec<UrlRule>) -> Self {
+        self.urls = UrlRules::github(rules);
+        self
+    }
+
+    /// Switch to the next of these `tokens` (after the one passed to [`GitHub::new()`]) when a
+    /// token runs out of rate limit, for backfills that need more requests than a token allows
+    pub fn with_tokens(mut self, tokens: Vec<String>) -> Self {
+        self.tokens.tokens.extend(tokens);
+        self
+    }
+
+    /// Create a secret gist with a file called `name` containing `content`, or update the gist
+    /// of the authenticated user that has a file of that name already
+    ///
+    /// Returns the web URL of the gist.
+    pub async fn publish_gist(
+        &self,
+        name: &str,
+        content: &str,
+        description: &str,
+        http: &Http,
+    ) -> anyhow::Result<String> {
+        // Anonymously, this would list all public gists
+        if self.tokens.tokens.is_empty() {
+            anyhow::bail!("publishing gists needs a GitHub token (use `github_token`)");
+        }
+
+        let gists = format!("{}/gists?per_page=100", self.api_url);
+        let existing = self.paged::<Gist>(gists, http).await?;
+        let existing = existing.iter().find(|gist| gist.files.contains_key(name));
+
+        let files = serde_json::json!({ name: { "content": content } });
+        let req = match existing {
+            Some(gist) => {
+                let url = format!("{}/gists/{}", self.api_url, gist.id);
+                info!(url, "updating gist");
+                self.request(http, Method::PATCH, &url, JSON)
+                    .json(&serde_json::json!({ "description": description, "files": files }))
+            }
+            None => {
+                let url = format!("{}/gists", self.api_url);
+                info!(url, "creating gist");
+                self.request(http, Method::POST, &url, JSON)
+                    .json(&serde_json::json!({
+                        "description": description,
+                        "files": files,
+                        "public": false,
+                    }))
+            }
+        };
+
+        let _permit = http.acquire(&self.api_url).await?;
+        let gist = req.send().await?.error_for_status()?.json::<Gist>().await?;
+        Ok(gist.html_url)
+    }
+
+    /// Open a pull request from `head` to `base` in `repo` (`owner/name`), unless one is open
+    ///
+    /// Returns the web URL of the (existing) pull request.
+    pub async fn pull_request(
+        &self,
+        repo: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        http: &Http,
+    ) -> anyhow::Result<String> {
+        if self.tokens.tokens.is_empty() {
+            anyhow::bail!("opening pull requests needs a GitHub token (use `github_token`)");
+        }
+
+        let owner = repo.split('/').next().unwrap_or(repo);
+        let pulls = format!("{}/repos/{repo}/pulls", self.api_url);
+        let open = format!("{pulls}?state=open&head={owner}:{head}&base={base}");
+        if let Some(pull) = self.paged::<Pull>(open, http).await?.into_iter().next() {
+            return Ok(pull.html_url);
+        }
+
+        info!(url = pulls, "opening pull request");
+        let _permit = http.acquire(&self.api_url).await?;
+        let pull = self
+            .request(http, Method::POST, &pulls, JSON)
+            .json(&serde_json::json!({ "title": title, "head": head, "base": base }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Pull>()
+            .await?;
+        Ok(pull.html_url)
+    }
+
+    /// The number of contributions that the contribution calendar on `user`'s profile counts
+    /// during `interval` (which mustn't span more than a year)
+    ///
+    /// The calendar counts commits to default branches, issues and pull requests opened and
+    /// reviews, but (unlike the events feed) only in the repositories it can see, including
+    /// private ones if the user chose to count those.
+    pub async fn contributions(
+        &self,
+        user: &str,
+        interval: Interval,
+        http: &Http,
+    ) -> anyhow::Result<u64> {
+        // GraphQL is only available to authenticated users
+        if self.tokens.tokens.is_empty() {
+            anyhow::bail!("the contribution calendar needs a GitHub token (use `github_token`)");
+        }
+
+        debug!(user, "querying contribution calendar");
+        let variables = serde_json::json!({
+            "login": user,
+            "from": interval.start,
+            "to": interval.end,
+        });
+        match self
+            .graphql::<CalendarData>(CALENDAR, variables, http)
+            .await?
+        {
+            Some(CalendarData { user: Some(user) }) => Ok(user
+                .contributions_collection
+                .contribution_calendar
+                .total_contributions),
+            _ => anyhow::bail!("no such user: {user}"),
+        }
+    }
+
+    /// Run the GraphQL `query` with `variables`, failing if the response has any errors
+    async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+        http: &Http,
+    ) -> anyhow::Result<Option<T>> {
+        let url = match self.api_url.strip_suffix("/v3") {
+            Some(api) => format!("{api}/graphql"),
+            None => format!("{}/graphql", self.api_url),
+        };
+        let _permit = http.acquire(&url).await?;
+        let rsp = self
+            .request(http, Method::POST, &url, JSON)
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<GraphQl<T>>()
+            .await?;
+        match (rsp.data, rsp.errors) {
+            (_, Some(errors)) if !errors.is_empty() => {
+                let messages = errors.into_iter().map(|error| error.message);
+                let messages = messages.collect::<Vec<_>>();
+                anyhow::bail!("GraphQL query failed: {}", messages.join("; "))
+            }
+            (data, _) => Ok(data),
+        }
+    }
+
+    /// When `tag` was made in `repo` (`owner/name`), as the time its commit was committed
+    pub async fn tagged(
+        &self,
+        repo: &str,
+        tag: &str,
+        http: &Http,
+    ) -> anyhow::Result<DateTime<Utc>> {
+        // Any ref will do, like a tag
+        let url = format!("{}/repos/{repo}/commits/{tag}", self.api_url);
+        debug!(url, "looking up tag");
+        let _permit = http.acquire(&url).await?;
+        let tagged = self.send(http, &url, JSON).await?;
+        Ok(tagged.json::<Tagged>().await?.commit.committer.date)
+    }
+
+    /// The rate limits of the (authenticated) user, by resource (like `core`, `search` and
+    /// `graphql`)
+    ///
+    /// Querying the rate limits doesn't count against them.
+    pub async fn rate_limits(&self, http: &Http) -> anyhow::Result<BTreeMap<String, RateLimit>> {
+        let url = format!("{}/rate_limit", self.api_url);
+        debug!(url, "querying rate limits");
+        let _permit = http.acquire(&url).await?;
+        let rsp = self.send(http, &url, JSON).await?;
+        Ok(rsp.json::<RateLimits>().await?.resources)
+    }
+
+    /// Get `url` as the given media type (`accept`), switching tokens if the one in use runs out
+    /// of rate limit (see [`GitHub::with_tokens()`])
+    async fn send(
+        &self,
+        http: &Http,
+        url: &str,
+        accept: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let token = self.tokens.current();
+        let rsp = http
+            .send(self.request_with(token, http, Method::GET, url, accept))
+            .await?;
+        self.tokens.observe(token, rsp.headers());
+        Ok(rsp)
+    }
+
+    fn request(
+        &self,
+        http: &Http,
+        method: Method,
+        url: &str,
+        accept: &str,
+    ) -> reqwest::RequestBuilder {
+        self.request_with(self.tokens.current(), http, method, url, accept)
+    }
+
+    /// Build a request authenticated with the token at index `token` (if any)
+    fn request_with(
+        &self,
+        token: usize,
+        http: &Http,
+        method: Method,
+        url: &str,
+        accept: &str,
+    ) -> reqwest::RequestBuilder {
+        let req = http
+            .client()
+            .request(method, url)
+            .header(ACCEPT, accept)
+            .header("X-GitHub-Api-Version", API_VERSION);
+        match self.tokens.tokens.get(token) {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+}
+
+impl GitHub {
+    /// Tell whether the item at `url` is [`Gone`], if looking it up failed with `error`
+    ///
+    /// Deleted items are gone (`410`), as are those not found (`404`), in which case their
+    /// repository is looked up to tell whether it's gone as well.
+    async fn gone(&self, url: &str, error: anyhow::Error, http: &Http) -> anyhow::Error {
+        let status = |error: &anyhow::Error| {
+            let error = error
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<reqwest::Error>());
+            error.and_then(reqwest::Error::status)
+        };
+        match status(&error) {
+            Some(StatusCode::GONE) => return Gone::Item.into(),
+            Some(StatusCode::NOT_FOUND) => {}
+            _ => return error,
+        }
+
+        // Like `/repos/{owner}/{repo}` for `/repos/{owner}/{repo}/issues/{number}`
+        let Some(repo) = url.rsplitn(3, '/').nth(2) else {
+            return error;
+        };
+        match self.send(http, repo, JSON).await {
+            Ok(_) => Gone::Item.into(),
+            Err(repo_error) if status(&repo_error) == Some(StatusCode::NOT_FOUND) => {
+                Gone::Repository.into()
+            }
+            Err(_) => error,
+        }
+    }
+
+    /// The first page of the events of `user`, or of the repository if it's named `owner/name`
+    /// (see [`Collector::follow()`](crate::Collector::follow))
+    fn events_url(&self, user: &str, http: &Http) -> String {
+        let feed = match user.contains('/') {
+            true => "repos",
+            false => "users",
+        };
+        format!(
+            "{}/{feed}/{user}/events?per_page={}",
+            self.api_url, http.per_page
+        )
+    }
+
+    /// The first page of the search for issues and pull requests involving `user` that were
+    /// updated within `interval`
+    fn search_url(&self, user: &str, interval: Interval) -> anyhow::Result<String> {
+        let (start, end) = (
+            interval.start.format("%Y-%m-%dT%H:%M:%SZ"),
+            interval.end.format("%Y-%m-%dT%H:%M:%SZ"),
+        );
+        let mut url = reqwest::Url::parse(&format!("{}/search/issues", self.api_url))?;
+        url.query_pairs_mut()
+            .append_pair("q", &format!("involves:{user} updated:{start}..{end}"))
+            .append_pair("per_page", "100");
+        Ok(url.to_string())
+    }
+
+    /// Collect all values from a paginated API response
+    async fn paged<T: DeserializeOwned>(&self, url: String, http: &Http) -> anyhow::Result<Vec<T>> {
+        let (mut values, mut next) = (Vec::new(), Some(url));
+        while let Some(url) = next.take() {
+            let (page, headers) = self.page(&url, JSON, http).await?;
+            values.extend(page);
+            next = next_link(&headers);
+        }
+
+        Ok(values)
+    }
+
+    /// Fetch a single page of the given media type (`accept`), along with the response headers
+    async fn page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        accept: &str,
+        http: &Http,
+    ) -> anyhow::Result<(Vec<T>, HeaderMap)> {
+        debug!(url, "fetching page");
+        let _permit = http.acquire(url).await?;
+        let rsp = self.send(http, url, accept).await?;
+        let headers = rsp.headers().clone();
+        Ok((rsp.json().await?, headers))
+    }
+
+    /// The web URL of the item at the API URL `url`, if a rule maps it (see [`UrlRules`])
+    fn html_url(&self, url: &str) -> Option<String> {
+        self.urls.web(url, &self.api_url, &self.web_url)
+    }
+
+    /// Fetch the events within `interval` from the feed at `url`, and the pages following it
+    fn events<'a>(
+        &'a self,
+        url: String,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        let fetch = move |url: String| async move {
+            debug!(url, "fetching events");
+            let token = self.tokens.current();
+            let request = self.request_with(token, http, Method::GET, &url, JSON);
+            let (events, headers) = http.page::<RestEvent>(&url, request).await?;
+            self.tokens.observe(token, &headers);
+            Ok::<_, anyhow::Error>((events, headers))
+        };
+        Box::pin(try_stream! {
+            let mut next = Some(url);
+            // The pages following the first, fetched ahead (see `Http::prefetch`)
+            let mut ahead = None::<BoxStream<'_, anyhow::Result<(Vec<RestEvent>, HeaderMap)>>>;
+            let (mut reached_start, mut pages) = (false, 0);
+            loop {
+                let (page, headers) = match (&mut ahead, next.take()) {
+                    (Some(ahead), _) => match ahead.next().await {
+                        Some(page) => page?,
+                        None => break,
+                    },
+                    (None, Some(url)) => fetch(url).await?,
+                    (None, None) => break,
+                };
+                next = next_link(&headers);
+                pages += 1;
+                if let (true, Some(next), Some(last)) =
+                    (ahead.is_none() && http.prefetch > 1, &next, link(&headers, "last"))
+                {
+                    // Otherwise, keep following the links
+                    let mut numbered = numbered(next, &last);
+                    if let Some(max) = http.max_pages {
+                        numbered.truncate(max.saturating_sub(pages));
+                    }
+                    if !numbered.is_empty() {
+                        let numbered = futures_util::stream::iter(numbered).map(fetch);
+                        ahead = Some(Box::pin(numbered.buffered(http.prefetch)));
+                    }
+                }
+
+                if let Some(oldest) = page.iter().map(|event| event.created_at).min() {
+                    http.metrics().reached(oldest);
+                }
+
+                // Events are returned newest first
+                for event in page {
+                    if event.created_at < interval.start {
+                        reached_start = true;
+                        break;
+                    } else if event.created_at < interval.end {
+                        yield Fetch::Event(event.normalize());
+                    }
+                }
+
+                if reached_start || http.capped(pages) {
+                    break;
+                } else if let Some(next) = &next {
+                    yield Fetch::Cursor(next.clone());
+                }
+            }
+
+            if !reached_start {
+                yield Fetch::Incomplete { total: None };
+            }
+        })
+    }
+}
+
+impl EventSource for GitHub {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec![self.web_url.clone()]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        format!("{user}@{}", self.host)
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        self.events(self.events_url(user, http), interval, http)
+    }
+
+    /// The cursor is the link to the next page of events
+    fn resume<'a>(
+        &'a self,
+        _user: &'a str,
+        interval: Interval,
+        cursor: String,
+        http: &'a Http,
+    ) -> Option<BoxStream<'a, anyhow::Result<Fetch>>> {
+        Some(self.events(cursor, interval, http))
+    }
+
+    /// Find forks and stars of repositories owned by `user`
+    ///
+    /// There is no feed for these, so the forks and stargazers of each repository are listed.
+    fn received<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let url = format!("{}/users/{user}/repos?type=owner&per_page=100", self.api_url);
+            for repo in self.paged::<Repository>(url, http).await? {
+                if repo.fork {
+                    continue;
+                }
+
+                let mut next = match repo.forks_count {
+                    0 => None,
+                    _ => Some(format!(
+                        "{}/repos/{}/forks?sort=newest&per_page=100",
+                        self.api_url, repo.full_name
+                    )),
+                };
+                'forks: while let Some(url) = next.take() {
+                    let (forks, headers) = self.page::<Fork>(&url, JSON, http).await?;
+                    next = next_link(&headers);
+                    for fork in forks {
+                        if fork.created_at < interval.start {
+                            break 'forks;
+                        } else if fork.created_at >= interval.end {
+                            continue;
+                        }
+
+                        let payload = serde_json::json!({
+                            "forkee": { "full_name": fork.full_name, "html_url": fork.html_url },
+                        });
+                        yield Fetch::Event(normalize(
+                            "ForkEvent",
+                            Some(&repo.full_name),
+                            fork.created_at,
+                            payload,
+                        ));
+                    }
+                }
+
+                if repo.stargazers_count == 0 {
+                    continue;
+                }
+
+                // Stargazers are listed oldest first, so walk back from the last page
+                let url = format!(
+                    "{}/repos/{}/stargazers?per_page=100",
+                    self.api_url, repo.full_name
+                );
+                let (mut stars, mut headers) = self.page::<Star>(&url, STAR_JSON, http).await?;
+                if let Some(last) = link(&headers, "last") {
+                    (stars, headers) = self.page::<Star>(&last, STAR_JSON, http).await?;
+                }
+
+                loop {
+                    let mut reached_start = false;
+                    for star in stars.iter().rev() {
+                        if star.starred_at < interval.start {
+                            reached_start = true;
+                            break;
+                        } else if star.starred_at < interval.end {
+                            let payload = serde_json::json!({ "action": "started" });
+                            yield Fetch::Event(normalize(
+                                "WatchEvent",
+                                Some(&repo.full_name),
+                                star.starred_at,
+                                payload,
+                            ));
+                        }
+                    }
+
+                    match (reached_start, link(&headers, "prev")) {
+                        (false, Some(prev)) => {
+                            (stars, headers) = self.page::<Star>(&prev, STAR_JSON, http).await?;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        })
+    }
+
+    /// List the gists of `user` created or updated within `interval`
+    fn snippets<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let since = interval.start.format("%Y-%m-%dT%H:%M:%SZ");
+            let url = format!("{}/users/{user}/gists?since={since}&per_page=100", self.api_url);
+            for gist in self.paged::<Gist>(url, http).await? {
+                // Gists updated after the interval may or may not have been updated within it
+                if !interval.contains(gist.created_at) && !interval.contains(gist.updated_at) {
+                    continue;
+                }
+
+                // Fall back to the (first) file name if there's no description
+                let title = match gist.description.filter(|text| !text.is_empty()) {
+                    Some(description) => description,
+                    None => gist.files.into_keys().next().unwrap_or_default(),
+                };
+                let payload = serde_json::json!({
+                    "gist": {
+                        "html_url": gist.html_url,
+                        "title": title,
+                        "created_at": gist.created_at,
+                    },
+                });
+                yield Fetch::Event(normalize("GistEvent", None, gist.updated_at, payload));
+            }
+        })
+    }
+
+    /// Notifications are only listed for the user the token belongs to, so other users get none
+    fn notifications<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let url = format!("{}/user", self.api_url);
+            let login = match self.tokens.tokens.is_empty() {
+                true => None,
+                false => {
+                    let _permit = http.acquire(&url).await?;
+                    Some(self.send(http, &url, JSON).await?.json::<User>().await?.login)
+                }
+            };
+            let threads = match login {
+                Some(login) if login.eq_ignore_ascii_case(user) => {
+                    let (since, before) = (
+                        interval.start.format("%Y-%m-%dT%H:%M:%SZ"),
+                        interval.end.format("%Y-%m-%dT%H:%M:%SZ"),
+                    );
+                    let url = format!(
+                        "{}/notifications?all=true&participating=true&since={since}\
+                         &before={before}&per_page=100",
+                        self.api_url
+                    );
+                    self.paged::<Thread>(url, http).await?
+                }
+                _ => {
+                    debug!(user, "not fetching notifications for another user");
+                    Vec::new()
+                }
+            };
+
+            for thread in threads {
+                // Other reasons (like commenting or authoring) generate events of their own
+                if !NOTIFIED.contains(&thread.reason.as_str()) {
+                    continue;
+                }
+                let key = match thread.subject.kind.as_str() {
+                    "PullRequest" => "pull_request",
+                    "Issue" => "issue",
+                    _ => continue,
+                };
+                let api_url = thread.subject.url.as_deref();
+                let html_url = thread
+                    .subject
+                    .html_url
+                    .or_else(|| api_url.and_then(|url| self.html_url(url)));
+                let Some(html_url) = html_url else {
+                    continue;
+                };
+
+                let payload = serde_json::json!({
+                    "action": thread.reason,
+                    "public": !thread.repository.private,
+                    key: { "html_url": html_url, "title": thread.subject.title },
+                });
+                let (repo, time) = (thread.repository.full_name.as_str(), thread.updated_at);
+                yield Fetch::Event(normalize("NotificationEvent", Some(repo), time, payload));
+            }
+        })
+    }
+
+    /// The search API only returns the first 1000 results, so a query matching more yields
+    /// those as incomplete
+    fn search<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let mut next = Some(self.search_url(user, interval)?);
+            while let Some(url) = next.take() {
+                debug!(url, "searching");
+                let _permit = http.acquire(&url).await?;
+                let rsp = self.send(http, &url, JSON).await?;
+                next = next_link(rsp.headers());
+                let results = rsp.json::<SearchResults>().await?;
+                if results.incomplete_results || results.total_count > 1000 {
+                    yield Fetch::Incomplete { total: Some(results.total_count) };
+                }
+
+                for found in results.items {
+                    let prefix = format!("{}/repos/", self.api_url);
+                    let Some(repo) = found.repository_url.strip_prefix(&prefix) else {
+                        continue;
+                    };
+                    let item = serde_json::json!({
+                        "html_url": found.html_url,
+                        "title": found.title,
+                        "created_at": found.created_at,
+                        "closed_at": found.closed_at,
+                        "merged_at": found.pull_request.as_ref().and_then(|pull| pull.merged_at),
+                        "user": found.user.map(|user| serde_json::json!({ "login": user.login })),
+                    });
+                    let key = match found.pull_request {
+                        Some(_) => "pull_request",
+                        None => "issue",
+                    };
+                    let (payload, time) = (serde_json::json!({ key: item }), found.updated_at);
+                    yield Fetch::Event(normalize("SearchEvent", Some(repo), time, payload));
+                }
+            }
+        })
+    }
+
+    fn details<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Details>>> {
+        Box::pin(async move {
+            let Some(url) = self.urls.api(html_url, &self.web_url, &self.api_url) else {
+                return Ok(None);
+            };
+
+            debug!(url, "fetching details");
+            let _permit = http.acquire(&url).await?;
+            // Transferred items are redirected to, unless they can't be seen
+            let rsp = match self.send(http, &url, JSON).await {
+                Ok(rsp) => rsp,
+                Err(error) => return Err(self.gone(&url, error, http).await),
+            };
+            let item = rsp.json::<Item>().await?;
+            let state = match (item.state.as_str(), item.merged_at, item.state_reason) {
+                (_, Some(_), _) => State::Merged,
+                ("closed", None, Some(reason)) if reason == "completed" => State::Completed,
+                ("closed", None, _) => State::Closed,
+                _ => State::Open,
+            };
+            Ok(Some(Details {
+                state,
+                labels: item.labels.into_iter().map(|label| label.name).collect(),
+                merged_by: item.merged_by.map(|user| user.login),
+                size: match (item.additions, item.deletions, item.changed_files) {
+                    (Some(additions), Some(deletions), Some(changed_files)) => Some(Size {
+                        additions,
+                        deletions,
+                        changed_files,
+                    }),
+                    _ => None,
+                },
+            }))
+        })
+    }
+
+    fn assets<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<Asset>>>> {
+        Box::pin(async move {
+            // Like `https://github.com/djc/askama/releases/tag/0.12.1`
+            let path = html_url.strip_prefix(&self.web_url);
+            let Some((repo, tag)) = path.and_then(|path| path.split_once("/releases/tag/")) else {
+                return Ok(None);
+            };
+            let url = format!("{}/repos{repo}/releases/tags/{tag}", self.api_url);
+            debug!(url, "fetching assets");
+            let _permit = http.acquire(&url).await?;
+            let release = match self.send(http, &url, JSON).await {
+                Ok(rsp) => rsp.json::<crate::Release>().await?,
+                Err(error) => return Err(self.gone(&url, error, http).await),
+            };
+            Ok(Some(release.assets))
+        })
+    }
+
+    /// Needs a token, since GraphQL is only available to authenticated users
+    fn participants<'a>(
+        &'a self,
+        html_url: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        Box::pin(async move {
+            // Like `https://github.com/djc/askama/pull/1`
+            let path = html_url.strip_prefix(&self.web_url);
+            let mut segments = path.unwrap_or_default().split('/').skip(1);
+            let (Some(owner), Some(name), Some("issues" | "pull"), Some(number)) = (
+                segments.next(),
+                segments.next(),
+                segments.next(),
+                segments
+                    .next()
+                    .and_then(|number| number.parse::<u64>().ok()),
+            ) else {
+                return Ok(None);
+            };
+            if self.tokens.tokens.is_empty() {
+                return Ok(None);
+            }
+
+            debug!(html_url, "looking up participants");
+            let variables = serde_json::json!({ "owner": owner, "name": name, "number": number });
+            let data = self.graphql::<ParticipantsData>(PARTICIPANTS, variables, http);
+            let item = data
+                .await?
+                .and_then(|data| data.repository?.issue_or_pull_request);
+            Ok(item.map(|item| {
+                let nodes = item.participants.nodes.into_iter();
+                nodes.map(|user| user.login).collect()
+            }))
+        })
+    }
+
+    /// Requests for renamed repositories are redirected to the current repository
+    fn current_name<'a>(
+        &'a self,
+        repo: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        Box::pin(async move {
+            let url = format!("{}/repos/{repo}", self.api_url);
+            debug!(url, "resolving repository");
+            let _permit = http.acquire(&url).await?;
+            let rsp = self.send(http, &url, JSON).await?;
+            Ok(Some(rsp.json::<Repository>().await?.full_name))
+        })
+    }
+
+    /// Only public memberships are listed, unless the token belongs to `user` and has the
+    /// `read:org` scope
+    fn organizations<'a>(
+        &'a self,
+        user: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        Box::pin(async move {
+            let url = format!("{}/users/{user}/orgs?per_page=100", self.api_url);
+            debug!(url, "listing organizations");
+            let _permit = http.acquire(&url).await?;
+            let orgs = self
+                .send(http, &url, JSON)
+                .await?
+                .json::<Vec<User>>()
+                .await?;
+            Ok(Some(orgs.into_iter().map(|org| org.login).collect()))
+        })
+    }
+
+    fn about<'a>(
+        &'a self,
+        repo: &'a str,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<About>>> {
+        Box::pin(async move {
+            let url = format!("{}/repos/{repo}", self.api_url);
+            debug!(url, "describing repository");
+            let _permit = http.acquire(&url).await?;
+            let repo = self
+                .send(http, &url, JSON)
+                .await?
+                .json::<Repository>()
+                .await?;
+            // Unset fields may be empty rather than missing
+            let set = |field: Option<String>| field.filter(|value| !value.is_empty());
+            Ok(Some(About {
+                description: set(repo.description),
+                language: set(repo.language),
+                homepage: set(repo.homepage),
+                archived: repo.archived,
+                upstream: repo
+                    .parent
+                    .map(|parent| (parent.full_name, parent.html_url)),
+            }))
+        })
+    }
+
+    /// Private events are only listed for the user the token belongs to, and for classic tokens,
+    /// only with the `repo` scope
+    ///
+    /// Fine-grained tokens don't reveal their permissions (until a request is refused for lack
+    /// of one), so only their user is checked. Tokens that expire say when.
+    fn check_access<'a>(
+        &'a self,
+        users: &'a [String],
+        private: bool,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Access>> {
+        Box::pin(async move {
+            let mut access = Access::default();
+            if self.tokens.tokens.is_empty() {
+                if private {
+                    let warning = "activity in private repositories needs a token (use \
+                                   `github_token`)";
+                    access.warnings.push(warning.to_owned());
+                }
+                return Ok(access);
+            }
+
+            let url = format!("{}/user", self.api_url);
+            debug!(url, "checking token");
+            let _permit = http.acquire(&url).await?;
+            let rsp = self.send(http, &url, JSON).await?;
+            let header = |name| rsp.headers().get(name)?.to_str().ok();
+            access.expires_at =
+                header("github-authentication-token-expiration").and_then(expires_at);
+            if !private {
+                return Ok(access);
+            }
+
+            let scopes = header("x-oauth-scopes")
+                .map(|scopes| scopes.split(',').map(str::trim).collect::<Vec<_>>());
+            let warnings = &mut access.warnings;
+            if let Some(scopes) = scopes.filter(|scopes| !scopes.contains(&"repo")) {
+                warnings.push(format!(
+                    "the token lacks the `repo` scope (it has `{}`), so activity in private \
+                     repositories is left out",
+                    scopes.join(", ")
+                ));
+            }
+
+            let login = rsp.json::<User>().await?.login;
+            for user in users
+                .iter()
+                .filter(|user| !user.eq_ignore_ascii_case(&login))
+            {
+                warnings.push(format!(
+                    "the token belongs to {login}, so activity of {user} in private repositories \
+                     is left out"
+                ));
+            }
+            Ok(access)
+        })
+    }
+
+    /// Estimate the number of pages needed from the first page of events
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            let url = self.events_url(user, http);
+            debug!(url, "probing events");
+            let _permit = http.acquire(&url).await?;
+            let rsp = self.send(http, &url, JSON).await?;
+            let last = link(rsp.headers(), "last").and_then(|url| page_number(&url));
+            let events = rsp.json::<Vec<RestEvent>>().await?;
+            let reached_start = events
+                .last()
+                .is_some_and(|event| event.created_at < interval.start);
+            Ok(match (reached_start, last) {
+                (false, Some(last)) => Cost {
+                    requests: last,
+                    exact: false,
+                    bytes_processed: None,
+                },
+                _ => Cost {
+                    requests: 1,
+                    exact: true,
+                    bytes_processed: None,
+                },
+            })
+        })
+    }
+
+    /// Plan the first request of each endpoint, and the number of pages where it's bounded
+    ///
+    /// The events feed has at most 300 events, and searches yield at most 1000 results.
+    fn plan(&self, user: &str, interval: Interval, pass: Pass, http: &Http) -> Vec<Planned> {
+        let get = |url: String, requests, rate_limit| Planned {
+            request: format!("GET {url}"),
+            requests,
+            at_most: requests.is_some_and(|requests| requests > 1),
+            rate_limit: Some(rate_limit),
+        };
+        let api_url = &self.api_url;
+        match pass {
+            Pass::Events => {
+                let mut pages = 300u64.div_ceil(http.per_page.max(1) as u64);
+                if let Some(max) = http.max_pages {
+                    pages = pages.min(max as u64);
+                }
+                vec![get(self.events_url(user, http), Some(pages), "core")]
+            }
+            // Plus the forks and stargazers of each repository
+            Pass::Received => {
+                let url = format!("{api_url}/users/{user}/repos?type=owner&per_page=100");
+                vec![get(url, None, "core")]
+            }
+            Pass::Snippets => {
+                let since = interval.start.format("%Y-%m-%dT%H:%M:%SZ");
+                let url = format!("{api_url}/users/{user}/gists?since={since}&per_page=100");
+                vec![get(url, None, "core")]
+            }
+            Pass::Notifications => {
+                let (since, before) = (
+                    interval.start.format("%Y-%m-%dT%H:%M:%SZ"),
+                    interval.end.format("%Y-%m-%dT%H:%M:%SZ"),
+                );
+                let url = format!(
+                    "{api_url}/notifications?all=true&participating=true&since={since}\
+                     &before={before}&per_page=100"
+                );
+                vec![
+                    get(format!("{api_url}/user"), Some(1), "core"),
+                    get(url, None, "core"),
+                ]
+            }
+            Pass::Search => match self.search_url(user, interval) {
+                Ok(url) => vec![get(url, Some(10), "search")],
+                Err(_) => Vec::new(),
+            },
+        }
+    }
+}
+
+/// Parse the `GitHub-Authentication-Token-Expiration` header (like `2024-10-01 12:00:00 UTC`)
+fn expires_at(expiration: &str) -> Option<DateTime<Utc>> {
+    let expiration = expiration.trim().replace(" UTC", " +0000");
+    let expires_at = DateTime::parse_from_str(&expiration, "%Y-%m-%d %H:%M:%S %z").ok()?;
+    Some(expires_at.to_utc())
+}
+
+/// The reasons for notifications that are included (see [`GitHub::notifications()`])
+const NOTIFIED: &[&str] = &["assign", "mention", "review_requested", "team_mention"];
+
+/// Extract the URL for the next page from the `Link` header, if any
+pub(crate) fn next_link(headers: &HeaderMap) -> Option<String> {
+    link(headers, "next")
+}
+
+/// Extract the `page` query parameter from a (pagination) URL
+pub(crate) fn page_number(url: &str) -> Option<u64> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let (_, page) = url.query_pairs().find(|(key, _)| key == "page")?;
+    page.parse().ok()
+}
+
+/// The URLs of the pages from `next` up to `last` (if they're numbered)
+fn numbered(next: &str, last: &str) -> Vec<String> {
+    let (Some(first), Some(last), Ok(url)) = (
+        page_number(next),
+        page_number(last),
+        reqwest::Url::parse(next),
+    ) else {
+        return Vec::new();
+    };
+
+    (first..=last)
+        .map(|page| {
+            let pairs = url
+                .query_pairs()
+                .map(|(key, value)| match key == "page" {
+                    true => (key.into_owned(), page.to_string()),
+                    false => (key.into_owned(), value.into_owned()),
+                })
+                .collect::<Vec<_>>();
+            let mut url = url.clone();
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+            url.into()
+        })
+        .collect()
+}
+
+/// Extract the URL for the given `rel` from the `Link` header, if any
+fn link(headers: &HeaderMap, rel: &str) -> Option<String> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    let rel = format!(r#"rel="{rel}""#);
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params.split(';').any(|param| param.trim() == rel).then(|| {
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_owned()
+        })
+    })
+}
+
+/// Derive the web URL for a GitHub (Enterprise Server) instance from its API URL
+///
+/// `https://api.github.com` maps to `https://github.com`, while GitHub Enterprise Server's
+/// `https://HOST/api/v3` maps to `https://HOST`.
+fn web_url(api_url: &reqwest::Url) -> anyhow::Result<String> {
+    let host = api_url
+        .host_str()
+        .ok_or_else(|| anyhow::Error::msg(format!("no host in API URL {api_url}")))?;
+    let host = host.strip_prefix("api.").unwrap_or(host);
+    Ok(match api_url.port() {
+        Some(port) => format!("{}://{host}:{port}", api_url.scheme()),
+        None => format!("{}://{host}", api_url.scheme()),
+    })
+}
+
+/// Merge the event's type, repository and timestamp into its `payload` (see [`crate::Event`])
+pub(crate) fn normalize(
+    kind: &str,
+    repo: Option<&str>,
+    created_at: DateTime<Utc>,
+    mut payload: serde_json::Value,
+) -> String {
+    if let Some(object) = payload.as_object_mut() {
+        object.insert("type".to_owned(), kind.into());
+        if let Some(repo) = repo {
+            object.insert("repo".to_owned(), serde_json::json!({ "name": repo }));
+        }
+        object.insert("created_at".to_owned(), created_at.to_rfc3339().into());
+    }
+
+    payload.to_string()
+}
+
+/// The tokens to authenticate with, of which one is in use at a time (see
+/// [`GitHub::with_tokens()`])
+struct Tokens {
+    tokens: Vec<String>,
+    /// The index of the token in use
+    current: AtomicUsize,
+}
+
+impl Tokens {
+    /// The index of the token in use
+    fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Switch to the next token if the one at index `token` (which was used for a response with
+    /// `headers`) is running out of rate limit
+    ///
+    /// A few requests are left for those still in flight, so that they don't get rate limited.
+    fn observe(&self, token: usize, headers: &HeaderMap) {
+        let remaining = headers
+            .get("x-ratelimit-remaining")
+            .and_then(|value| value.to_str().ok()?.parse::<u64>().ok());
+        if self.tokens.len() < 2 || remaining.is_none_or(|remaining| remaining > RESERVE) {
+            return;
+        }
+
+        // Unless a request still in flight switched already
+        let next = (token + 1) % self.tokens.len();
+        let switched =
+            self.current
+                .compare_exchange(token, next, Ordering::Relaxed, Ordering::Relaxed);
+        if switched.is_ok() {
+            info!(
+                remaining,
+                "switching to the next token, as this one runs out of rate limit"
+            );
+        }
+    }
+}
+
+/// How many requests to leave in the rate limit of a token before switching to the next one
+const RESERVE: u64 = 10;
+
+/// An event from the REST API
+#[derive(Debug, Deserialize)]
+pub(crate) struct RestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    repo: RestRepo,
+    /// Whether the repository is public
+    public: bool,
+    pub(crate) created_at: DateTime<Utc>,
+    payload: serde_json::Value,
+}
+
+impl RestEvent {
+    /// The payload, with the type, repository, time and visibility of the event included
+    pub(crate) fn normalize(self) -> String {
+        let mut payload = self.payload;
+        if let Some(object) = payload.as_object_mut() {
+            object.insert("public".to_owned(), self.public.into());
+        }
+        normalize(&self.kind, Some(&self.repo.name), self.created_at, payload)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RestRepo {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    full_name: String,
+    fork: bool,
+    forks_count: u64,
+    stargazers_count: u64,
+    description: Option<String>,
+    language: Option<String>,
+    homepage: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    /// The repository this one was forked from (only included for a single repository)
+    parent: Option<Parent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Parent {
+    full_name: String,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pull {
+    html_url: String,
+}
+
+/// The quota for requests to a resource, which is replenished at `reset`
+#[derive(Debug, Deserialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub used: u64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub reset: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimits {
+    resources: BTreeMap<String, RateLimit>,
+}
+
+/// A page of search results (see [`GitHub::search()`])
+#[derive(Debug, Deserialize)]
+struct SearchResults {
+    total_count: u64,
+    /// Whether the search timed out before finding all results
+    incomplete_results: bool,
+    items: Vec<Found>,
+}
+
+/// An issue or pull request found by searching
+#[derive(Debug, Deserialize)]
+struct Found {
+    html_url: String,
+    title: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    user: Option<User>,
+    /// The API URL of the repository
+    repository_url: String,
+    /// Only set for pull requests
+    pull_request: Option<FoundPull>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FoundPull {
+    merged_at: Option<DateTime<Utc>>,
+}
+
+/// A notification thread (see [`GitHub::notifications()`])
+#[derive(Debug, Deserialize)]
+struct Thread {
+    /// Why the user was notified (like `review_requested` or `mention`)
+    reason: String,
+    updated_at: DateTime<Utc>,
+    subject: Subject,
+    repository: ThreadRepo,
+}
+
+/// The item a notification is about
+#[derive(Debug, Deserialize)]
+struct Subject {
+    title: String,
+    /// The API URL of the item, if it has one
+    url: Option<String>,
+    /// The web URL of the item, if included, which is preferred over mapping `url` to it
+    html_url: Option<String>,
+    /// `PullRequest`, `Issue`, `Release` and so on
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ThreadRepo {
+    full_name: String,
+    private: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Gist {
+    id: String,
+    html_url: String,
+    description: Option<String>,
+    files: BTreeMap<String, IgnoredAny>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// An issue or pull request
+#[derive(Debug, Deserialize)]
+struct Item {
+    /// `open` or `closed`
+    state: String,
+    /// Only set for merged pull requests
+    merged_at: Option<DateTime<Utc>>,
+    /// `completed` or `not_planned` for closed issues
+    state_reason: Option<String>,
+    labels: Vec<Label>,
+    /// Only set for merged pull requests
+    merged_by: Option<User>,
+    /// Only set for pull requests
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    changed_files: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Star {
+    starred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Fork {
+    full_name: String,
+    html_url: String,
+    created_at: DateTime<Utc>,
+}
+
+/// A GraphQL response, with its data or errors
+#[derive(Debug, Deserialize)]
+struct GraphQl<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarData {
+    user: Option<CalendarUser>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarUser {
+    contributions_collection: ContributionsCollection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsCollection {
+    contribution_calendar: ContributionCalendar,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionCalendar {
+    total_contributions: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParticipantsData {
+    repository: Option<ParticipantsRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParticipantsRepository {
+    issue_or_pull_request: Option<ParticipantsItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParticipantsItem {
+    participants: Participants,
+}
+
+#[derive(Debug, Deserialize)]
+struct Participants {
+    nodes: Vec<User>,
+}
+
+/// The commit a tag points to (see [`GitHub::tagged()`])
+#[derive(Debug, Deserialize)]
+struct Tagged {
+    commit: TaggedCommit,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaggedCommit {
+    committer: Committer,
+}
+
+#[derive(Debug, Deserialize)]
+struct Committer {
+    date: DateTime<Utc>,
+}
+
+const CALENDAR: &str = "query($login: String!, $from: DateTime!, $to: DateTime!) {
+  user(login: $login) {
+    contributionsCollection(from: $from, to: $to) {
+      contributionCalendar { totalContributions }
+    }
+  }
+}";
+
+/// Only the first 100 participants, which is plenty to tell whether anyone else took part
+const PARTICIPANTS: &str = "query($owner: String!, $name: String!, $number: Int!) {
+  repository(owner: $owner, name: $name) {
+    issueOrPullRequest(number: $number) {
+      ... on Issue { participants(first: 100) { nodes { login } } }
+      ... on PullRequest { participants(first: 100) { nodes { login } } }
+    }
+  }
+}";
+
+const SOURCE: &str = "github";
+pub(crate) const WEB_URL: &str = "https://github.com";
+pub(crate) const API_URL: &str = "https://api.github.com";
+const JSON: &str = "application/vnd.github+json";
+/// Includes the time at which each stargazer starred the repository
+const STAR_JSON: &str = "application/vnd.github.star+json";
+const API_VERSION: &str = "2022-11-28";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn planned() {
+        let github = GitHub::new("https://api.github.com".to_owned(), None).unwrap();
+        let interval = Interval::month("202403").unwrap();
+        let mut http = Http::new(reqwest::Client::new(), 1, 1);
+        http.per_page = 30;
+        let plan = github.plan("djc", interval, Pass::Events, &http);
+        assert_eq!(
+            plan[0].request,
+            "GET https://api.github.com/users/djc/events?per_page=30"
+        );
+        assert_eq!((plan[0].requests, plan[0].at_most), (Some(10), true));
+
+        http.max_pages = Some(2);
+        let plan = github.plan("djc", interval, Pass::Events, &http);
+        assert_eq!(plan[0].requests, Some(2));
+        let plan = github.plan("djc", interval, Pass::Search, &http);
+        assert_eq!(plan[0].rate_limit, Some("search"));
+    }
+
+    #[test]
+    fn numbered_pages() {
+        let url =
+            |page| format!("https://api.github.com/users/djc/events?per_page=100&page={page}");
+        assert_eq!(numbered(&url(2), &url(4)), [url(2), url(3), url(4)]);
+        let next = "https://api.github.com/users/djc/events?cursor=abc";
+        assert!(numbered(next, &url(4)).is_empty());
+    }
+
+    #[test]
+    fn html_urls() {
+        let github = GitHub::new("https://github.example.com/api/v3".to_owned(), None).unwrap();
+        let url =
+            |path| github.html_url(&format!("https://github.example.com/api/v3/repos/{path}"));
+        assert_eq!(
+            url("djc/askama/pulls/12").as_deref(),
+            Some("https://github.example.com/djc/askama/pull/12")
+        );
+        assert_eq!(
+            url("djc/askama/issues/3").as_deref(),
+            Some("https://github.example.com/djc/askama/issues/3")
+        );
+        assert_eq!(url("djc/askama/releases/1"), None);
+    }
+
+    #[test]
+    fn rotation() {
+        let tokens = Tokens {
+            tokens: vec!["a".to_owned(), "b".to_owned()],
+            current: AtomicUsize::new(0),
+        };
+        let remaining = |remaining: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-ratelimit-remaining", remaining.parse().unwrap());
+            headers
+        };
+        tokens.observe(0, &remaining("4000"));
+        assert_eq!(tokens.current(), 0);
+        tokens.observe(0, &remaining("10"));
+        assert_eq!(tokens.current(), 1);
+        // From a request that was sent before switching
+        tokens.observe(0, &remaining("9"));
+        assert_eq!(tokens.current(), 1);
+        tokens.observe(1, &remaining("0"));
+        assert_eq!(tokens.current(), 0);
+    }
+
+    #[test]
+    fn expiration() {
+        let expected = "2024-10-01T12:00:00+00:00";
+        let utc = expires_at("2024-10-01 12:00:00 UTC").unwrap();
+        assert_eq!(utc.to_rfc3339(), expected);
+        let offset = expires_at("2024-10-01 05:00:00 -0700").unwrap();
+        assert_eq!(offset.to_rfc3339(), expected);
+        assert_eq!(expires_at("never"), None);
+    }
+
+    #[test]
+    fn rate_limits() {
+        let json = r#"{"resources": {"core": {"limit": 5000, "used": 130, "remaining": 4870,
+            "reset": 1727780400}}, "rate": {"limit": 5000}}"#;
+        let limits = serde_json::from_str::<RateLimits>(json).unwrap().resources;
+        assert_eq!(limits["core"].remaining, 4870);
+        assert_eq!(
+            limits["core"].reset.to_rfc3339(),
+            "2024-10-01T11:00:00+00:00"
+        );
+    }
+}
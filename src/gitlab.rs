@@ -0,0 +1,362 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use async_stream::try_stream;
+use chrono::{DateTime, Days, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::github::next_link;
+use crate::{Cost, Event, EventSource, Fetch, Http, Interval, ItemMeta, Review};
+
+/// The GitLab events API of the instance at `host`
+///
+/// GitLab events don't carry the project path or item URLs, so these are resolved via the
+/// projects API and the events are normalized into the same shape as GitHub event payloads.
+/// Pushing a tag counts as a release.
+pub struct GitLab {
+    host: String,
+    token: Option<String>,
+}
+
+impl GitLab {
+    pub fn new(host: String, token: Option<String>) -> Self {
+        Self { host, token }
+    }
+
+    /// The URL for the events of `user` within `interval`
+    fn events_url(&self, user: &str, interval: Interval, per_page: usize) -> String {
+        // `after` and `before` are exclusive and only have day granularity (in UTC). Oldest
+        // first, so that the times an item was opened or closed are known for later events
+        let after = (interval.start.date_naive() - Days::new(1)).format("%Y-%m-%d");
+        let last = (interval.end - chrono::Duration::seconds(1)).date_naive();
+        let before = (last + Days::new(1)).format("%Y-%m-%d");
+        format!(
+            "https://{}/api/v4/users/{user}/events?per_page={per_page}&sort=asc&after={after}&before={before}",
+            self.host
+        )
+    }
+
+    /// The project with the given `id`, looked up (once) through the projects API
+    async fn project<'p>(
+        &self,
+        id: u64,
+        projects: &'p mut HashMap<u64, Project>,
+        http: &Http,
+    ) -> anyhow::Result<&'p Project> {
+        let entry = match projects.entry(id) {
+            Entry::Occupied(entry) => return Ok(entry.into_mut()),
+            Entry::Vacant(entry) => entry,
+        };
+
+        let url = format!("https://{}/api/v4/projects/{id}", self.host);
+        let _permit = http.acquire(&url).await?;
+        let project = http
+            .send(self.get(http, &url))
+            .await?
+            .json::<Project>()
+            .await?;
+        Ok(entry.insert(project))
+    }
+
+    fn get(&self, http: &Http, url: &str) -> reqwest::RequestBuilder {
+        let req = http.client().get(url);
+        match &self.token {
+            Some(token) => req.header("PRIVATE-TOKEN", token),
+            None => req,
+        }
+    }
+}
+
+impl EventSource for GitLab {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec![format!("https://{}", self.host)]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        format!("{user}@{}", self.host)
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let mut next = Some(self.events_url(user, interval, http.per_page));
+            let mut projects = HashMap::<u64, Project>::new();
+            // When each item was opened, closed and merged, as far as seen in the events
+            let mut times = HashMap::<String, Times>::new();
+            let mut pages = 0;
+            while let Some(url) = next.take() {
+                debug!(url, "fetching events");
+                let (page, headers) = http.page::<GitLabEvent>(&url, self.get(http, &url)).await?;
+                next = next_link(&headers);
+
+                if let Some(oldest) = page.iter().map(|event| event.created_at).min() {
+                    http.metrics().reached(oldest);
+                }
+
+                for event in page {
+                    if !interval.contains(event.created_at) {
+                        continue;
+                    }
+
+                    if let (Some(project_id), Some(push)) = (event.project_id, &event.push_data) {
+                        if push.ref_type != "tag" || push.action != "created" {
+                            continue;
+                        }
+
+                        let project = self.project(project_id, &mut projects, http).await?;
+                        let short = project.path_with_namespace.rsplit('/').next();
+                        let item = ItemMeta {
+                            html_url: format!("{}/-/tags/{}", project.web_url, push.git_ref),
+                            title: format!("Tagged {} {}", short.unwrap_or_default(), push.git_ref),
+                            created_at: Some(event.created_at),
+                            closed_at: None,
+                            merged_at: None,
+                            node_id: None,
+                            updated_at: None,
+                            body: None,
+                            user: None,
+                            author_association: None,
+                            pull_request: None,
+                        };
+                        yield Fetch::event(&Event {
+                            kind: Some("ReleaseEvent".to_owned()),
+                            action: Some("published".to_owned()),
+                            created_at: Some(event.created_at),
+                            public: project.public(),
+                            issue: Some(item),
+                            ..Default::default()
+                        })?;
+                        continue;
+                    }
+
+                    let (kind, iid) = match (&event.target_type, &event.note) {
+                        (Some(TargetType::MergeRequest), _) => {
+                            (Kind::MergeRequest, event.target_iid)
+                        }
+                        (Some(TargetType::Issue), _) => (Kind::Issue, event.target_iid),
+                        (
+                            Some(
+                                TargetType::Note
+                                | TargetType::DiscussionNote
+                                | TargetType::DiffNote,
+                            ),
+                            Some(note),
+                        ) => match note.noteable_type {
+                            TargetType::MergeRequest => (Kind::MergeRequest, note.noteable_iid),
+                            TargetType::Issue => (Kind::Issue, note.noteable_iid),
+                            _ => continue,
+                        },
+                        _ => {
+                            debug!(action = event.action_name, "skipping event");
+                            continue;
+                        }
+                    };
+
+                    let (Some(project_id), Some(iid), Some(title)) =
+                        (event.project_id, iid, event.target_title)
+                    else {
+                        continue;
+                    };
+
+                    let project = self.project(project_id, &mut projects, http).await?;
+
+                    let html_url = format!("{}/-/{}/{iid}", project.web_url, kind.path());
+                    let item_times = times.entry(html_url.clone()).or_default();
+                    let time = Some(event.created_at);
+                    match (kind, event.action_name.as_str()) {
+                        (_, "opened") => item_times.created_at = time,
+                        (_, "closed") => item_times.closed_at = time,
+                        (_, "reopened") => item_times.closed_at = None,
+                        (Kind::MergeRequest, "accepted") => {
+                            item_times.closed_at = time;
+                            item_times.merged_at = time;
+                        }
+                        _ => {}
+                    }
+                    let item = ItemMeta {
+                        html_url,
+                        title,
+                        created_at: item_times.created_at,
+                        closed_at: item_times.closed_at,
+                        merged_at: item_times.merged_at,
+                        node_id: None,
+                        updated_at: None,
+                        body: None,
+                        user: None,
+                        author_association: None,
+                        pull_request: None,
+                    };
+
+                    // Describe the action in terms of the equivalent GitHub event
+                    let (event_type, action) = match (kind, event.action_name.as_str()) {
+                        (_, "commented on") => ("IssueCommentEvent", "created"),
+                        (Kind::MergeRequest, "approved") => ("PullRequestReviewEvent", "created"),
+                        (Kind::MergeRequest, "accepted") => ("PullRequestEvent", "closed"),
+                        (Kind::MergeRequest, action) => ("PullRequestEvent", action),
+                        (Kind::Issue, action) => ("IssuesEvent", action),
+                    };
+                    yield Fetch::event(&match kind {
+                        Kind::MergeRequest => Event {
+                            kind: Some(event_type.to_owned()),
+                            action: Some(action.to_owned()),
+                            created_at: Some(event.created_at),
+                            public: project.public(),
+                            review: (event.action_name == "approved").then(|| Review {
+                                state: "approved".to_owned(),
+                                html_url: None,
+                                id: None,
+                            }),
+                            issue: None,
+                            pull_request: Some(item),
+                            ..Default::default()
+                        },
+                        Kind::Issue => Event {
+                            kind: Some(event_type.to_owned()),
+                            action: Some(action.to_owned()),
+                            created_at: Some(event.created_at),
+                            public: project.public(),
+                            issue: Some(item),
+                            pull_request: None,
+                            ..Default::default()
+                        },
+                    })?;
+                }
+
+                pages += 1;
+                if next.is_some() && http.capped(pages) {
+                    yield Fetch::Incomplete { total: None };
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Estimate the number of requests from the first page of events
+    ///
+    /// Besides the event pages, one request is needed per project; the number of projects on
+    /// the first page is used as a lower bound.
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            let url = self.events_url(user, interval, http.per_page);
+            debug!(url, "probing events");
+            let _permit = http.acquire(&url).await?;
+            let rsp = http.send(self.get(http, &url)).await?;
+            let pages = rsp
+                .headers()
+                .get("x-total-pages")
+                .and_then(|pages| pages.to_str().ok()?.parse::<u64>().ok());
+            let events = rsp.json::<Vec<GitLabEvent>>().await?;
+            let projects = events
+                .iter()
+                .filter_map(|event| event.project_id)
+                .collect::<HashSet<_>>();
+
+            Ok(Cost {
+                requests: pages.unwrap_or(1).max(1) + projects.len() as u64,
+                exact: false,
+                bytes_processed: None,
+            })
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    MergeRequest,
+    Issue,
+}
+
+impl Kind {
+    fn path(self) -> &'static str {
+        match self {
+            Self::MergeRequest => "merge_requests",
+            Self::Issue => "issues",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabEvent {
+    action_name: String,
+    target_type: Option<TargetType>,
+    target_iid: Option<u64>,
+    target_title: Option<String>,
+    project_id: Option<u64>,
+    note: Option<Note>,
+    push_data: Option<PushData>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushData {
+    action: String,
+    ref_type: String,
+    #[serde(rename = "ref")]
+    git_ref: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Note {
+    noteable_type: TargetType,
+    noteable_iid: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+enum TargetType {
+    MergeRequest,
+    Issue,
+    Note,
+    DiscussionNote,
+    DiffNote,
+    #[serde(other)]
+    Other,
+}
+
+/// When an item was opened, closed and merged (if seen during the period)
+#[derive(Default)]
+struct Times {
+    created_at: Option<DateTime<Utc>>,
+    closed_at: Option<DateTime<Utc>>,
+    merged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    web_url: String,
+    path_with_namespace: String,
+    visibility: Option<Visibility>,
+}
+
+impl Project {
+    /// Whether the project is public, if known (internal projects are only visible when signed in)
+    fn public(&self) -> Option<bool> {
+        self.visibility
+            .map(|visibility| matches!(visibility, Visibility::Public))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Visibility {
+    Public,
+    Internal,
+    Private,
+}
+
+const SOURCE: &str = "gitlab";
@@ -0,0 +1,189 @@
+use std::path::Path;
+use std::{env, fs};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    to_yaml, Activity, Atom, Collector, Dot, Format, Html, Ics, Interval, Renderer, Replay, Rst,
+    Twir, Window,
+};
+
+/// Everything that goes into rendering a report, so that rendering it doesn't depend on anything
+/// else (like the current time)
+#[derive(Debug)]
+pub struct Report<'a> {
+    pub activity: &'a Activity,
+    pub users: &'a [String],
+    /// The name of the period covered (like "March 2024", see [`Period::name()`])
+    ///
+    /// [`Period::name()`]: crate::Period::name
+    pub name: String,
+    pub interval: Interval,
+    /// When the report was generated (used in Atom feeds and iCalendar files)
+    pub generated: DateTime<Utc>,
+    /// The reStructuredText settings, whose titles, links and locale the other formats share
+    pub render: &'a Rst,
+}
+
+/// Render `report` in `format`
+///
+/// The same report always renders to the same text, which makes this suitable for golden tests
+/// of reports and custom renderers (see [`load_fixture()`]).
+pub fn render_report(report: &Report<'_>, format: Format) -> anyhow::Result<String> {
+    let Report {
+        activity,
+        users,
+        render,
+        ..
+    } = *report;
+    let mut out = Vec::new();
+    let name = report.name.clone();
+    match format {
+        Format::Rst => render.render(activity, users, &mut out)?,
+        Format::Atom => {
+            let atom = Atom {
+                period: name,
+                key: report.interval.key(),
+                updated: report.generated,
+                titles: &render.titles,
+                links: &render.links,
+            };
+            atom.render(activity, users, &mut out)?
+        }
+        Format::Html => {
+            let html = Html {
+                period: name,
+                titles: &render.titles,
+                links: &render.links,
+            };
+            html.render(activity, users, &mut out)?
+        }
+        Format::Json => serde_json::to_writer_pretty(&mut out, &activity.versioned())?,
+        Format::Yaml => out.extend_from_slice(to_yaml(&activity.versioned())?.as_bytes()),
+        Format::Twir => {
+            let twir = Twir {
+                titles: &render.titles,
+                links: &render.links,
+            };
+            twir.render(activity, users, &mut out)?
+        }
+        Format::Ics => {
+            let ics = Ics {
+                generated: report.generated,
+                titles: &render.titles,
+                links: &render.links,
+            };
+            ics.render(activity, users, &mut out)?
+        }
+        Format::Dot => Dot { period: name }.render(activity, users, &mut out)?,
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+/// Collect the activity of `users` during `interval` from the dump at `path` (see
+/// [`Collector::with_dump()`]), without making any requests
+///
+/// Like [`Replay`], only the events themselves are used: nothing is looked up or cached.
+pub async fn load_fixture(
+    path: &Path,
+    users: &[String],
+    interval: Interval,
+) -> anyhow::Result<Activity> {
+    let replay = Replay::load(path)?;
+    let collector =
+        Collector::new(Box::new(replay), reqwest::Client::new(), 1, 1).with_cache(false);
+    collector.collect(interval, users, Window::default()).await
+}
+
+/// Check that `rendered` matches the snapshot at `path`, panicking with the first line that
+/// differs if it doesn't
+///
+/// With `TMOG_EVENTS_UPDATE_SNAPSHOTS=1` in the environment, the snapshot is (re)written
+/// instead, so that changes to the output can be reviewed as changes to the snapshots.
+pub fn assert_snapshot(path: &Path, rendered: &str) {
+    if env::var_os(UPDATE_SNAPSHOTS).is_some_and(|update| update == "1") {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(path, rendered).unwrap();
+        return;
+    }
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(err) => panic!(
+            "failed to read snapshot {} ({err}), set {UPDATE_SNAPSHOTS}=1 to write it",
+            path.display()
+        ),
+    };
+    if expected == rendered {
+        return;
+    }
+    let (mut expected_lines, mut rendered_lines) = (expected.lines(), rendered.lines());
+    for number in 1.. {
+        let (expected, rendered) = (expected_lines.next(), rendered_lines.next());
+        if expected != rendered {
+            panic!(
+                "output differs from snapshot {} at line {number}\n  expected: {:?}\n  \
+                 rendered: {:?}\n(set {UPDATE_SNAPSHOTS}=1 to update it)",
+                path.display(),
+                expected.unwrap_or("<end>"),
+                rendered.unwrap_or("<end>"),
+            );
+        }
+    }
+}
+
+/// Set to `1` to update snapshots (see [`assert_snapshot()`])
+const UPDATE_SNAPSHOTS: &str = "TMOG_EVENTS_UPDATE_SNAPSHOTS";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn golden() {
+        let event = serde_json::json!({
+            "type": "PullRequestEvent",
+            "repo": { "name": "hyperium/hyper" },
+            "public": true,
+            "created_at": "2024-03-01T12:00:00Z",
+            "payload": {
+                "action": "opened",
+                "pull_request": {
+                    "html_url": "https://github.com/hyperium/hyper/pull/1",
+                    "title": "Fix a panic",
+                },
+            },
+        });
+        let url = "https://api.github.com/users/djc/events?page=1";
+        let dump = serde_json::json!({ "url": url, "event": event }).to_string();
+        let path = std::env::temp_dir().join(format!("tmog-events-golden-{}", std::process::id()));
+        fs::write(&path, dump).unwrap();
+        let (users, interval) = (["djc".to_owned()], Interval::month("202403").unwrap());
+        let activity = load_fixture(&path, &users, interval).await.unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let render = Rst::default();
+        let report = Report {
+            activity: &activity,
+            users: &users,
+            name: "March 2024".to_owned(),
+            interval,
+            generated: DateTime::from_timestamp(1710000000, 0).unwrap(),
+            render: &render,
+        };
+        let snapshots = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots");
+        for format in [
+            Format::Rst,
+            Format::Atom,
+            Format::Ics,
+            Format::Json,
+            Format::Dot,
+        ] {
+            let rendered = render_report(&report, format).unwrap();
+            let name = format!("golden.{}", format.extension());
+            assert_snapshot(&snapshots.join(name), &rendered);
+        }
+    }
+}
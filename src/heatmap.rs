@@ -0,0 +1,57 @@
+use std::io::Write;
+
+use chrono::{Days, NaiveDate};
+
+use crate::{Activity, Calendar, Interval};
+
+/// Write a GitHub-style heatmap of the interactions on each day of `interval` to `out`
+///
+/// Each column is a week (starting on the calendar's first day of the week), with a row per day
+/// of the week. Days are shaded relative to the busiest day.
+pub fn heatmap(
+    out: &mut dyn Write,
+    activity: &Activity,
+    interval: Interval,
+    calendar: &Calendar,
+) -> anyhow::Result<()> {
+    let (start, last) = interval.days();
+    let end = last + Days::new(1);
+    let first = calendar.week_start.of(start);
+    let weeks = ((end - first).num_days() as u64).div_ceil(7);
+    let max = activity.days.values().copied().max().unwrap_or(0);
+
+    for row in 0..7 {
+        let weekday = first + Days::new(row);
+        write!(out, "{} ", weekday.format("%a"))?;
+        for week in 0..weeks {
+            let day = first + Days::new(week * 7 + row);
+            write!(out, " {}", shade(day, start, end, activity, max))?;
+        }
+        writeln!(out)?;
+    }
+
+    write!(out, "\n    Less")?;
+    for shade in SHADES {
+        write!(out, " {shade}")?;
+    }
+    writeln!(out, " More")?;
+    Ok(())
+}
+
+/// The shade for `day`, or a space if it lies outside of `start..end`
+fn shade(day: NaiveDate, start: NaiveDate, end: NaiveDate, activity: &Activity, max: u64) -> char {
+    if day < start || day >= end {
+        return ' ';
+    }
+
+    match activity.days.get(&day).copied().unwrap_or(0) {
+        0 => SHADES[0],
+        n => {
+            let levels = SHADES.len() as u64 - 1;
+            SHADES[(n * levels).div_ceil(max) as usize]
+        }
+    }
+}
+
+/// From no interactions to the most interactions on a single day
+const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
@@ -0,0 +1,154 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{Activity, Entry};
+
+/// Passes the items of a report through an external command, which can drop, change or move them
+///
+/// The command gets each item as a JSON object on a line of its stdin, like
+/// `{"project": "hyper", "html_url": "…", "entry": {"title": "…", "labels": […], …}}`, and
+/// answers on stdout with a line for each item, in the same order: the object as is to keep the
+/// item, changed to change its title, labels or notes (say, to put it in another category) or to
+/// move it to another project, or `null` to leave the item out.
+#[derive(Debug)]
+pub struct Hook {
+    command: String,
+}
+
+impl Hook {
+    /// Run `command` (a program and its arguments, separated by spaces) for each report
+    pub fn new(command: String) -> Self {
+        Self { command }
+    }
+
+    pub fn apply(&self, activity: &mut Activity) -> anyhow::Result<()> {
+        let mut input = Vec::new();
+        let items = activity.projects.iter().flat_map(|(project, entries)| {
+            entries.iter().map(move |(html_url, entry)| Item {
+                project,
+                html_url,
+                entry,
+            })
+        });
+        for item in items {
+            serde_json::to_writer(&mut input, &item)?;
+            input.push(b'\n');
+        }
+
+        let mut words = self.command.split_whitespace();
+        let mut child = Command::new(words.next().context("empty hook command")?)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run {}", self.command))?;
+        // Written from another thread, so that the command can answer before reading everything
+        let mut stdin = child.stdin.take().context("no stdin for hook")?;
+        let (written, output) = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || stdin.write_all(&input));
+            let output = child.wait_with_output();
+            (writer.join(), output)
+        });
+        let output = output.with_context(|| format!("failed to run {}", self.command))?;
+        if !output.status.success() {
+            anyhow::bail!("{} failed ({})", self.command, output.status);
+        }
+        if let Ok(Err(error)) = written {
+            return Err(error).context("failed to pass the items to the hook");
+        }
+
+        let answers = String::from_utf8(output.stdout)?;
+        let mut answers = answers.lines().filter(|line| !line.trim().is_empty());
+        let projects = std::mem::take(&mut activity.projects);
+        let (total, mut answered) = (projects.values().map(|e| e.len()).sum::<usize>(), 0);
+        for (html_url, mut entry) in projects.into_values().flatten() {
+            let Some(answer) = answers.next() else {
+                anyhow::bail!("{} answered for {answered} of {total} items", self.command);
+            };
+            answered += 1;
+            let answer = serde_json::from_str::<Option<Answer>>(answer)
+                .with_context(|| format!("invalid answer from hook: {answer}"))?;
+            let Some(Answer {
+                project,
+                entry: changes,
+            }) = answer
+            else {
+                continue;
+            };
+            entry.title = changes.title;
+            entry.labels = changes.labels;
+            entry.notes = changes.notes;
+            let entries = activity.projects.entry(project).or_default();
+            entries.insert(html_url, entry);
+        }
+        Ok(())
+    }
+}
+
+/// An item as passed to the hook
+#[derive(Serialize)]
+struct Item<'a> {
+    project: &'a str,
+    html_url: &'a str,
+    entry: &'a Entry,
+}
+
+/// The hook's answer for an item (see [`Hook`])
+#[derive(Deserialize)]
+struct Answer {
+    project: String,
+    entry: Changes,
+}
+
+/// The parts of the entry that the hook can change
+#[derive(Deserialize)]
+struct Changes {
+    title: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    notes: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ItemKind;
+
+    #[test]
+    fn hooked() {
+        let mut activity = Activity::default();
+        for number in [1, 2, 3] {
+            let entry = Entry::new(format!("Fix panic {number}"), ItemKind::PullRequest);
+            let url = format!("https://github.com/hyperium/hyper/pull/{number}");
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(url, entry);
+        }
+
+        let hook = Hook::new(
+            "sed -e s|^.*/pull/2\".*$|null| -e s|Fix|Fixed| -e \\,/pull/3,s|\"hyper\"|\"h2\"|"
+                .to_owned(),
+        );
+        hook.apply(&mut activity).unwrap();
+        let titles = |project: &str| {
+            let entries = activity.projects[project].values();
+            entries
+                .map(|entry| entry.title.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles("hyper"), ["Fixed panic 1"]);
+        assert_eq!(titles("h2"), ["Fixed panic 3"]);
+
+        let mut activity = Activity::default();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        let entry = Entry::new("Fix panic".to_owned(), ItemKind::Issue);
+        entries.insert(
+            "https://github.com/hyperium/hyper/issues/1".to_owned(),
+            entry,
+        );
+        assert!(Hook::new("true".to_owned()).apply(&mut activity).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use crate::render::{plural, summary};
+use crate::{Activity, Links, Markup, Renderer, Titles};
+
+/// Renders reports as a standalone HTML page, with a section per project
+#[derive(Debug)]
+pub struct Html<'a> {
+    /// The name of the period (like "October 2024")
+    pub period: String,
+    pub titles: &'a Titles,
+    pub links: &'a Links,
+}
+
+impl Renderer for Html<'_> {
+    fn render(
+        &self,
+        activity: &Activity,
+        users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        let period = Markup::Html.text(&self.period);
+        let users = users
+            .iter()
+            .map(|user| Markup::Html.text(user))
+            .collect::<Vec<_>>();
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, r#"<html lang="en">"#)?;
+        writeln!(
+            out,
+            r#"<head><meta charset="utf-8"><title>{period}</title></head>"#
+        )?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>{period}</h1>")?;
+        writeln!(
+            out,
+            "<p>{} by {}</p>",
+            Markup::Html.text(&summary(activity)),
+            users.join(", ")
+        )?;
+        if activity.interrupted {
+            let note = "Collecting was interrupted, so this report is partial.";
+            writeln!(out, "<p><strong>{note}</strong></p>")?;
+        }
+        for project in projects(activity) {
+            writeln!(out, "<h2>{}</h2>", Markup::Html.text(project))?;
+            writeln!(out, "{}", items(activity, project, self.titles, self.links))?;
+        }
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+        Ok(())
+    }
+}
+
+/// The projects with items or pushes, in order
+pub(crate) fn projects(activity: &Activity) -> BTreeSet<&String> {
+    activity
+        .projects
+        .keys()
+        .chain(activity.pushes.keys())
+        .collect()
+}
+
+/// A list of links to the items of and pushes to `project`
+pub(crate) fn items(activity: &Activity, project: &str, titles: &Titles, links: &Links) -> String {
+    let mut list = String::from("<ul>");
+    for (html_url, entry) in activity.entries(project) {
+        let link = links.link(project, entry.link(html_url));
+        let title = titles.normalize(&entry.title);
+        let (title, link) = (Markup::Html.text(&title), Markup::Html.url(&link));
+        list.push_str(&format!(r#"<li><a href="{link}">{title}</a>"#));
+        if let Some(forge) = links.forge(html_url) {
+            list.push_str(&format!(" [{}]", Markup::Html.text(forge)));
+        }
+        if entry.first_contribution {
+            list.push_str(" 🆕 first contribution");
+        }
+        if entry.continued {
+            list.push_str(" (continued)");
+        }
+        for note in &entry.notes {
+            list.push_str(&format!("<p>{}</p>", Markup::Html.text(note)));
+        }
+        if let Some(excerpt) = &entry.excerpt {
+            let lines = excerpt.lines().map(|line| Markup::Html.text(line));
+            list.push_str(&format!(
+                "<p>{}</p>",
+                lines.collect::<Vec<_>>().join("<br>")
+            ));
+        }
+        if !entry.assets.is_empty() {
+            list.push_str("<ul>");
+            for asset in &entry.assets {
+                let downloads = plural(asset.download_count as usize, "download");
+                let name = Markup::Html.text(&asset.name);
+                list.push_str(&format!("<li>{name}: {downloads}</li>"));
+            }
+            list.push_str("</ul>");
+        }
+        list.push_str("</li>");
+    }
+    for (repo, pushes) in activity.pushes.get(project).into_iter().flatten() {
+        let compare = format!(
+            "{}/{repo}/compare/{}...{}",
+            pushes
+                .web_url
+                .as_ref()
+                .unwrap_or(&links.web_url)
+                .trim_end_matches('/'),
+            pushes.base,
+            pushes.head
+        );
+        let link = links.link(project, &compare);
+        let s = if pushes.commits == 1 { "" } else { "s" };
+        list.push_str(&format!(
+            r#"<li><a href="{}">{} commit{s} pushed to {}</a></li>"#,
+            Markup::Html.url(&link),
+            pushes.commits,
+            Markup::Html.text(repo),
+        ));
+    }
+    list.push_str("</ul>");
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn page() {
+        let mut activity = Activity::default();
+        let url = "https://github.com/hyperium/hyper/pull/1?a&b";
+        let entry = Entry::new("Use <T> & co".to_owned(), ItemKind::PullRequest);
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(url.to_owned(), entry);
+
+        let (titles, links) = (Titles::default(), Links::default());
+        let html = Html {
+            period: "March 2024".to_owned(),
+            titles: &titles,
+            links: &links,
+        };
+        let mut out = Vec::new();
+        html.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let page = String::from_utf8(out).unwrap();
+        assert!(page.contains("<h1>March 2024</h1>"));
+        assert!(page.contains("<p>1 pull request in 1 project by djc</p>"));
+        assert!(page.contains(
+            "<h2>hyper</h2>\n<ul><li><a href=\"https://github.com/hyperium/hyper/pull/1?a&amp;b\">\
+             Use &lt;T&gt; &amp; co</a></li></ul>"
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Languages for the headings and period names in reports
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// Dutch
+    Nl,
+}
+
+impl Locale {
+    /// Translate the English `text`, which is returned as is if there's no translation
+    pub fn translate(self, text: &str) -> &str {
+        let translations = match self {
+            Self::En => return text,
+            Self::Nl => DUTCH,
+        };
+
+        translations
+            .iter()
+            .find(|(english, _)| *english == text)
+            .map_or(text, |(_, translated)| translated)
+    }
+
+    /// The name of the month `month0` (counting from 0 for January)
+    pub fn month(self, month0: u32) -> &'static str {
+        let months = match self {
+            Self::En => &ENGLISH_MONTHS,
+            Self::Nl => &DUTCH_MONTHS,
+        };
+        months[month0 as usize % 12]
+    }
+}
+
+/// English loanwords that are common in Dutch are included as is, so the table covers all headings
+const DUTCH: &[(&str, &str)] = &[
+    ("Activity by day", "Activiteit per dag"),
+    ("Activity by time", "Activiteit per tijdstip"),
+    ("Appendix", "Bijlage"),
+    (
+        "Collecting was interrupted, so this report is partial.",
+        "Het verzamelen is onderbroken, dus dit verslag is onvolledig.",
+    ),
+    ("Commits", "Commits"),
+    ("Community", "Gemeenschap"),
+    ("Compared to", "Vergeleken met"),
+    (
+        "Contributions to other projects",
+        "Bijdragen aan andere projecten",
+    ),
+    ("Discussions", "Discussies"),
+    ("Generated by", "Gegenereerd door"),
+    ("Highlights", "Hoogtepunten"),
+    ("Issues", "Issues"),
+    ("Merged pull requests", "Samengevoegde pull requests"),
+    ("My projects", "Mijn projecten"),
+    ("Other", "Overig"),
+    ("Pull requests", "Pull requests"),
+    ("Releases", "Releases"),
+    ("Reports", "Verslagen"),
+    ("Snippets", "Fragmenten"),
+    ("Sponsors", "Sponsors"),
+    ("Summary", "Samenvatting"),
+    ("Week", "Week"),
+    ("Where I was pulled in", "Waar ik bij werd betrokken"),
+    ("continued", "vervolg"),
+    ("first contribution", "eerste bijdrage"),
+    ("merged by", "samengevoegd door"),
+    ("on", "op"),
+    ("the week of", "de week van"),
+];
+
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const DUTCH_MONTHS: [&str; 12] = [
+    "januari",
+    "februari",
+    "maart",
+    "april",
+    "mei",
+    "juni",
+    "juli",
+    "augustus",
+    "september",
+    "oktober",
+    "november",
+    "december",
+];
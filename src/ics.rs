@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::render::plural;
+use crate::{Activity, Links, Renderer, Titles};
+
+/// Renders reports as an iCalendar file, with an all-day event for each day with activity
+///
+/// Each event sums up the items touched that day (like "3 contributions across 2 projects") and
+/// lists them in its description. Event UIDs are derived from the day, so that calendar apps
+/// update the events when the report is imported again. Pushes aren't included, since they're
+/// summed up by repository rather than by day.
+#[derive(Debug)]
+pub struct Ics<'a> {
+    /// When the report was generated
+    pub generated: DateTime<Utc>,
+    pub titles: &'a Titles,
+    pub links: &'a Links,
+}
+
+impl Renderer for Ics<'_> {
+    fn render(
+        &self,
+        activity: &Activity,
+        users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        let mut days = BTreeMap::<NaiveDate, Vec<(&str, String)>>::new();
+        for (project, entries) in &activity.projects {
+            for (html_url, entry) in entries {
+                let link = self.links.link(project, entry.link(html_url));
+                let title = self.titles.normalize(&entry.title);
+                for day in &entry.days {
+                    let item = format!("{project}: {title} ({link})");
+                    days.entry(*day).or_default().push((project, item));
+                }
+            }
+        }
+
+        let stamp = self.generated.format("%Y%m%dT%H%M%SZ");
+        line(out, "BEGIN:VCALENDAR")?;
+        line(out, "VERSION:2.0")?;
+        line(out, "PRODID:-//tmog-events//tmog-events//EN")?;
+        line(out, &format!("X-WR-CALNAME:{}", text(&users.join(", "))))?;
+        for (day, items) in &days {
+            let projects = items.iter().map(|(project, _)| project);
+            let projects = projects.collect::<BTreeSet<_>>();
+            let items = items.iter().map(|(_, item)| item.as_str());
+            let items = items.collect::<Vec<_>>();
+            let summary = format!(
+                "{} across {}",
+                plural(items.len(), "contribution"),
+                plural(projects.len(), "project")
+            );
+            let next = day.succ_opt().unwrap_or(*day);
+            line(out, "BEGIN:VEVENT")?;
+            let uid = format!(
+                "UID:{}-{}@tmog-events",
+                day.format("%Y%m%d"),
+                users.join("+")
+            );
+            line(out, &uid)?;
+            line(out, &format!("DTSTAMP:{stamp}"))?;
+            line(out, &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")))?;
+            line(out, &format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")))?;
+            line(out, &format!("SUMMARY:{}", text(&summary)))?;
+            line(out, &format!("DESCRIPTION:{}", text(&items.join("\n"))))?;
+            line(out, "TRANSP:TRANSPARENT")?;
+            line(out, "END:VEVENT")?;
+        }
+        line(out, "END:VCALENDAR")?;
+        Ok(())
+    }
+}
+
+/// Write a content line, folded at 75 octets (without splitting characters) as RFC 5545 requires
+fn line(out: &mut dyn Write, content: &str) -> anyhow::Result<()> {
+    let mut start = 0;
+    let mut limit = 75;
+    while content.len() - start > limit {
+        let mut end = start + limit;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        write!(out, "{}\r\n ", &content[start..end])?;
+        start = end;
+        // Continuation lines start with a space, which counts towards their length
+        limit = 74;
+    }
+    write!(out, "{}\r\n", &content[start..])?;
+    Ok(())
+}
+
+/// Escape `text` for a `TEXT` value
+fn text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn calendar() {
+        let mut activity = Activity::default();
+        let day = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        for (project, number, days) in [("hyper", 1, vec![day(1), day(4)]), ("h2", 2, vec![day(1)])]
+        {
+            let url = format!("https://github.com/hyperium/{project}/pull/{number}");
+            let mut entry = Entry::new(format!("Fix panic, again {number}"), ItemKind::PullRequest);
+            entry.days.extend(days);
+            let entries = activity.projects.entry(project.to_owned()).or_default();
+            entries.insert(url, entry);
+        }
+
+        let (titles, links) = (Titles::default(), Links::default());
+        let ics = Ics {
+            generated: DateTime::from_timestamp(1710000000, 0).unwrap(),
+            titles: &titles,
+            links: &links,
+        };
+        let mut out = Vec::new();
+        ics.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let calendar = String::from_utf8(out).unwrap();
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+        assert!(calendar.contains(
+            "UID:20240301-djc@tmog-events\r\nDTSTAMP:20240309T160000Z\r\n\
+             DTSTART;VALUE=DATE:20240301\r\nDTEND;VALUE=DATE:20240302\r\n\
+             SUMMARY:2 contributions across 2 projects\r\n"
+        ));
+        assert!(calendar.contains("SUMMARY:1 contribution across 1 project\r\n"));
+        // Long lines are folded
+        assert!(calendar.contains(
+            "DESCRIPTION:hyper: Fix panic\\, again 1 (https://github.com/hyperium/hyper/p\r\n \
+             ull/1)\r\n"
+        ));
+        assert!(calendar.lines().all(|line| line.len() <= 76));
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{BufRead, Write};
+
+use clap::ValueEnum;
+
+use crate::Format;
+
+/// Write a config file from the answers to a few questions (see `tmog-events init`)
+///
+/// The questions are written to `output` and answered from `input`, one line each. Empty answers
+/// take the default, if there is one.
+pub fn scaffold(input: &mut dyn BufRead, output: &mut dyn Write) -> anyhow::Result<String> {
+    let mut ask = |question: &str| -> anyhow::Result<String> {
+        write!(output, "{question}: ")?;
+        output.flush()?;
+        let mut line = String::new();
+        match input.read_line(&mut line)? {
+            0 => anyhow::bail!("input ran out before all questions were answered"),
+            _ => Ok(line.trim().to_owned()),
+        }
+    };
+
+    let user = loop {
+        match ask("GitHub user")? {
+            user if user.is_empty() => continue,
+            user => break user,
+        }
+    };
+    let scopes = ask(&format!(
+        "Users and organizations whose projects are your own (comma-separated) [{user}]"
+    ))?;
+    let scopes = match scopes.is_empty() {
+        true => vec![user.clone()],
+        false => scopes
+            .split(',')
+            .map(|scope| scope.trim().to_owned())
+            .filter(|scope| !scope.is_empty())
+            .collect(),
+    };
+    let formats = Format::value_variants()
+        .iter()
+        .filter_map(|format| format.to_possible_value())
+        .map(|value| value.get_name().to_owned())
+        .collect::<Vec<_>>();
+    let format = loop {
+        let format = ask(&format!("Report format ({}) [rst]", formats.join(", ")))?;
+        if format.is_empty() {
+            break "rst".to_owned();
+        } else if formats.contains(&format) {
+            break format;
+        }
+    };
+    let token = ask("GitHub token (leave empty to read it from GITHUB_TOKEN)")?;
+
+    let mut config = String::from("# Written by `tmog-events init` (see the README for more)\n");
+    config.push_str(&format!("user = {}\n", quoted(&user)));
+    let scopes = scopes.iter().map(|scope| quoted(scope)).collect::<Vec<_>>();
+    config.push_str(&format!("personal_scopes = [{}]\n", scopes.join(", ")));
+    config.push_str(&format!("format = {}\n", quoted(&format)));
+    match token.is_empty() {
+        true => config.push_str("# The token is read from the GITHUB_TOKEN environment variable\n"),
+        false => config.push_str(&format!("github_token = {}\n", quoted(&token))),
+    }
+    Ok(config)
+}
+
+/// Quote `value` as a TOML string (whose escapes are those of JSON)
+fn quoted(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaffolded() {
+        let mut input = "\ndjc\nrustls, hyperium,\nmarkdown\nhtml\n\n".as_bytes();
+        let mut output = Vec::new();
+        let config = scaffold(&mut input, &mut output).unwrap();
+        assert_eq!(
+            config,
+            "# Written by `tmog-events init` (see the README for more)\n\
+             user = \"djc\"\n\
+             personal_scopes = [\"rustls\", \"hyperium\"]\n\
+             format = \"html\"\n\
+             # The token is read from the GITHUB_TOKEN environment variable\n"
+        );
+        // Invalid answers are asked again
+        let questions = String::from_utf8(output).unwrap();
+        assert_eq!(questions.matches("GitHub user: ").count(), 2);
+        assert_eq!(questions.matches("Report format").count(), 2);
+
+        let mut input = "djc\n\n\nghp_\"x\n".as_bytes();
+        let config = scaffold(&mut input, &mut Vec::new()).unwrap();
+        assert!(config.contains("personal_scopes = [\"djc\"]\nformat = \"rst\"\n"));
+        assert!(config.ends_with("github_token = \"ghp_\\\"x\"\n"));
+        assert!(scaffold(&mut "djc\n".as_bytes(), &mut Vec::new()).is_err());
+    }
+}
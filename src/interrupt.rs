@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Set once the process is interrupted (see [`handle_interrupts()`])
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Stop collecting on Ctrl-C (`SIGINT`) instead of exiting, keeping the events fetched so far
+///
+/// Activity collected after the interrupt is marked as such (see [`Activity::interrupted`]), so
+/// that a partial report can still be written. Interrupting again exits as usual.
+///
+/// [`Activity::interrupted`]: crate::Activity::interrupted
+pub fn handle_interrupts() {
+    #[cfg(unix)]
+    // SAFETY: the handler only touches an atomic and calls `signal()`, which are safe in a
+    // signal handler
+    unsafe {
+        let handler = interrupt as extern "C" fn(libc::c_int);
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    // SAFETY: `signal()` is async-signal-safe
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+}
+
+/// Whether the process was interrupted
+pub(crate) fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Wait until the process is interrupted (which may be never)
+pub(crate) async fn interrupted() {
+    while !is_interrupted() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
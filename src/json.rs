@@ -0,0 +1,127 @@
+use serde_json::Value;
+
+/// Splits a JSON array that arrives in chunks (like a response body) into its elements
+///
+/// Only the element being received is kept in memory, rather than the whole array, so that
+/// memory use doesn't grow with the size of a page.
+#[derive(Debug, Default)]
+pub(crate) struct Elements {
+    buf: Vec<u8>,
+    /// How far `buf` was scanned
+    pos: usize,
+    /// Nesting depth at `pos`, where the elements of the array are at depth 1
+    depth: usize,
+    /// Where the element being received starts in `buf`
+    start: Option<usize>,
+    in_string: bool,
+    escaped: bool,
+    /// Whether the array was closed
+    done: bool,
+}
+
+impl Elements {
+    /// Add the next `chunk` of the array, returning the elements it completes
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> anyhow::Result<Vec<Value>> {
+        self.buf.extend_from_slice(chunk);
+        let mut elements = Vec::new();
+        while self.pos < self.buf.len() {
+            let (pos, byte) = (self.pos, self.buf[self.pos]);
+            self.pos += 1;
+            if self.in_string {
+                match (self.escaped, byte) {
+                    (true, _) => self.escaped = false,
+                    (false, b'\\') => self.escaped = true,
+                    (false, b'"') => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match (self.depth, byte) {
+                (_, b' ' | b'\t' | b'\n' | b'\r') => {}
+                (0, b'[') if !self.done => self.depth = 1,
+                (0, _) => anyhow::bail!("expected a JSON array"),
+                (_, b'[' | b'{') => {
+                    self.begin(pos);
+                    self.depth += 1;
+                }
+                // The end of a scalar element, or of the array
+                (1, b',' | b']') => {
+                    if let Some(start) = self.start.take() {
+                        elements.push(serde_json::from_slice(&self.buf[start..pos])?);
+                    }
+                    if byte == b']' {
+                        self.depth = 0;
+                        self.done = true;
+                    }
+                }
+                (_, b']' | b'}') => {
+                    self.depth -= 1;
+                    if let (1, Some(start)) = (self.depth, self.start) {
+                        elements.push(serde_json::from_slice(&self.buf[start..=pos])?);
+                        self.start = None;
+                    }
+                }
+                (_, b'"') => {
+                    self.begin(pos);
+                    self.in_string = true;
+                }
+                _ => self.begin(pos),
+            }
+        }
+
+        // Forget what was scanned, except for the element being received
+        let keep = self.start.unwrap_or(self.pos);
+        self.buf.drain(..keep);
+        self.pos -= keep;
+        self.start = self.start.map(|_| 0);
+        Ok(elements)
+    }
+
+    /// Check that the whole array was received
+    pub(crate) fn finish(self) -> anyhow::Result<()> {
+        match self.done {
+            true => Ok(()),
+            false => anyhow::bail!("truncated JSON array"),
+        }
+    }
+
+    /// Start an element at `pos`, unless inside one already
+    fn begin(&mut self, pos: usize) {
+        if self.depth == 1 && self.start.is_none() {
+            self.start = Some(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked() {
+        let json = br#" [{"a": [1, "]"]}, "x\"y", 12 , {"b": {}}] "#;
+        // Every way to split the array in two gives the same elements
+        for split in 0..json.len() {
+            let mut elements = Elements::default();
+            let (first, second) = json.split_at(split);
+            let mut values = elements.push(first).unwrap();
+            values.extend(elements.push(second).unwrap());
+            elements.finish().unwrap();
+            assert_eq!(
+                values,
+                [
+                    serde_json::json!({ "a": [1, "]"] }),
+                    serde_json::json!("x\"y"),
+                    serde_json::json!(12),
+                    serde_json::json!({ "b": {} }),
+                ]
+            );
+        }
+
+        let mut elements = Elements::default();
+        assert_eq!(elements.push(b"[1, 2").unwrap(), [serde_json::json!(1)]);
+        assert!(elements.finish().is_err());
+        assert!(Elements::default().push(b"{}").is_err());
+    }
+}
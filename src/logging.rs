@@ -0,0 +1,165 @@
+use std::fmt;
+use std::io::Write;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Writes logs as JSON, one object per line (for collecting the logs of a long-running `serve`)
+///
+/// Each object has the `timestamp`, `level`, `target` and `message` of the log event, its other
+/// `fields`, and the `spans` it happened in (outermost first), each with its `name` and `fields`.
+#[derive(Debug)]
+pub struct JsonLogs<W> {
+    writer: W,
+}
+
+impl<W: for<'a> MakeWriter<'a> + 'static> JsonLogs<W> {
+    /// Write logs to `writer` (like [`std::io::stderr`])
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<S, W> Layer<S> for JsonLogs<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<Fields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let message = fields.0.remove("message").unwrap_or_default();
+
+        let spans = ctx.event_scope(event).into_iter().flat_map(|scope| {
+            scope.from_root().map(|span| {
+                let fields = span
+                    .extensions()
+                    .get::<Fields>()
+                    .map(|fields| &fields.0)
+                    .cloned();
+                serde_json::json!({ "name": span.name(), "fields": fields.unwrap_or_default() })
+            })
+        });
+
+        let metadata = event.metadata();
+        let log = serde_json::json!({
+            "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "message": message,
+            "fields": fields.0,
+            "spans": spans.collect::<Vec<_>>(),
+        });
+        let mut line = log.to_string();
+        line.push('\n');
+        // There's nowhere to report failing to log
+        let _ = self.writer.make_writer().write_all(line.as_bytes());
+    }
+}
+
+/// The fields of a span or event, as JSON values
+#[derive(Default)]
+struct Fields(Map<String, Value>);
+
+impl Visit for Fields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_owned(), format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{info, info_span};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn logged() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer = {
+            let buf = buf.clone();
+            move || Buffer(buf.clone())
+        };
+        let subscriber = tracing_subscriber::registry().with(JsonLogs::new(writer));
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("load", user = "djc").entered();
+            info!(url = "https://api.github.com", page = 2, "fetching events");
+        });
+
+        let logs = buf.lock().unwrap();
+        let log = serde_json::from_slice::<Value>(&logs).unwrap();
+        assert_eq!(log["level"], "INFO");
+        assert_eq!(log["message"], "fetching events");
+        assert_eq!(
+            log["fields"],
+            serde_json::json!({ "url": "https://api.github.com", "page": 2 })
+        );
+        assert_eq!(
+            log["spans"],
+            serde_json::json!([{ "name": "load", "fields": { "user": "djc" } }])
+        );
+    }
+
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -1,202 +1,2238 @@
-use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{self, BufReader, BufWriter, Write};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, IsTerminal, Write};
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use std::{env, fs};
 
 use anyhow::Context;
-use clap::Parser;
-use gcp_auth::{provider, TokenProvider};
-use serde::{Deserialize, Serialize};
-use tracing::info;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use gcp_auth::provider;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+use tmog_events::{
+    diff, handle_interrupts, heatmap, parse_date, render_report, scaffold, toot, Access, Activity,
+    Annotations, Archive, Atom, Bitbucket, Blog, BotFilter, Calendar, Category, Cipher, Collector,
+    Comparison, Curation, Dashboard, EventSource, EventType, Excerpt, Export, FilterKind, Forge,
+    Forges, Format, GitHub, GitLab, Gitea, Header, Hook, Http, Interval, JsonLogs, LinkPrefix,
+    Links, Locale, Mastodon, Matrix, Period, ProjectOrder, Projects, Publish, Published,
+    RateLimited, Renderer, Replay, RepoFilter, Report, Reports, Response, Rst, ScoreWeights,
+    Section, Seen, Server, Shortcodes, Sizes, SmallProjects, Snapshot, Sort, Sourcehut, SplitBy,
+    Sponsors, Stats, Titles, UrlRule, Versioned, Webhook, WeekStart, Weights, Window,
+};
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+async fn main() -> ExitCode {
+    let mut partial = false;
+    match run(&mut partial).await {
+        Ok(()) if partial => ExitCode::from(PARTIAL),
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            ExitCode::from(exit_code(&error))
+        }
+    }
+}
 
-    let mut args = Args::parse();
-    let provider = provider().await?;
+/// Run the command, setting `partial` if a report is missing events
+async fn run(partial: &mut bool) -> anyhow::Result<()> {
+    let args = Args::parse();
+    logging(&args);
+    // There's no config to read yet
+    if let Some(Command::Init { completions, force }) = &args.command {
+        return init(&args.config, *completions, *force);
+    } else if let Some(Command::Schema) = &args.command {
+        let schema = serde_json::to_string_pretty(&Versioned::schema())?;
+        println!("{schema}");
+        return Ok(());
+    } else if let Some(Command::Diff { old, new }) = &args.command {
+        return diff_reports(old, new);
+    }
     let config = fs::read(&args.config).context("failed to read config file")?;
     let config = basic_toml::from_slice::<Config>(&config)?;
 
-    let client = reqwest::Client::builder()
-        .user_agent(format!(
-            "{}@{}",
-            env!("CARGO_PKG_NAME"),
-            env!("CARGO_PKG_VERSION")
-        ))
-        .build()?;
-
-    args.month.retain(|c| c != '-');
-    let cache = format!("{}.json", &args.month);
-    let events = match File::open(&cache) {
-        Ok(file) => {
-            info!(cache, "loading events from cache");
-            serde_json::from_reader::<_, Vec<String>>(BufReader::new(file))?
-        }
-        Err(err) if err.kind() == io::ErrorKind::NotFound => {
-            info!(cache, "failed to open cache: {err}");
-            let events = query(
-                &args.month,
-                &config.user,
-                &config.gcp_project,
-                &*provider,
-                &client,
-            )
-            .await?;
-            let mut writer = BufWriter::new(File::create(&cache)?);
-            info!(cache, "saving events to cache");
-            serde_json::to_writer(&mut writer, &events)?;
-            events
-        }
-        Err(err) => return Err(err.into()),
-    };
-
-    let mut map = HashMap::<String, HashMap<String, String>>::default();
-    for event in events {
-        let event = serde_json::from_str::<Event>(&event)?;
-        let item = match (event.issue, event.pull_request) {
-            (Some(issue), None) => issue,
-            (None, Some(pr)) => pr,
-            _ => continue,
+    // Proxies from the environment are used unless one is configured
+    let connect_timeout = args
+        .connect_timeout
+        .or(config.connect_timeout)
+        .unwrap_or(10);
+    let read_timeout = args.read_timeout.or(config.read_timeout).unwrap_or(30);
+    let user_agent = match &config.user_agent {
+        Some(user_agent) => user_agent.clone(),
+        None => format!("{}@{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+    };
+    let mut client = reqwest::Client::builder()
+        .user_agent(user_agent)
+        .connect_timeout(Duration::from_secs(connect_timeout))
+        .read_timeout(Duration::from_secs(read_timeout));
+    if let Some(proxy) = args.proxy.as_ref().or(config.proxy.as_ref()) {
+        let proxy =
+            reqwest::Proxy::all(proxy).with_context(|| format!("invalid proxy {proxy:?}"))?;
+        client = client.proxy(proxy);
+    }
+    let client = client.build()?;
+
+    let users = match (args.users.is_empty(), config.team.is_empty()) {
+        (false, _) => args.users.clone(),
+        (true, false) => config.team.clone(),
+        (true, true) => match &config.user {
+            Some(user) => vec![user.clone()],
+            None => anyhow::bail!("no user configured (use `user`, `team` or --user)"),
+        },
+    };
+
+    if let Some(Command::Annotate { url, note, clear }) = &args.command {
+        let (path, cipher) = (Path::new(ANNOTATIONS), cache_cipher(&config)?);
+        let mut annotations = Annotations::load(path, cipher.as_ref())?;
+        if *clear && !annotations.clear(url) {
+            warn!(url, "no notes to clear");
+        }
+        if let Some(note) = note {
+            annotations.add(url, note.clone());
+        }
+        return annotations.save(path, cipher.as_ref());
+    }
+
+    if let Some(Command::RateLimit) = &args.command {
+        return rate_limits(&args, &config, client).await;
+    }
+
+    let mut collector = collector(&args, &config, client).await?;
+    // A dry run makes no requests at all
+    let access = match args.dry_run {
+        true => Access::default(),
+        false => collector.check_access(&users).await?,
+    };
+    if (args.member_scopes || config.member_scopes) && !args.dry_run {
+        collector.add_member_scopes(&users).await;
+    }
+    let warn_days = config.token_expiry_warning.unwrap_or(7);
+    let expiring = access
+        .expires_at
+        .filter(|expires_at| *expires_at - Utc::now() < chrono::Duration::days(warn_days));
+    if let Some(expires_at) = expiring {
+        let expiring = TokenExpiring { expires_at };
+        if args.check {
+            return Err(expiring.into());
+        }
+        warn!("{expiring}");
+    }
+    if args.check {
+        return Ok(());
+    }
+    let date = match &args.command {
+        Some(
+            Command::Estimate { date }
+            | Command::Archive { date, .. }
+            | Command::Review { date }
+            | Command::Contributions { date }
+            | Command::Dashboard { date, .. }
+            | Command::Export { until: date, .. },
+        ) => date.as_deref(),
+        Some(
+            Command::Annotate { .. }
+            | Command::Init { .. }
+            | Command::RateLimit
+            | Command::Schema
+            | Command::Diff { .. }
+            | Command::Serve { .. }
+            | Command::Watch { .. }
+            | Command::Changelog { .. },
+        )
+        | None => args.date.as_deref(),
+    };
+    let period = match args.each_month {
+        Some(_) => Period::Month,
+        None => args.period.or(config.period).unwrap_or_default(),
+    };
+    let calendar = Calendar {
+        week_start: args.week_start.or(config.week_start).unwrap_or_default(),
+        timezone: args.timezone.or(config.timezone).unwrap_or(Tz::UTC),
+    };
+    let window = args.window.or(config.window).unwrap_or_default();
+    let date = match date {
+        Some(date) => parse_date(date)?,
+        None => calendar.today(),
+    };
+    let intervals = match &args.each_month {
+        Some(range) => months(range, &calendar)?,
+        None => vec![period.interval(date, args.offset, &calendar)?],
+    };
+
+    if args.dry_run {
+        return dry_run(&collector, &intervals, &users);
+    }
+    if let Some(Command::Estimate { .. }) = &args.command {
+        let concurrency = args.concurrency.or(config.concurrency).unwrap_or(8);
+        return estimate(&collector, intervals[0], &users, concurrency).await;
+    }
+
+    let contributions = matches!(args.command, Some(Command::Contributions { .. }));
+    let changelog = matches!(args.command, Some(Command::Changelog { .. }));
+    let mut render = Rst {
+        attribution: args.attribution || users.len() > 1,
+        roles: args.roles || config.roles,
+        project_order: args
+            .project_order
+            .or(config.project_order)
+            .unwrap_or_default(),
+        min_items: args.min_items.or(config.min_items).unwrap_or(1),
+        small_projects: args
+            .small_projects
+            .or(config.small_projects)
+            .unwrap_or_default(),
+        sort: args.sort.or(config.sort).unwrap_or_default(),
+        group_by_kind: args.group_by_kind || config.group_by_kind,
+        states: args.states || config.states,
+        sizes: args.sizes.or(config.sizes),
+        triage: args.triage || config.triage,
+        pairing: args.pairing || config.pairing,
+        merged_section: args.merged_section || config.merged_section,
+        mentions_section: args.mentions_section || config.mentions_section,
+        split_maintained: args.split_maintained || config.split_maintained,
+        highlights: args.highlights.or(config.highlights),
+        weights: config.highlight_weights,
+        releases_section: args.releases_section || config.releases_section,
+        numbers: args.numbers || config.numbers,
+        // The comparison goes into the summary statistics
+        stats: args
+            .stats
+            .or(config.stats)
+            .or(contributions.then_some(Stats::Top)),
+        scores: (args.scores || config.scores).then_some(config.score_weights),
+        days: args.days || config.days,
+        times: args.times || config.times,
+        comparison: None,
+        titles: Titles {
+            max_len: args.max_title_len.or(config.max_title_len),
+            strip_prefixes: args.strip_prefixes || config.strip_prefixes,
+            shortcodes: args.emoji.or(config.emoji),
+        },
+        appendix: args.appendix || config.appendix,
+        sponsors: args.sponsors.or(config.sponsors),
+        categories: config.categories,
+        sections: config.sections,
+        links: Links {
+            prefixes: config.link_prefixes,
+            web_url: collector.web_url().to_owned(),
+            forges: match config.forges.is_empty() || args.forge.is_some() {
+                true => Vec::new(),
+                false => collector.web_urls().to_vec(),
+            },
+        },
+        locale: args.locale.or(config.locale).unwrap_or_default(),
+        header: None,
+        streaks: None,
+        generated: None,
+    };
+
+    let title = args.title.as_deref().or(config.title.as_deref());
+    let format = args.format.or(config.format).unwrap_or_default();
+    let hook = args.hook.clone().or(config.hook.clone()).map(Hook::new);
+    if let Some(Command::Dashboard { dir, .. }) = &args.command {
+        let interval = Period::Month.interval(date, 0, &calendar)?;
+        let mut intervals = vec![interval];
+        intervals.extend(collector.cached_periods(interval, Period::Month, &calendar, &users)?);
+        let mut months = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            let mut activity = collect(&collector, interval, &users, window).await?;
+            *partial |= !activity.is_complete();
+            Curation::load(&curation_path(interval), collector.cipher())?.apply(&mut activity);
+            if let Some(hook) = &hook {
+                hook.apply(&mut activity)?;
+            }
+            months.push((interval, activity));
+        }
+        let dashboard = Dashboard {
+            titles: &render.titles,
+            links: &render.links,
+            locale: render.locale,
+        };
+        dashboard.write(dir, &months, &users)?;
+        info!(dir = %dir.display(), months = months.len(), "wrote dashboard");
+        return Ok(());
+    }
+    if let Some(Command::Export { since, to, .. }) = &args.command {
+        let first = parse_date(since)?;
+        if first > date {
+            anyhow::bail!("--since {since} is after the last month to export");
+        }
+        let mut export = Export::open(to)?;
+        handle_interrupts();
+        let mut exported = 0;
+        for interval in months_between(first, date, &calendar)? {
+            if export.contains(interval) {
+                continue;
+            }
+            let activity = collect(&collector, interval, &users, window).await?;
+            *partial |= !activity.is_complete();
+            if activity.interrupted {
+                let name = Period::Month.name(interval, Locale::En);
+                anyhow::bail!("interrupted before collecting all events for {name}");
+            }
+            if !export.write(interval, &activity, Utc::now())? {
+                let month = Period::Month.name(interval, Locale::En);
+                info!(
+                    month,
+                    "not recording the month as exported, so it's written again later"
+                );
+            }
+            exported += 1;
+        }
+        info!(dir = %to.display(), months = exported, "exported activity");
+        return Ok(());
+    }
+    if let Some(Command::Serve { listen }) = &args.command {
+        return serve(
+            *listen,
+            &collector,
+            &mut render,
+            &users,
+            window,
+            &calendar,
+            title,
+            hook.as_ref(),
+        )
+        .await;
+    }
+    if let Some(Command::Watch {
+        since_last_run,
+        every,
+        feeds,
+    }) = &args.command
+    {
+        let every = every.map(|minutes| Duration::from_secs(minutes * 60));
+        return watch(
+            &collector,
+            &mut render,
+            &users,
+            window,
+            period,
+            &calendar,
+            title,
+            format,
+            *since_last_run,
+            every,
+            feeds.as_deref(),
+            &config.follow,
+            hook.as_ref(),
+        )
+        .await;
+    }
+    let publish = overrides(&args.publish, &config.publish);
+    let blog = match publish.contains(&Publish::Blog) {
+        true => Some(
+            config
+                .blog
+                .as_ref()
+                .context("publishing to a blog needs a `[blog]` configuration")?,
+        ),
+        false => None,
+    };
+    let webhook = match publish.contains(&Publish::Webhook) {
+        true => Some(
+            config
+                .webhook
+                .as_ref()
+                .context("posting to a webhook needs a `[webhook]` configuration")?,
+        ),
+        false => None,
+    };
+    let mastodon = match publish.contains(&Publish::Mastodon) {
+        true => {
+            let mastodon = config
+                .mastodon
+                .as_ref()
+                .context("posting to Mastodon needs a `[mastodon]` configuration")?;
+            let token = mastodon
+                .token
+                .clone()
+                .or_else(|| env::var("MASTODON_TOKEN").ok());
+            Some((
+                mastodon,
+                token.context("posting to Mastodon needs a token (use `token`)")?,
+            ))
+        }
+        false => None,
+    };
+    let matrix = match publish.contains(&Publish::Matrix) {
+        true => {
+            let matrix = config
+                .matrix
+                .as_ref()
+                .context("posting to Matrix needs a `[matrix]` configuration")?;
+            let token = matrix
+                .token
+                .clone()
+                .or_else(|| env::var("MATRIX_TOKEN").ok());
+            Some((
+                matrix,
+                token.context("posting to Matrix needs a token (use `token`)")?,
+            ))
+        }
+        false => None,
+    };
+    let toot_limit = config.toot_limit.unwrap_or(500);
+    let pull_request = blog.filter(|blog| blog.pull_request.is_some());
+    let github_token = config
+        .github_token
+        .clone()
+        .or_else(|| env::var("GITHUB_TOKEN").ok());
+    // Fail before collecting anything
+    if publish.contains(&Publish::Gist) && github_token.is_none() {
+        anyhow::bail!("publishing gists needs a GitHub token (use `github_token` or GITHUB_TOKEN)");
+    }
+    if contributions && github_token.is_none() {
+        anyhow::bail!(
+            "the contribution calendar needs a GitHub token (use `github_token` or GITHUB_TOKEN)"
+        );
+    }
+    if pull_request.is_some() && github_token.is_none() {
+        anyhow::bail!(
+            "opening pull requests needs a GitHub token (use `github_token` or GITHUB_TOKEN)"
+        );
+    }
+    let needs_github =
+        publish.contains(&Publish::Gist) || pull_request.is_some() || contributions || changelog;
+    let github = match needs_github {
+        true => Some(GitHub::new(
+            args.api_url
+                .clone()
+                .or_else(|| config.api_url.clone())
+                .unwrap_or_else(|| "https://api.github.com".to_owned()),
+            github_token.clone(),
+        )?),
+        false => None,
+    };
+    if pull_request.is_some_and(|blog| blog.github_repo().is_none()) {
+        anyhow::bail!("pull requests can only be opened for blogs on github.com");
+    }
+    if let (Some(Command::Changelog { repo, since }), Some(github)) = (&args.command, &github) {
+        return print_changelog(
+            &collector,
+            github,
+            &mut render,
+            repo,
+            since,
+            window,
+            &calendar,
+            format,
+        )
+        .await;
+    }
+    let split_by = args.split_by.or(config.split_by);
+    let extension = format.extension();
+    let (each_month, owned, each_owned) = (
+        format!("{{year}}{{month}}.{extension}"),
+        format!("{{owner}}.{extension}"),
+        format!("{{year}}{{month}}-{{owner}}.{extension}"),
+    );
+    let output = match (args.output.as_deref(), config.output.as_deref()) {
+        (Some(output), _) | (None, Some(output)) => Some(output),
+        // Separate reports can't all go to stdout
+        (None, None) => match (split_by.is_some(), args.each_month.is_some()) {
+            (true, true) => Some(each_owned.as_str()),
+            (true, false) => Some(owned.as_str()),
+            (false, true) => Some(each_month.as_str()),
+            (false, false) => None,
+        },
+    };
+    let footer = args.footer || config.footer;
+    let clipboard = args.clipboard || config.clipboard;
+    let streaks = args.streaks || config.streaks;
+    let seen = args.seen.or(config.seen);
+    let mut published = match seen {
+        Some(_) => Some(Published::load(Path::new(PUBLISHED), collector.cipher())?),
+        None => None,
+    };
+
+    if args.merge && output.is_none() && !matches!(args.command, Some(Command::Archive { .. })) {
+        anyhow::bail!("--merge needs a report to merge into (use --output or archive)");
+    } else if args.merge && format != Format::Rst {
+        anyhow::bail!("--merge only works for reStructuredText reports");
+    }
+    if split_by.is_some() {
+        if !output.is_some_and(|output| output.contains("{owner}")) {
+            anyhow::bail!("--split-by needs `{{owner}}` in the --output path");
+        }
+        let archiving = matches!(args.command, Some(Command::Archive { .. }));
+        let publishing = [Publish::Gist, Publish::Blog, Publish::Matrix];
+        if archiving || clipboard || publishing.iter().any(|p| publish.contains(p)) {
+            anyhow::bail!("--split-by can't be combined with archive, --clipboard or --publish");
+        }
+    }
+
+    let archive = match &args.command {
+        Some(Command::Archive { dir, force, .. }) => Some((Reports::new(dir.clone()), *force)),
+        _ => None,
+    };
+
+    // On Ctrl-C, write a partial report for the events fetched so far
+    handle_interrupts();
+    // With `--each-month`, the previous month's activity is reused for the comparison
+    let mut previous = None::<(Activity, Interval)>;
+    for interval in intervals {
+        let archived = match &archive {
+            Some((reports, force)) if !args.toot => {
+                let force = *force || args.merge;
+                Some((reports, reports.claim(period, interval, force)?))
+            }
+            _ => None,
+        };
+        let mut activity = collect(&collector, interval, &users, window).await?;
+        *partial |= !activity.is_complete();
+        let path = curation_path(interval);
+        let mut curation = Curation::load(&path, collector.cipher())?;
+        if let Some(Command::Review { .. }) = &args.command {
+            curation.review(&activity, &period.name(interval, render.locale))?;
+            curation.save(&path, collector.cipher())?;
+            info!(path = %path.display(), "saved review");
+        } else if args.interactive {
+            // Prompt on stderr, so that the prompts don't end up in the report
+            curation.prompt(&activity, &mut io::stdin().lock(), &mut io::stderr())?;
+            curation.save(&path, collector.cipher())?;
+            info!(path = %path.display(), "saved decisions");
+        }
+        curation.apply(&mut activity);
+        if let (Some(published), Some(seen)) = (&published, seen) {
+            published.apply(&mut activity, interval, seen);
+        }
+        if let Some(hook) = &hook {
+            hook.apply(&mut activity)?;
+        }
+        if let Some(github) = github.as_ref().filter(|_| contributions) {
+            let mut calendar = 0;
+            for user in &users {
+                calendar += github
+                    .contributions(user, interval, collector.http())
+                    .await
+                    .context("failed to look up the contribution calendar")?;
+            }
+            activity.calendar = Some(calendar);
+        }
+        if activity.interrupted && (args.toot || mastodon.is_some()) {
+            anyhow::bail!("interrupted before collecting all events");
+        }
+        if args.toot || mastodon.is_some() {
+            let name = period.name(interval, render.locale);
+            let toot = toot(&activity, &name, toot_limit);
+            if args.toot {
+                println!("{toot}");
+            }
+            if let Some((mastodon, token)) = &mastodon {
+                let url = mastodon.post(&toot, token, collector.http()).await?;
+                eprintln!("{url}");
+            }
+            if args.toot {
+                previous = Some((activity, interval));
+                continue;
+            }
+        }
+        render.comparison = match (args.compare || config.compare, &previous) {
+            (false, _) => None,
+            (true, Some((before, last))) => {
+                Some(activity.compare(before, period.name(*last, render.locale)))
+            }
+            (true, None) => {
+                let previous = period.interval(interval.date(interval.start), 1, &calendar);
+                let users = &users;
+                compare(
+                    &collector,
+                    &activity,
+                    period,
+                    previous,
+                    render.locale,
+                    users,
+                    window,
+                )
+                .await
+            }
         };
 
-        let project = match item.project() {
-            Some(project) => project,
-            None => return Err(anyhow::Error::msg("no project for {item:?}")),
+        render.header = header(title, period, interval, render.locale);
+        if streaks {
+            let earlier = collector.cached_days(interval, period, &calendar, &users)?;
+            let today = Utc::now().with_timezone(&calendar.timezone).date_naive();
+            render.streaks = Some(activity.streaks(interval, &earlier, today));
+        }
+        render.generated = footer.then(|| Utc::now().with_timezone(&calendar.timezone));
+        // The report as written (including any items merged into)
+        let report = match (split_by, archived, output) {
+            // With a file for each owner, there's no single report to copy or publish
+            (Some(SplitBy::Owner), _, template) => {
+                for (owner, activity) in activity.by_owner() {
+                    let report = report(format, &render, &activity, &users, period, interval)?;
+                    let template = template.unwrap_or_default().replace("{owner}", &owner);
+                    let path = PathBuf::from(output_path(&template, interval));
+                    written(&path, report, args.merge)?;
+                }
+                Vec::new()
+            }
+            (None, Some((reports, path)), _) => {
+                let report = report(format, &render, &activity, &users, period, interval)?;
+                let report = merged(&path, report, args.merge)?;
+                reports.write(&path, &report)?;
+                info!(path = %path.display(), "archived report");
+                report
+            }
+            (None, None, Some(template)) => {
+                let report = report(format, &render, &activity, &users, period, interval)?;
+                let path = PathBuf::from(output_path(template, interval));
+                written(&path, report, args.merge)?
+            }
+            (None, None, None) => {
+                let report = report(format, &render, &activity, &users, period, interval)?;
+                std::io::stdout().lock().write_all(&report)?;
+                report
+            }
         };
+        if clipboard {
+            let report = str::from_utf8(&report)?;
+            arboard::Clipboard::new()
+                .and_then(|mut clipboard| clipboard.set_text(report))
+                .context("failed to copy the report to the clipboard")?;
+            info!("copied report to the clipboard");
+        }
+        // A partial report shouldn't be published
+        if activity.interrupted {
+            let name = period.name(interval, Locale::En);
+            anyhow::bail!(
+                "interrupted before collecting all events, so the report for {name} is partial"
+            );
+        }
+        if let Some(published) = &mut published {
+            published.record(&activity, interval);
+            published.save(Path::new(PUBLISHED), collector.cipher())?;
+        }
+
+        if let Some(github) = github.as_ref().filter(|_| publish.contains(&Publish::Gist)) {
+            let name = format!("tmog-events-{}.{}", interval.key(), format.extension());
+            let description = period.name(interval, render.locale);
+            let report = str::from_utf8(&report)?;
+            let url = github
+                .publish_gist(&name, report, &description, collector.http())
+                .await
+                .context("failed to publish gist")?;
+            // Not on stdout, where it would end up in the report
+            eprintln!("{url}");
+        }
+
+        if let Some(blog) = blog {
+            let path = PathBuf::from(output_path(&blog.path, interval));
+            let message = format!("Add report for {}", period.name(interval, Locale::En));
+            let branch = format!("tmog-events/{}", interval.key());
+            let committed = blog.commit(&path, &report, &message, &branch)?;
+            if let (Some(github), Some(repo), Some(base)) =
+                (&github, blog.github_repo(), &blog.pull_request)
+            {
+                // Nothing to open a pull request for if the report didn't change
+                if committed {
+                    let url = github
+                        .pull_request(repo, &branch, base, &message, collector.http())
+                        .await
+                        .context("failed to open pull request")?;
+                    eprintln!("{url}");
+                }
+            }
+        }
 
-        map.entry(project.to_owned())
-            .or_insert_with(HashMap::<String, String>::default)
-            .insert(item.html_url, item.title);
+        if let Some((matrix, token)) = &matrix {
+            let report = str::from_utf8(&report)?;
+            let url = matrix.post(report, token, collector.http()).await?;
+            eprintln!("{url}");
+        }
+
+        if let Some(webhook) = webhook {
+            let message = webhook.message(&activity, &period.name(interval, render.locale));
+            webhook
+                .post(&message, collector.http())
+                .await
+                .context("failed to post to webhook")?;
+        }
+
+        if args.heatmap || config.heatmap {
+            heatmap(
+                &mut std::io::stderr().lock(),
+                &activity,
+                interval,
+                &calendar,
+            )?;
+        }
+        previous = Some((activity, interval));
     }
 
-    let mut stdout = std::io::stdout().lock();
-    for (project, items) in map {
-        write!(stdout, "{}\n", project).unwrap();
-        for _ in 0..project.len() {
-            write!(stdout, "=").unwrap();
+    if let Some((reports, _)) = &archive {
+        reports.index(render.locale)?;
+    }
+    Ok(())
+}
+
+/// The exit code for `error`, so that scripts can tell whether retrying may help
+fn exit_code(error: &anyhow::Error) -> u8 {
+    if error.chain().any(|cause| cause.is::<RateLimited>()) {
+        return RATE_LIMITED;
+    }
+    if error.is::<TokenExpiring>() {
+        return EXPIRING;
+    }
+    if error.is::<Differs>() {
+        return DIFFERENT;
+    }
+
+    for cause in error.chain() {
+        if cause.is::<gcp_auth::Error>() {
+            return AUTH;
+        }
+        let Some(error) = cause.downcast_ref::<reqwest::Error>() else {
+            continue;
+        };
+        match error.status() {
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => return AUTH,
+            None if error.is_connect() || error.is_timeout() || error.is_request() => {
+                return NETWORK
+            }
+            _ => {}
         }
-        write!(stdout, "\n\n").unwrap();
+    }
+    1
+}
+
+/// Print the items that differ between the JSON reports at `old` and `new` (see `Command::Diff`)
+fn diff_reports(old: &Path, new: &Path) -> anyhow::Result<()> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+    };
+    let (old, new) = (read(old)?, read(new)?);
+    let differences = diff(&old, &new)?;
+    for difference in &differences {
+        println!("{difference}");
+    }
+    match differences.len() {
+        0 => Ok(()),
+        items => Err(Differs { items }.into()),
+    }
+}
+
+/// The reports compared by `diff` differ
+#[derive(Debug, thiserror::Error)]
+#[error("the reports differ in {items} items")]
+struct Differs {
+    items: usize,
+}
 
-        let items = items
-            .into_iter()
-            .map(|(html_url, title)| ItemMeta { title, html_url });
-        for ItemMeta { title, html_url } in items {
-            write!(stdout, "* `{title} <{html_url}>`_\n")?;
+/// The token expires soon, so it should be rotated before runs start failing
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "the token expires at {} (in {} days): rotate it before it does",
+    expires_at.format("%Y-%m-%d %H:%M UTC"),
+    (*expires_at - Utc::now()).num_days()
+)]
+struct TokenExpiring {
+    expires_at: DateTime<Utc>,
+}
+
+/// Exit code for reports that were written, but are missing events
+const PARTIAL: u8 = 3;
+/// Exit code for failing to authenticate (or lacking permission)
+const AUTH: u8 = 4;
+/// Exit code for running out of rate limit
+const RATE_LIMITED: u8 = 5;
+/// Exit code for failing to reach a host (or timing out)
+const NETWORK: u8 = 6;
+/// Exit code for a token that expires soon (with `--check`)
+const EXPIRING: u8 = 7;
+/// Exit code for reports that differ (with `diff`)
+const DIFFERENT: u8 = 8;
+
+/// Collect the activity of `users` during `interval` (with any notes), warning about missing
+/// events
+async fn collect(
+    collector: &Collector,
+    interval: Interval,
+    users: &[String],
+    window: Window,
+) -> anyhow::Result<Activity> {
+    let mut activity = match collector.collect(interval, users, window).await?.complete() {
+        Ok(activity) => activity,
+        Err(incomplete) => {
+            for source in incomplete.incomplete() {
+                warn!(%source, "report is missing events");
+            }
+            *incomplete.activity
         }
+    };
+    Annotations::load(Path::new(ANNOTATIONS), collector.cipher())?.apply(&mut activity);
+    Ok(activity)
+}
 
-        write!(stdout, "\n").unwrap();
+/// Print the remaining GitHub API quota of each kind, and when it's replenished (see
+/// `Command::RateLimit`)
+async fn rate_limits(args: &Args, config: &Config, client: reqwest::Client) -> anyhow::Result<()> {
+    let github = GitHub::new(
+        args.api_url
+            .clone()
+            .or_else(|| config.api_url.clone())
+            .unwrap_or_else(|| "https://api.github.com".to_owned()),
+        config
+            .github_token
+            .clone()
+            .or_else(|| env::var("GITHUB_TOKEN").ok()),
+    )?;
+    let limits = github.rate_limits(&Http::new(client, 1, 1)).await?;
+    let now = Utc::now();
+    for resource in ["core", "search", "graphql"] {
+        let Some(limit) = limits.get(resource) else {
+            continue;
+        };
+        let minutes = (limit.reset - now).num_minutes().max(0);
+        println!(
+            "{resource}: {}/{} left, resets at {} (in {minutes} min)",
+            limit.remaining,
+            limit.limit,
+            limit.reset.format("%H:%M UTC"),
+        );
+    }
+    Ok(())
+}
+
+/// Write a config file to `path` (unless there is one already) from questions asked on stderr,
+/// and print the `completions` for a shell (see `Command::Init`)
+fn init(path: &Path, completions: Option<Shell>, force: bool) -> anyhow::Result<()> {
+    match path.exists() && !force {
+        true => {
+            let path = path.display();
+            warn!(%path, "keeping the existing config (pass --force to replace it)");
+        }
+        false => {
+            let config = scaffold(&mut io::stdin().lock(), &mut io::stderr())?;
+            fs::write(path, config)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            info!(path = %path.display(), "wrote config");
+        }
     }
 
+    if let Some(shell) = completions {
+        let name = env!("CARGO_PKG_NAME");
+        clap_complete::generate(shell, &mut Args::command(), name, &mut io::stdout());
+    }
     Ok(())
 }
 
-async fn query(
-    month: &str,
-    user: &str,
-    project: &str,
-    provider: &dyn TokenProvider,
-    client: &reqwest::Client,
-) -> anyhow::Result<Vec<String>> {
-    info!("requesting token");
-    let token = provider
-        .token(&["https://www.googleapis.com/auth/bigquery"])
-        .await?;
-
-    info!(month, user, project, "querying BigQuery");
-    let url = format!("{BIG_QUERY}/projects/{project}/queries");
-    let data = JobsQueryData { query: format!("SELECT payload FROM githubarchive.month.{month} WHERE actor.login = '{user}' ORDER BY created_at") };
-    let rsp = client
-        .post(&url)
-        .json(&data)
-        .bearer_auth(token.as_str())
-        .send()
-        .await?;
-
-    let data = rsp.json::<JobsQueryResponse>().await?;
-    Ok(data
-        .rows
-        .into_iter()
-        .map(|mut row| {
-            assert_eq!(row.f.len(), 1);
-            row.f.pop().unwrap().v
-        })
-        .collect())
-}
-
-#[allow(dead_code)] // Helper function for generating formattable JSON
-fn dump(path: impl AsRef<Path>, events: &[String]) -> anyhow::Result<()> {
-    let mut writer = BufWriter::new(File::create(path)?);
-    write!(writer, "[\n    ")?;
-    for (i, event) in events.iter().enumerate() {
-        if i > 0 {
-            write!(writer, ",\n    ")?;
-        }
-        write!(writer, "{}", event)?;
-    }
-    write!(writer, "\n]\n")?;
+/// Where notes attached to items are kept (see `Command::Annotate`)
+const ANNOTATIONS: &str = "annotations.json";
+/// Where the items listed in earlier reports are kept (see `--seen`)
+const PUBLISHED: &str = "published.json";
+/// Where the items of the last run of `watch` are kept
+const WATCHED: &str = "watch.json";
+
+/// Render the report for `activity` during `interval` in `format`
+fn report(
+    format: Format,
+    render: &Rst,
+    activity: &Activity,
+    users: &[String],
+    period: Period,
+    interval: Interval,
+) -> anyhow::Result<Vec<u8>> {
+    let report = Report {
+        activity,
+        users,
+        name: period.name(interval, render.locale),
+        interval,
+        generated: Utc::now(),
+        render,
+    };
+    Ok(render_report(&report, format)?.into_bytes())
+}
+
+/// Print the changelog of the pull requests merged in `repo` since `tag` (see
+/// `Command::Changelog`)
+#[allow(clippy::too_many_arguments)]
+async fn print_changelog(
+    collector: &Collector,
+    github: &GitHub,
+    render: &mut Rst,
+    repo: &str,
+    tag: &str,
+    window: Window,
+    calendar: &Calendar,
+    format: Format,
+) -> anyhow::Result<()> {
+    let start = github
+        .tagged(repo, tag, collector.http())
+        .await
+        .with_context(|| format!("failed to look up {tag} in {repo}"))?;
+    let interval = Interval {
+        start,
+        end: Utc::now(),
+        timezone: calendar.timezone,
+    };
+    let mut activity = collector.follow(interval, repo, window).await?;
+    // Only what was merged goes into the release
+    activity.retain_merged();
+    if render.categories.is_empty() {
+        render.categories = Category::changelog();
+    }
+    let (first, last) = interval.days();
+    let title = format!("Changes since {tag}");
+    render.header = Some(Header { title, first, last });
+    let report = Report {
+        activity: &activity,
+        users: &[repo.to_owned()],
+        name: format!("{repo} since {tag}"),
+        interval,
+        generated: Utc::now(),
+        render,
+    };
+    io::stdout()
+        .lock()
+        .write_all(render_report(&report, format)?.as_bytes())?;
     Ok(())
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Event {
-    issue: Option<ItemMeta>,
-    pull_request: Option<ItemMeta>,
+/// Log to stderr at the level set by `RUST_LOG` or else the verbosity flags (only for this crate)
+fn logging(args: &Args) {
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => {
+            let level = match (args.quiet, args.verbose) {
+                (true, _) => "error",
+                (false, 0) => "warn",
+                (false, 1) => "warn,tmog_events=info",
+                (false, 2) => "warn,tmog_events=debug",
+                (false, _) => "warn,tmog_events=trace",
+            };
+            EnvFilter::new(level)
+        }
+    };
+
+    let registry = tracing_subscriber::registry().with(filter);
+    match args.log_json {
+        true => registry.with(JsonLogs::new(io::stderr)).init(),
+        false => registry
+            .with(tracing_subscriber::fmt::layer().with_writer(io::stderr))
+            .init(),
+    }
+}
+
+/// The cipher to encrypt the cache (and the other files holding activity) with, if a passphrase
+/// is configured
+fn cache_cipher(config: &Config) -> anyhow::Result<Option<Cipher>> {
+    let passphrase = config
+        .cache_passphrase
+        .clone()
+        .or_else(|| env::var("TMOG_EVENTS_CACHE_PASSPHRASE").ok());
+    passphrase.map(Cipher::new).transpose()
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ItemMeta {
-    html_url: String,
-    title: String,
+/// Where decisions from reviewing the report for `interval` are kept (see `Command::Review`)
+fn curation_path(interval: Interval) -> PathBuf {
+    PathBuf::from(format!("{}-curation.json", interval.key()))
 }
 
-impl ItemMeta {
-    fn project(&self) -> Option<&str> {
-        let path = self.html_url.strip_prefix("https://github.com/")?;
-        let mut parts = path.splitn(3, '/');
-        let org = parts.next()?;
-        let repo = parts.next()?;
-        Some(match REPO_PROJECT.contains(&org) {
-            true => repo,
-            false => org,
-        })
+/// The header for the report during `interval`, if a `title` is configured
+fn header(
+    title: Option<&str>,
+    period: Period,
+    interval: Interval,
+    locale: Locale,
+) -> Option<Header> {
+    title.map(|title| {
+        let (first, last) = interval.days();
+        let title = title.replace("{period}", &period.name(interval, locale));
+        Header { title, first, last }
+    })
+}
+
+/// Serve monthly reports over HTTP, collecting the activity on demand (see `Command::Serve`)
+#[allow(clippy::too_many_arguments)]
+async fn serve(
+    listen: SocketAddr,
+    collector: &Collector,
+    render: &mut Rst,
+    users: &[String],
+    window: Window,
+    calendar: &Calendar,
+    title: Option<&str>,
+    hook: Option<&Hook>,
+) -> anyhow::Result<()> {
+    let server = Server::bind(listen).await?;
+    info!(addr = %server.local_addr()?, "serving reports");
+    loop {
+        let request = match server.accept().await {
+            Ok(request) => request,
+            Err(error) => {
+                warn!("failed to accept connection: {error:#}");
+                continue;
+            }
+        };
+        if request.path() == "/metrics" {
+            let metrics = collector.http().metrics().render();
+            let content_type = "text/plain; version=0.0.4; charset=utf-8";
+            request
+                .respond(Response::ok(content_type, metrics.into_bytes()))
+                .await;
+            continue;
+        }
+        let Some((date, format)) = request.report() else {
+            request.respond(Response::not_found()).await;
+            continue;
+        };
+
+        info!(path = request.path(), "rendering report");
+        let response = async {
+            let interval = Period::Month.interval(date, 0, calendar)?;
+            let mut activity = collect(collector, interval, users, window).await?;
+            Curation::load(&curation_path(interval), collector.cipher())?.apply(&mut activity);
+            if let Some(hook) = hook {
+                hook.apply(&mut activity)?;
+            }
+            render.header = header(title, Period::Month, interval, render.locale);
+            report(format, render, &activity, users, Period::Month, interval)
+        };
+        request
+            .respond(match response.await {
+                Ok(report) => Response::ok(format.content_type(), report),
+                Err(error) => Response::error(&error),
+            })
+            .await;
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct JobsQueryResponse {
-    rows: Vec<Row>,
+/// Print the report for the items of the current period, or only those that are new since the
+/// last run, checking again after `every` (if set)
+///
+/// With `feeds`, an Atom feed of the activity on each of the repositories to `follow` is written
+/// there as well.
+#[allow(clippy::too_many_arguments)]
+async fn watch(
+    collector: &Collector,
+    render: &mut Rst,
+    users: &[String],
+    window: Window,
+    period: Period,
+    calendar: &Calendar,
+    title: Option<&str>,
+    format: Format,
+    since_last_run: bool,
+    every: Option<Duration>,
+    feeds: Option<&Path>,
+    follow: &[String],
+    hook: Option<&Hook>,
+) -> anyhow::Result<()> {
+    let path = Path::new(WATCHED);
+    loop {
+        let interval = period.interval(calendar.today(), 0, calendar)?;
+        let mut activity = collect(collector, interval, users, window).await?;
+        Curation::load(&curation_path(interval), collector.cipher())?.apply(&mut activity);
+        if let Some(hook) = hook {
+            hook.apply(&mut activity)?;
+        }
+        let current = Snapshot::of(&activity);
+        if since_last_run {
+            Snapshot::load(path, collector.cipher())?.since(&mut activity);
+        }
+        current.save(path, collector.cipher())?;
+
+        match activity.projects.is_empty() && activity.snippets.is_empty() {
+            true => info!("no new items"),
+            false => {
+                render.header = header(title, period, interval, render.locale);
+                let report = report(format, render, &activity, users, period, interval)?;
+                io::stdout().lock().write_all(&report)?;
+            }
+        }
+        if let Some(dir) = feeds {
+            for repo in follow {
+                let activity = collector.follow(interval, repo, window).await?;
+                let atom = Atom {
+                    period: format!("{repo}, {}", period.name(interval, render.locale)),
+                    key: format!("{repo}:{}", interval.key()),
+                    updated: Utc::now(),
+                    titles: &render.titles,
+                    links: &render.links,
+                };
+                let mut feed = Vec::new();
+                atom.render(&activity, std::slice::from_ref(repo), &mut feed)?;
+                let path = dir.join(format!("{repo}.atom"));
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("failed to create {}", parent.display()))?;
+                }
+                fs::write(&path, feed)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+            }
+        }
+        let Some(every) = every else {
+            return Ok(());
+        };
+        // Never poll more often than GitHub asks for
+        let metrics = collector.http().metrics();
+        let wait = match metrics.requested_poll_interval() {
+            Some(requested) if requested > every => requested,
+            _ => every,
+        };
+        metrics.polling(wait);
+        info!(
+            seconds = wait.as_secs(),
+            "checking again in {}s",
+            wait.as_secs()
+        );
+        tokio::time::sleep(wait).await;
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct Row {
-    f: Vec<Field>,
+/// Write `report` to `path` (with `merge`, adding its new items to the report there), returning
+/// the report as written
+fn written(path: &Path, report: Vec<u8>, merge: bool) -> anyhow::Result<Vec<u8>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let report = merged(path, report, merge)?;
+    fs::write(path, &report).with_context(|| format!("failed to write {}", path.display()))?;
+    info!(path = %path.display(), "wrote report");
+    Ok(report)
 }
 
-#[derive(Debug, Deserialize)]
-struct Field {
-    v: String,
+/// With `merge`, add the new items in `report` to the existing report at `path` (if any)
+fn merged(path: &Path, report: Vec<u8>, merge: bool) -> anyhow::Result<Vec<u8>> {
+    if !merge {
+        return Ok(report);
+    }
+
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(report),
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to read {}", path.display()))
+        }
+    };
+    let report = String::from_utf8(report)?;
+    Ok(tmog_events::merge(&existing, &report).into_bytes())
+}
+
+/// Expand the placeholders for the start of `interval` in the `template` for an output path
+///
+/// Supports `{year}`, `{month}` and `{day}` (zero-padded), `{quarter}` (1-4) and `{week}` (the
+/// ISO week number).
+fn output_path(template: &str, interval: Interval) -> String {
+    let start = interval.date(interval.start);
+    template
+        .replace("{year}", &start.format("%Y").to_string())
+        .replace("{month}", &start.format("%m").to_string())
+        .replace("{day}", &start.format("%d").to_string())
+        .replace("{quarter}", &(start.month0() / 3 + 1).to_string())
+        .replace("{week}", &start.format("%V").to_string())
 }
 
-#[derive(Debug, Serialize)]
-struct JobsQueryData {
-    query: String,
+/// The intervals of the months in `range` (like `2024-01..2024-06`, both inclusive)
+fn months(range: &str, calendar: &Calendar) -> anyhow::Result<Vec<Interval>> {
+    let (first, last) = range
+        .split_once("..")
+        .with_context(|| format!("invalid range {range:?} (expected FIRST..LAST)"))?;
+    let (first, last) = (parse_date(first)?, parse_date(last)?);
+    if first > last {
+        anyhow::bail!("range {range:?} ends before it starts");
+    }
+    months_between(first, last, calendar)
+}
+
+/// The intervals of the months from the one with `first` up to the one with `last`
+fn months_between(
+    first: NaiveDate,
+    last: NaiveDate,
+    calendar: &Calendar,
+) -> anyhow::Result<Vec<Interval>> {
+    let mut months = Vec::new();
+    let mut interval = Period::Month.interval(first, 0, calendar)?;
+    while interval.date(interval.start) <= last {
+        months.push(interval);
+        interval = Period::Month.interval(interval.days().1 + chrono::Days::new(1), 0, calendar)?;
+    }
+    Ok(months)
+}
+
+/// Compare the `activity` to that of the `previous` period (loaded from the cache, if any)
+async fn compare(
+    collector: &Collector,
+    activity: &Activity,
+    period: Period,
+    previous: anyhow::Result<Interval>,
+    locale: Locale,
+    users: &[String],
+    window: Window,
+) -> Option<Comparison> {
+    let previous = previous.ok()?;
+    let name = period.name(previous, locale);
+    match collector.collect(previous, users, window).await {
+        Ok(before) => Some(activity.compare(&before, name)),
+        Err(error) => {
+            warn!(
+                previous = name,
+                "failed to collect the previous period: {error:#}"
+            );
+            None
+        }
+    }
+}
+
+/// Print the expected number of requests and runtime for collecting the activity
+async fn estimate(
+    collector: &Collector,
+    interval: Interval,
+    users: &[String],
+    concurrency: usize,
+) -> anyhow::Result<()> {
+    let estimates = collector.estimate(interval, users).await?;
+    let mut stdout = std::io::stdout().lock();
+    let (mut requests, mut total, mut longest) = (0, Duration::ZERO, Duration::ZERO);
+    for estimate in &estimates {
+        let time = estimate.latency * estimate.requests as u32;
+        requests += estimate.requests;
+        total += time;
+        longest = longest.max(time);
+
+        write!(stdout, "{} ({}): ", estimate.user, estimate.source)?;
+        match estimate.cached {
+            true => write!(stdout, "cached")?,
+            false => {
+                let bound = if estimate.exact { "" } else { "at least " };
+                write!(stdout, "{bound}{} request(s)", estimate.requests)?;
+            }
+        }
+        if let Some(bytes) = estimate.bytes_processed {
+            write!(stdout, ", {:.2} GiB processed", bytes as f64 / GIB)?;
+        }
+        writeln!(stdout)?;
+    }
+
+    // Requests for a single user are sequential, but users are fetched concurrently
+    let runtime = longest.max(total / concurrency.max(1) as u32);
+    writeln!(
+        stdout,
+        "total: {requests} request(s), expected runtime {:.1}s",
+        runtime.as_secs_f64()
+    )?;
+    Ok(())
+}
+
+/// Print the requests that collecting the activity in each of the `intervals` would make
+fn dry_run(collector: &Collector, intervals: &[Interval], users: &[String]) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    // Requests by the rate limit they count against
+    let mut limits = BTreeMap::<&str, u64>::new();
+    let (mut total, mut at_most, mut unknown) = (0, false, false);
+    for interval in intervals {
+        writeln!(stdout, "{}:", interval.key())?;
+        for plan in collector.plan(*interval, users) {
+            write!(stdout, "  {}: ", plan.request)?;
+            match plan.requests {
+                Some(requests) => {
+                    let bound = if plan.at_most { "at most " } else { "" };
+                    write!(stdout, "{bound}{requests} request(s)")?;
+                    total += requests;
+                    at_most |= plan.at_most;
+                    if let Some(limit) = plan.rate_limit {
+                        *limits.entry(limit).or_default() += requests;
+                    }
+                }
+                None => {
+                    write!(stdout, "unknown number of requests")?;
+                    unknown = true;
+                }
+            }
+            match plan.rate_limit {
+                Some(limit) => writeln!(stdout, " ({limit})")?,
+                None => writeln!(stdout)?,
+            }
+        }
+    }
+
+    let bound = if at_most { "at most " } else { "" };
+    write!(stdout, "total: {bound}{total} request(s)")?;
+    for (i, (limit, requests)) in limits.iter().enumerate() {
+        let sep = if i == 0 { " (" } else { ", " };
+        write!(stdout, "{sep}{limit}: {requests}")?;
+    }
+    if !limits.is_empty() {
+        write!(stdout, ")")?;
+    }
+    match unknown {
+        true => writeln!(stdout, ", plus some that can't be told beforehand")?,
+        false => writeln!(stdout)?,
+    }
+    Ok(())
+}
+
+/// Build a `Collector` for the source selected by the configuration and command line
+async fn collector(
+    args: &Args,
+    config: &Config,
+    client: reqwest::Client,
+) -> anyhow::Result<Collector> {
+    let api_url = args.api_url.clone().or_else(|| config.api_url.clone());
+    let changelog = matches!(args.command, Some(Command::Changelog { .. }));
+    let source: Box<dyn EventSource> = match (&args.replay, args.forge) {
+        (Some(replay), _) => Box::new(Replay::load(replay)?),
+        // Several forges are only combined if none was picked on the command line
+        (None, None) if !config.forges.is_empty() => {
+            let mut sources = Vec::new();
+            for forge in &config.forges {
+                let api_url = forge.api_url.clone().or_else(|| api_url.clone());
+                let (host, web_url) = (forge.host.clone(), forge.web_url.clone());
+                sources.push(source(forge.forge, host, api_url, web_url, config).await?);
+            }
+            Box::new(Forges::new(sources))
+        }
+        (None, forge) => {
+            let forge = forge.or(config.forge).unwrap_or_default();
+            let host = args.host.clone().or_else(|| config.host.clone());
+            let web_url = args.web_url.clone().or_else(|| config.web_url.clone());
+            source(forge, host, api_url, web_url, config).await?
+        }
+    };
+
+    let mut collector = Collector::new(
+        source,
+        client,
+        args.concurrency.or(config.concurrency).unwrap_or(8),
+        config.per_host_concurrency.unwrap_or(4),
+    )
+    .with_branches(args.branches || config.branches)
+    .with_community(
+        args.community || config.community || args.sponsors.or(config.sponsors).is_some(),
+    )
+    .with_snippets(args.snippets || config.snippets)
+    .with_notifications(
+        args.notifications
+            || config.notifications
+            || args.mentions_section
+            || config.mentions_section,
+    )
+    .with_search(args.search || config.search)
+    .with_details(
+        args.states
+            || config.states
+            || args.sizes.or(config.sizes).is_some()
+            || args.merged_section
+            || config.merged_section
+            || !config.categories.is_empty()
+            || changelog,
+    )
+    .with_about(args.about || config.about)
+    .with_release_notes(
+        match args.release_note_lines.or(config.release_note_lines) {
+            Some(lines) => Some(Excerpt::Lines(lines)),
+            None => (args.release_notes || config.release_notes).then_some(Excerpt::Paragraph),
+        },
+    )
+    .with_assets(args.assets || config.assets)
+    .with_bots_excluded(
+        (args.exclude_bots || config.exclude_bots)
+            .then(|| BotFilter::new(config.bot_allowlist.clone(), config.bot_denylist.clone())),
+    )
+    .with_authored_only(args.authored_only || config.authored_only)
+    .with_self_activity_excluded(args.exclude_self_activity || config.exclude_self_activity)
+    .with_event_types(
+        overrides(&args.only, &config.only).to_vec(),
+        overrides(&args.skip, &config.skip).to_vec(),
+    )
+    .with_filter_order(config.filters.clone())
+    .with_repos(RepoFilter::new(
+        overrides(&args.include, &config.include),
+        overrides(&args.exclude, &config.exclude),
+    )?);
+    let member_scopes = args.member_scopes || config.member_scopes;
+    let projects = match (&args.personal_scopes[..], config.personal_scopes.as_deref()) {
+        // Memberships take the place of the default scopes
+        ([], None) if member_scopes => Projects::new(&[])?,
+        ([], None) => Projects::default(),
+        ([], Some(personal)) | (personal, _) => Projects::new(personal)?,
+    };
+    collector = collector
+        .with_projects(
+            projects
+                .with_aliases(config.project_aliases.clone())?
+                .with_renames(config.repo_renames.clone())
+                .with_maintained(&config.maintained)?,
+        )
+        .with_renames_followed(args.follow_renames || config.follow_renames);
+    collector = collector.with_private(args.include_private || config.include_private);
+    if args.redact || config.redact {
+        collector = collector.with_redacted(&config.redacted)?;
+    }
+    // Each of several forges has a web URL of its own
+    let web_url = args.web_url.as_ref().or(config.web_url.as_ref());
+    if let Some(web_url) = web_url.filter(|_| config.forges.is_empty() || args.forge.is_some()) {
+        collector = collector.with_web_url(web_url.clone());
+    }
+    if let Some(path) = &args.dump {
+        collector = collector.with_dump(path)?;
+    }
+    if let Some(cipher) = cache_cipher(config)? {
+        if args.dump.is_some() {
+            warn!("the events dumped with --dump are not encrypted");
+        }
+        collector = collector.with_encryption(cipher);
+    }
+    // Replays are for debugging, so they should always go through the pipeline, and watching (or
+    // a changelog up to now) needs the events as they are now rather than as they were cached
+    let watching = matches!(args.command, Some(Command::Watch { .. }));
+    collector = collector
+        .with_cache(args.replay.is_none() && !watching && !changelog)
+        .with_resume(args.resume)
+        .with_prefetch(args.prefetch.or(config.prefetch).unwrap_or(1))
+        .with_per_page(args.per_page.or(config.per_page).unwrap_or(100))
+        .with_max_pages(args.max_pages.or(config.max_pages))
+        .with_request_budget(args.request_budget.or(config.request_budget))
+        .with_strict(args.strict || config.strict);
+    // Only for someone watching a terminal, and not for a daemon
+    let serving = matches!(args.command, Some(Command::Serve { .. }));
+    let quiet = args.quiet || args.log_json || serving;
+    collector = collector.with_progress(!quiet && io::stderr().is_terminal());
+
+    Ok(collector)
+}
+
+/// The source of events from `forge` at `host` (or its default host)
+async fn source(
+    forge: Forge,
+    host: Option<String>,
+    api_url: Option<String>,
+    web_url: Option<String>,
+    config: &Config,
+) -> anyhow::Result<Box<dyn EventSource>> {
+    let host = host.unwrap_or_else(|| forge.default_host().to_owned());
+    let token = |token: &Option<String>, var: &str| token.clone().or_else(|| env::var(var).ok());
+    Ok(match (forge, api_url) {
+        (Forge::GitLab, _) => Box::new(GitLab::new(
+            host,
+            token(&config.gitlab_token, "GITLAB_TOKEN"),
+        )),
+        (Forge::Gitea, _) => Box::new(Gitea::new(host, token(&config.gitea_token, "GITEA_TOKEN"))),
+        (Forge::Bitbucket, _) => Box::new(Bitbucket::new(
+            config.bitbucket_repos.clone(),
+            token(&config.bitbucket_token, "BITBUCKET_TOKEN"),
+        )),
+        (Forge::Sourcehut, _) => Box::new(Sourcehut::new(
+            config.sourcehut_trackers.clone(),
+            config.sourcehut_lists.clone(),
+            token(&config.sourcehut_token, "SRHT_TOKEN"),
+        )),
+        (Forge::GitHub, Some(api_url)) => {
+            let mut github = GitHub::new(api_url, token(&config.github_token, "GITHUB_TOKEN"))?
+                .with_tokens(config.github_tokens.clone())
+                .with_url_rules(config.url_rules.clone());
+            if let Some(web_url) = web_url {
+                github = github.with_web_url(web_url);
+            }
+            Box::new(github)
+        }
+        (Forge::GitHub, None) => Box::new(Archive::new(
+            config
+                .gcp_project
+                .clone()
+                .context("`gcp_project` must be configured to query the GitHub archive")?,
+            provider().await?,
+            token(&config.github_token, "GITHUB_TOKEN"),
+        )),
+    })
+}
+
+/// Values from the command line, which override those from the configuration (if any)
+fn overrides<'a, T>(args: &'a [T], config: &'a [T]) -> &'a [T] {
+    match args.is_empty() {
+        true => config,
+        false => args,
+    }
 }
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    month: String,
-    #[clap(long, default_value = "config.toml")]
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// A day (`YYYYMMDD`) or month (`YYYYMM`) in the period to summarize (defaults to today)
+    date: Option<String>,
+    /// The length of the period to summarize
+    #[clap(long, global = true)]
+    period: Option<Period>,
+    /// Summarize the period this many periods before the one containing the date
+    #[clap(long, global = true, value_name = "N", default_value_t = 0)]
+    offset: u32,
+    /// The first day of weekly periods (default: monday, as for ISO weeks)
+    #[clap(long, global = true, value_name = "DAY")]
+    week_start: Option<WeekStart>,
+    /// Write a report for each month in the range (like `2024-01..2024-06`) to its own file (see
+    /// `--output`, which defaults to `{year}{month}.rst` here)
+    #[clap(long, value_name = "RANGE", conflicts_with_all = ["date", "period", "offset"])]
+    each_month: Option<String>,
+    /// The timezone in which periods start and end, like `Europe/Amsterdam` (default: UTC)
+    #[clap(long, global = true, value_name = "TZ")]
+    timezone: Option<Tz>,
+    /// The language for headings and period names (default: en)
+    #[clap(long, global = true)]
+    locale: Option<Locale>,
+    /// The format of the report (default: rst)
+    #[clap(long, value_enum, global = true)]
+    format: Option<Format>,
+    /// Open the report with a title (replacing `{period}` with its name) and the days covered
+    #[clap(long, global = true, value_name = "TEMPLATE")]
+    title: Option<String>,
+    /// Close the report with a note on when it was generated
+    #[clap(long, global = true)]
+    footer: bool,
+    /// Close the report with a note on the days with activity and the streaks of consecutive days
+    #[clap(long, global = true)]
+    streaks: bool,
+    /// Also publish the report in these places (comma-separated, printing any URLs)
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "WHERE",
+        global = true
+    )]
+    publish: Vec<Publish>,
+    /// Add new items to the report written before (see `--output` and `archive`), instead of
+    /// overwriting it
+    #[clap(long, global = true)]
+    merge: bool,
+    /// Print a short summary for social media instead of the report (see `--publish mastodon`)
+    #[clap(long, global = true, conflicts_with_all = ["merge", "output"])]
+    toot: bool,
+    /// Ask whether to include each item (or change its title) before writing the report,
+    /// saving the decisions for later runs (see `review`)
+    #[clap(long, short, global = true)]
+    interactive: bool,
+    /// Pass the items through this command before writing the report, as JSON lines on its stdin;
+    /// it answers with a line for each (changed, or `null` to leave the item out) on stdout
+    #[clap(long, global = true, value_name = "COMMAND")]
+    hook: Option<String>,
+    /// What to do with items that were listed in the report for an earlier period (which are
+    /// remembered in `published.json`)
+    #[clap(long, value_enum, value_name = "ACTION", global = true)]
+    seen: Option<Seen>,
+    /// Write the report to this file instead of stdout, replacing `{year}`, `{month}`, `{day}`,
+    /// `{quarter}` and `{week}` for the (start of the) period
+    #[clap(long, global = true, value_name = "PATH")]
+    output: Option<String>,
+    /// Write a report for each owner (user or organization) of the repositories instead, to the
+    /// `--output` path with `{owner}` replaced (by default `{owner}.rst` and so on)
+    #[clap(long, value_enum, value_name = "SCOPE", global = true)]
+    split_by: Option<SplitBy>,
+    /// Also copy the report to the clipboard
+    #[clap(long, global = true)]
+    clipboard: bool,
+    /// GitHub user(s) to summarize (overrides `user`/`team` from the config)
+    #[clap(long = "user", global = true)]
+    users: Vec<String>,
+    /// Annotate each item with the user(s) who touched it (implied for multiple users)
+    #[clap(long, global = true)]
+    attribution: bool,
+    /// Annotate each item with the user's role(s) and the number of reviews and comments
+    #[clap(long, global = true)]
+    roles: bool,
+    /// Split the items of each project into pull requests, issues, releases, discussions and other
+    #[clap(long, global = true)]
+    group_by_kind: bool,
+    /// How to order the projects (default: name)
+    #[clap(long, value_enum, value_name = "ORDER", global = true)]
+    project_order: Option<ProjectOrder>,
+    /// Projects with fewer items are collected in a trailing "Other" section (or dropped)
+    #[clap(long, value_name = "N", global = true)]
+    min_items: Option<usize>,
+    /// What to do with projects with fewer than `--min-items` items (default: other)
+    #[clap(long, value_enum, value_name = "ACTION", global = true)]
+    small_projects: Option<SmallProjects>,
+    /// How to order the items within each project (default: number)
+    #[clap(long, value_enum, global = true)]
+    sort: Option<Sort>,
+    /// Prefix the titles of issues, pull requests and discussions with their number
+    #[clap(long, global = true)]
+    numbers: bool,
+    /// Truncate titles longer than this many characters
+    #[clap(long, value_name = "N", global = true)]
+    max_title_len: Option<usize>,
+    /// Strip conventional prefixes (like `fix:` or `[WIP]`) from titles
+    #[clap(long, global = true)]
+    strip_prefixes: bool,
+    /// Convert emoji shortcodes (like `:tada:`) in titles, or strip them
+    #[clap(long, value_enum, value_name = "ACTION", global = true)]
+    emoji: Option<Shortcodes>,
+    /// Add a section with summary statistics at the top or bottom of the report
+    #[clap(long, value_enum, value_name = "WHERE", global = true)]
+    stats: Option<Stats>,
+    /// Score each project in the summary statistics by the weights of its contributions (see
+    /// `score_weights` in the config)
+    #[clap(long, global = true)]
+    scores: bool,
+    /// Add a section with the number of interactions on each day
+    #[clap(long, global = true)]
+    days: bool,
+    /// Add a section with the number of interactions per day of the week and time of day
+    #[clap(long, global = true)]
+    times: bool,
+    /// Print a heatmap of the interactions per day to the terminal (on stderr)
+    #[clap(long, global = true)]
+    heatmap: bool,
+    /// Compare the activity to that of the previous period
+    #[clap(long, global = true)]
+    compare: bool,
+    /// Append a per-person breakdown to the (team) report
+    #[clap(long, global = true)]
+    appendix: bool,
+    /// Maximum number of concurrent requests
+    #[clap(long, global = true)]
+    concurrency: Option<usize>,
+    /// Fetch this many pages of events ahead at a time, instead of one after the other, for
+    /// long feeds (GitHub only; default: 1)
+    #[clap(long, global = true, value_name = "PAGES")]
+    prefetch: Option<usize>,
+    /// Number of events to ask for per page of events (GitHub and GitLab; default: 100)
+    #[clap(long, global = true, value_name = "EVENTS")]
+    per_page: Option<usize>,
+    /// Fetch at most this many pages of events per feed, for quick (incomplete) runs
+    #[clap(long, global = true, value_name = "PAGES")]
+    max_pages: Option<usize>,
+    /// Send at most this many API requests, leaving the details of items unknown once they're used
+    /// up
+    #[clap(long, global = true, value_name = "REQUESTS")]
+    request_budget: Option<u64>,
+    /// Include branches created during the period (tags are always included)
+    #[clap(long, global = true)]
+    branches: bool,
+    /// Add a section on forks and stars of the user's repositories
+    #[clap(long, global = true)]
+    community: bool,
+    /// Add a section on new sponsors, revealing as much as allowed (GitHub archive only)
+    #[clap(long, value_enum, global = true)]
+    sponsors: Option<Sponsors>,
+    /// Add a section on gists created or updated during the period (GitHub only)
+    #[clap(long, global = true)]
+    snippets: bool,
+    /// Include items you were requested to review, mentioned in or assigned to, from your
+    /// notifications (GitHub only, for the token's user)
+    #[clap(long, global = true)]
+    notifications: bool,
+    /// Search for items involving the user to fill gaps in their events, warning about any found
+    /// (GitHub only)
+    #[clap(long, global = true)]
+    search: bool,
+    /// Mark items since merged or completed with ✔ and closed ones with ✘ (GitHub only)
+    #[clap(long, global = true)]
+    states: bool,
+    /// Mark pull requests with their size, as a badge (S, M, L or XL, by the lines changed) or
+    /// the numbers of lines and files changed (GitHub only)
+    #[clap(long, value_name = "STYLE", global = true)]
+    sizes: Option<Sizes>,
+    /// Summarize the closing, labeling and assigning of issues in each project, like "closed 14
+    /// issues, labeled 22, assigned 5"
+    #[clap(long, global = true)]
+    triage: bool,
+    /// Describe each project under its heading, from its repository (GitHub only)
+    #[clap(long, global = true)]
+    about: bool,
+    /// Include the first paragraph of the notes of each release under it
+    #[clap(long, global = true)]
+    release_notes: bool,
+    /// Include this many lines of the notes of each release instead (implies `--release-notes`)
+    #[clap(long, value_name = "LINES", global = true)]
+    release_note_lines: Option<usize>,
+    /// List the files uploaded to each release, with their download counts (GitHub only)
+    #[clap(long, global = true)]
+    assets: bool,
+    /// Only include repositories (`owner/name`) matching one of these glob patterns
+    #[clap(long, value_name = "GLOB", global = true)]
+    include: Vec<String>,
+    /// Leave out repositories (`owner/name`) matching one of these glob patterns
+    #[clap(long, value_name = "GLOB", global = true)]
+    exclude: Vec<String>,
+    /// Owners whose repositories are separate projects (glob patterns; overrides the config)
+    #[clap(long = "personal-scope", value_name = "GLOB", global = true)]
+    personal_scopes: Vec<String>,
+    /// Also treat the users and the organizations they're members of as personal scopes, instead
+    /// of the default ones (GitHub only)
+    #[clap(long, global = true)]
+    member_scopes: bool,
+    /// Split the report into the user's own (or maintained) projects and other contributions
+    #[clap(long, global = true)]
+    split_maintained: bool,
+    /// Open the report with a section on the N most significant items
+    #[clap(long, global = true, value_name = "N")]
+    highlights: Option<usize>,
+    /// List the releases of all projects in a section at the top, by date
+    #[clap(long, global = true)]
+    releases_section: bool,
+    /// Note who co-authored pushed commits (by their `Co-authored-by` trailers)
+    #[clap(long, global = true)]
+    pairing: bool,
+    /// List the pull requests you merged for others in a section of their own (GitHub only)
+    #[clap(long, global = true)]
+    merged_section: bool,
+    /// List the items you were only mentioned in, requested to review or assigned to in a section
+    /// of their own, from your notifications (implies `--notifications`)
+    #[clap(long, global = true)]
+    mentions_section: bool,
+    /// Include activity in private repositories (left out by default)
+    #[clap(long, global = true)]
+    include_private: bool,
+    /// Hide the titles of items in the repositories listed in `redacted` (in the config)
+    #[clap(long, global = true)]
+    redact: bool,
+    /// Group repositories renamed since under their current name (GitHub only)
+    #[clap(long, global = true)]
+    follow_renames: bool,
+    /// Leave out items opened by bots (accounts ending in `[bot]`, by default)
+    #[clap(long, global = true)]
+    exclude_bots: bool,
+    /// Only include items opened by the user(s), releases and pushes (leaving out reviews and
+    /// comments on other items)
+    #[clap(long, global = true)]
+    authored_only: bool,
+    /// Leave out issues, pull requests and pushes in the user(s)' own repositories that no one
+    /// else took part in (needs a token on GitHub)
+    #[clap(long, global = true)]
+    exclude_self_activity: bool,
+    /// Only include these types of events (comma-separated)
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "TYPES",
+        global = true
+    )]
+    only: Vec<EventType>,
+    /// Leave out these types of events (comma-separated)
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "TYPES",
+        global = true
+    )]
+    skip: Vec<EventType>,
+    /// Which timestamp decides whether an item falls within the period (default: event)
+    #[clap(long, value_enum, global = true)]
+    window: Option<Window>,
+    /// The kind of forge to fetch events from
+    #[clap(long, value_enum, global = true)]
+    forge: Option<Forge>,
+    /// Host name of the GitLab or Gitea instance (default: gitlab.com or codeberg.org)
+    #[clap(long, global = true)]
+    host: Option<String>,
+    /// Fetch events from the GitHub REST API at this URL instead of the GitHub archive
+    ///
+    /// Use this for GitHub Enterprise Server (`https://HOST/api/v3`), or `https://api.github.com`.
+    #[clap(long, global = true)]
+    api_url: Option<String>,
+    /// Base URL of item links (defaults to the web URL matching the API URL)
+    #[clap(long, global = true)]
+    web_url: Option<String>,
+    /// Send all requests through this proxy (like `http://proxy.example.com:3128`), instead of
+    /// the one in the `HTTPS_PROXY` or `HTTP_PROXY` environment variables (if any)
+    #[clap(long, global = true, value_name = "URL")]
+    proxy: Option<String>,
+    /// Give up connecting to a host after this many seconds (default: 10)
+    #[clap(long, global = true, value_name = "SECS")]
+    connect_timeout: Option<u64>,
+    /// Give up on a response when no data arrives for this many seconds (default: 30)
+    #[clap(long, global = true, value_name = "SECS")]
+    read_timeout: Option<u64>,
+    /// Write the raw payload of every event fetched to this file, as JSON Lines (for debugging)
+    #[clap(long, global = true, value_name = "PATH")]
+    dump: Option<PathBuf>,
+    /// Collect the events written by `--dump` to this file instead of fetching any (GitHub only)
+    #[clap(long, global = true, value_name = "PATH", conflicts_with = "dump")]
+    replay: Option<PathBuf>,
+    /// Only check the token (for permissions, and whether it expires soon), without collecting
+    /// anything
+    #[clap(long, global = true)]
+    check: bool,
+    /// Print the requests that collecting would make (and how many), without making any
+    #[clap(long, global = true)]
+    dry_run: bool,
+    /// Continue fetching from where an earlier run failed or was interrupted (GitHub only)
+    #[clap(long, global = true)]
+    resume: bool,
+    /// Fail on events of unknown types and on malformed events, showing their payload
+    #[clap(long, global = true)]
+    strict: bool,
+    /// Log progress (`-v`), or details such as each request (`-vv`); `RUST_LOG` takes precedence
+    #[clap(long, short, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Only log errors
+    #[clap(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Log as JSON, one object per line (such as when running `serve`)
+    #[clap(long, global = true)]
+    log_json: bool,
+    #[clap(long, default_value = "config.toml", global = true)]
     config: PathBuf,
 }
 
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Estimate the number of requests and the runtime for a report, without running it
+    Estimate {
+        /// A day (`YYYYMMDD`) or month (`YYYYMM`) in the period to summarize (defaults to today)
+        date: Option<String>,
+    },
+    /// Add the report to an archive of past reports (like `reports/2024/03.rst`) and regenerate
+    /// its index
+    Archive {
+        /// A day (`YYYYMMDD`) or month (`YYYYMM`) in the period to summarize (defaults to today)
+        date: Option<String>,
+        /// The directory containing the archive
+        #[clap(long, default_value = "reports")]
+        dir: PathBuf,
+        /// Overwrite the report for the period, if it was archived before
+        #[clap(long)]
+        force: bool,
+    },
+    /// Write a static site of monthly reports (like for GitHub Pages), with an index of the months
+    /// and a page with the history of each project
+    ///
+    /// The site covers the month and the months before it whose events are cached (going back
+    /// until one that isn't), so only the latest one is fetched.
+    Dashboard {
+        /// A day (`YYYYMMDD`) or month (`YYYYMM`) in the last month to include (defaults to today)
+        date: Option<String>,
+        /// The directory to write the site to
+        #[clap(long, default_value = "dashboard")]
+        dir: PathBuf,
+    },
+    /// Review the items in the report in an editor before writing it
+    ///
+    /// Items can be left out, retitled and reordered within their project. The decisions are
+    /// saved next to the cache (like `202403-curation.json`) and apply to later runs as well.
+    Review {
+        /// A day (`YYYYMMDD`) or month (`YYYYMM`) in the period to summarize (defaults to today)
+        date: Option<String>,
+    },
+    /// Write the report with its summary comparing it to the users' GitHub contribution calendars
+    ///
+    /// This tells whether the report misses work that the events feed left out, like "The events
+    /// feed captured 91% of the 453 contributions counted by the contribution calendar". Looking
+    /// up the calendars takes a GitHub token.
+    Contributions {
+        /// A day (`YYYYMMDD`) or month (`YYYYMM`) in the period to summarize (defaults to today)
+        date: Option<String>,
+    },
+    /// Print a changelog of the pull requests merged in a repository since a tag, grouped by
+    /// their labels (GitHub only)
+    ///
+    /// The groups are the `categories` from the config, or else Features, Fixes and Docs (by
+    /// labels like `enhancement`, `bug` and `documentation`). The pull requests are found in the
+    /// events of the repository, which go back 90 days at most.
+    Changelog {
+        /// The repository (`owner/name`)
+        #[clap(long)]
+        repo: String,
+        /// The tag of the previous release
+        #[clap(long, value_name = "TAG")]
+        since: String,
+    },
+    /// Export the activity of every month since a given one, as CSV tables partitioned by month
+    /// (like `DIR/month=2024-03/items.csv`), for analysis in a data warehouse
+    ///
+    /// Months are loaded from the cache where possible. Each month exported is recorded in
+    /// `DIR/checkpoint.json`, so that running the export again after it was interrupted (or
+    /// failed) continues with the first month that wasn't exported yet.
+    Export {
+        /// The first month to export (`YYYYMM`)
+        #[clap(long, value_name = "MONTH")]
+        since: String,
+        /// The last month to export (`YYYYMM`, defaults to the current month)
+        #[clap(long, value_name = "MONTH")]
+        until: Option<String>,
+        /// The directory to write the tables to
+        #[clap(long, value_name = "DIR")]
+        to: PathBuf,
+    },
+    /// Attach a note to an item, which is shown beneath it in later reports
+    ///
+    /// Notes are kept in `annotations.json` in the working directory.
+    Annotate {
+        /// The link to the item (as in the report)
+        url: String,
+        /// The note to add (after any earlier notes)
+        #[clap(required_unless_present = "clear")]
+        note: Option<String>,
+        /// Remove the earlier notes for the item
+        #[clap(long)]
+        clear: bool,
+    },
+    /// Write a config file (see `--config`) from the answers to a few questions
+    Init {
+        /// Also print completions for this shell (to stdout)
+        #[clap(long, value_name = "SHELL")]
+        completions: Option<Shell>,
+        /// Replace an existing config file
+        #[clap(long)]
+        force: bool,
+    },
+    /// Show the remaining GitHub API quota for the configured token, and when it resets
+    ///
+    /// This tells whether a large backfill (see `--each-month`) can run right now.
+    RateLimit,
+    /// Print the JSON Schema of JSON (and YAML) reports
+    ///
+    /// Reports include the `schema_version` they follow, which changes when their structure does.
+    Schema,
+    /// Compare two JSON reports (see `--format json`), printing the items added, removed or changed
+    ///
+    /// This tells whether a change (or fetching afresh) left out items it shouldn't have. The run
+    /// fails (with exit code 8) if the reports differ.
+    Diff {
+        /// The earlier report
+        old: PathBuf,
+        /// The later report
+        new: PathBuf,
+    },
+    /// Print the items of the current period, such as for a daily digest next to the report
+    ///
+    /// The items of each run are saved in `watch.json` in the working directory, to compare the
+    /// next run against. Events are always fetched afresh, rather than loaded from the cache.
+    Watch {
+        /// Only print the items that are new since the last run (leaving out pushes, stars and
+        /// the like)
+        #[clap(long)]
+        since_last_run: bool,
+        /// Keep running, checking again after this many minutes (or longer, if GitHub asks to
+        /// poll its events less often)
+        #[clap(long, value_name = "MINUTES")]
+        every: Option<u64>,
+        /// Also write an Atom feed of the activity on each followed repository (see `follow` in
+        /// the config) to this directory, like `DIR/hyperium/hyper.atom` (GitHub only)
+        #[clap(long, value_name = "DIR")]
+        feeds: Option<PathBuf>,
+    },
+    /// Serve monthly reports over HTTP (like `/report/2024/03`), collecting them on demand
+    ///
+    /// Reports are HTML pages, or another format with its extension (like `/report/2024/03.json`).
+    /// Events are loaded from the cache where possible. Prometheus metrics are at `/metrics`.
+    Serve {
+        /// The address to listen on
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+    },
+}
+
 #[derive(Debug, Deserialize)]
 struct Config {
-    gcp_project: String,
-    user: String,
+    gcp_project: Option<String>,
+    user: Option<String>,
+    /// Multiple users to aggregate into a single team report
+    #[serde(default)]
+    team: Vec<String>,
+    /// The length of the period to summarize (see `--period`)
+    period: Option<Period>,
+    /// The first day of weekly periods (see `--week-start`)
+    week_start: Option<WeekStart>,
+    /// The timezone in which periods start and end (see `--timezone`)
+    timezone: Option<Tz>,
+    /// The language for headings and period names (see `--locale`)
+    locale: Option<Locale>,
+    /// The format of the report (see `--format`)
+    format: Option<Format>,
+    /// Open the report with this title, in which `{period}` is replaced (see `--title`)
+    title: Option<String>,
+    /// Close the report with a note on when it was generated (see `--footer`)
+    #[serde(default)]
+    footer: bool,
+    /// Close the report with a note on the days with activity (see `--streaks`)
+    #[serde(default)]
+    streaks: bool,
+    /// Write the report to this file instead of stdout (see `--output`)
+    output: Option<String>,
+    /// Write a report for each owner of the repositories instead (see `--split-by`)
+    split_by: Option<SplitBy>,
+    /// Also copy the report to the clipboard (see `--clipboard`)
+    #[serde(default)]
+    clipboard: bool,
+    /// Also publish the report in these places (see `--publish`)
+    #[serde(default)]
+    publish: Vec<Publish>,
+    /// The repository to commit reports to for `publish = ["blog"]`
+    blog: Option<Blog>,
+    /// The chat webhook to post summaries to for `publish = ["webhook"]`
+    webhook: Option<Webhook>,
+    /// The account to post summaries to for `publish = ["mastodon"]`
+    mastodon: Option<Mastodon>,
+    /// The room to post reports to for `publish = ["matrix"]`
+    matrix: Option<Matrix>,
+    /// Maximum length of summaries for social media (default: 500)
+    toot_limit: Option<usize>,
+    /// Annotate each item with the user's role(s) and interactions (see `--roles`)
+    #[serde(default)]
+    roles: bool,
+    /// Split the items of each project by kind (see `--group-by-kind`)
+    #[serde(default)]
+    group_by_kind: bool,
+    /// Split the items of each project by their labels, in this order
+    #[serde(default)]
+    categories: Vec<Category>,
+    /// Per-project overrides of the heading, intro and pinned items of the sections, keyed by
+    /// project name
+    #[serde(default)]
+    sections: HashMap<String, Section>,
+    /// How to order the projects (see `--project-order`)
+    project_order: Option<ProjectOrder>,
+    /// Minimum number of items for a project to get its own section (see `--min-items`)
+    min_items: Option<usize>,
+    /// What to do with smaller projects (see `--small-projects`)
+    small_projects: Option<SmallProjects>,
+    /// How to order the items within each project (see `--sort`)
+    sort: Option<Sort>,
+    /// What to do with items listed in earlier reports (see `--seen`)
+    seen: Option<Seen>,
+    /// Append a per-person breakdown to the report (see `--appendix`)
+    #[serde(default)]
+    appendix: bool,
+    /// Include branches created during the period (see `--branches`)
+    #[serde(default)]
+    branches: bool,
+    /// Add a section on forks and stars of the user's repositories (see `--community`)
+    #[serde(default)]
+    community: bool,
+    /// Add a section on new sponsors (see `--sponsors`)
+    sponsors: Option<Sponsors>,
+    /// Add a section on gists (see `--snippets`)
+    #[serde(default)]
+    snippets: bool,
+    /// Include items from notifications (see `--notifications`)
+    #[serde(default)]
+    notifications: bool,
+    /// Search for items missing from the events (see `--search`)
+    #[serde(default)]
+    search: bool,
+    /// Mark merged and closed items (see `--states`)
+    #[serde(default)]
+    states: bool,
+    /// How to mark pull requests with their size (see `--sizes`)
+    sizes: Option<Sizes>,
+    /// Summarize the triage of issues in each project (see `--triage`)
+    #[serde(default)]
+    triage: bool,
+    /// Describe each project (see `--about`)
+    #[serde(default)]
+    about: bool,
+    /// Include the start of release notes (see `--release-notes`)
+    #[serde(default)]
+    release_notes: bool,
+    /// How many lines of release notes to include (see `--release-note-lines`)
+    release_note_lines: Option<usize>,
+    /// List the assets of releases (see `--assets`)
+    #[serde(default)]
+    assets: bool,
+    /// Only include repositories matching one of these patterns (see `--include`)
+    #[serde(default)]
+    include: Vec<String>,
+    /// Leave out repositories matching one of these patterns (see `--exclude`)
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// The order to apply filters in, like `["repos", "bots", "window", "event-types"]`
+    #[serde(default)]
+    filters: Vec<FilterKind>,
+    /// A command to pass the items through (see `--hook`)
+    hook: Option<String>,
+    /// Owners whose repositories are separate projects (see `--personal-scope`)
+    personal_scopes: Option<Vec<String>>,
+    /// Also treat memberships as personal scopes (see `--member-scopes`)
+    #[serde(default)]
+    member_scopes: bool,
+    /// Project names for repositories matching the glob patterns (keys)
+    #[serde(default)]
+    project_aliases: BTreeMap<String, String>,
+    /// Current names of renamed repositories, keyed by their old `owner/name`
+    #[serde(default)]
+    repo_renames: HashMap<String, String>,
+    /// Add a section with summary statistics (see `--stats`)
+    stats: Option<Stats>,
+    /// Score each project in the summary statistics (see `--scores`)
+    #[serde(default)]
+    scores: bool,
+    /// How to score contributions, like `{ authored = 5, review = 3, comment = 1, release = 5,
+    /// commit = 1 }`
+    #[serde(default)]
+    score_weights: ScoreWeights,
+    /// Add a section with the interactions per day (see `--days`)
+    #[serde(default)]
+    days: bool,
+    /// Add a section with the interactions per day of the week and time of day (see `--times`)
+    #[serde(default)]
+    times: bool,
+    /// Print a heatmap of the interactions per day (see `--heatmap`)
+    #[serde(default)]
+    heatmap: bool,
+    /// Compare the activity to that of the previous period (see `--compare`)
+    #[serde(default)]
+    compare: bool,
+    /// Prefix titles with item numbers (see `--numbers`)
+    #[serde(default)]
+    numbers: bool,
+    /// Truncate long titles (see `--max-title-len`)
+    max_title_len: Option<usize>,
+    /// Strip conventional prefixes from titles (see `--strip-prefixes`)
+    #[serde(default)]
+    strip_prefixes: bool,
+    /// Convert or strip emoji shortcodes in titles (see `--emoji`)
+    emoji: Option<Shortcodes>,
+    /// Split the report by whether projects are maintained (see `--split-maintained`)
+    #[serde(default)]
+    split_maintained: bool,
+    /// Open the report with the most significant items (see `--highlights`)
+    highlights: Option<usize>,
+    /// How to rank items for the highlights, like `{ merged = 100, release = 50, interaction = 1 }`
+    #[serde(default)]
+    highlight_weights: Weights,
+    /// List releases in a section of their own (see `--releases-section`)
+    #[serde(default)]
+    releases_section: bool,
+    /// Note co-authors of pushed commits (see `--pairing`)
+    #[serde(default)]
+    pairing: bool,
+    /// List pull requests merged for others separately (see `--merged-section`)
+    #[serde(default)]
+    merged_section: bool,
+    /// List items you were pulled into separately (see `--mentions-section`)
+    #[serde(default)]
+    mentions_section: bool,
+    /// Repositories (glob patterns) maintained by the user(s), besides their own
+    #[serde(default)]
+    maintained: Vec<String>,
+    /// Repositories (`owner/name`) to write feeds for while watching (see `watch --feeds`)
+    #[serde(default)]
+    follow: Vec<String>,
+    /// Include activity in private repositories (see `--include-private`)
+    #[serde(default)]
+    include_private: bool,
+    /// Hide the titles of items in the `redacted` repositories (see `--redact`)
+    #[serde(default)]
+    redact: bool,
+    /// Repositories (glob patterns) whose item titles are hidden when redacting
+    #[serde(default)]
+    redacted: Vec<String>,
+    /// Look up the current names of repositories (see `--follow-renames`)
+    #[serde(default)]
+    follow_renames: bool,
+    /// Leave out items opened by bots (see `--exclude-bots`)
+    #[serde(default)]
+    exclude_bots: bool,
+    /// Accounts that are never considered bots
+    #[serde(default)]
+    bot_allowlist: Vec<String>,
+    /// Accounts that are considered bots, even though their name doesn't end in `[bot]`
+    #[serde(default)]
+    bot_denylist: Vec<String>,
+    /// Only include items opened by the user(s) (see `--authored-only`)
+    #[serde(default)]
+    authored_only: bool,
+    /// Leave out activity in the user(s)' own repositories that no one else took part in (see
+    /// `--exclude-self-activity`)
+    #[serde(default)]
+    exclude_self_activity: bool,
+    /// Only include these types of events (see `--only`)
+    #[serde(default)]
+    only: Vec<EventType>,
+    /// Leave out these types of events (see `--skip`)
+    #[serde(default)]
+    skip: Vec<EventType>,
+    /// Which timestamp decides whether an item falls within the period (see `--window`)
+    window: Option<Window>,
+    /// Maximum number of concurrent requests (default: 8)
+    concurrency: Option<usize>,
+    /// Maximum number of concurrent requests to a single host (default: 4)
+    per_host_concurrency: Option<usize>,
+    /// Number of pages of events to fetch ahead at a time (see `--prefetch`)
+    prefetch: Option<usize>,
+    /// Number of events per page of events (see `--per-page`)
+    per_page: Option<usize>,
+    /// Maximum number of pages of events per feed (see `--max-pages`)
+    max_pages: Option<usize>,
+    /// Maximum number of API requests (see `--request-budget`)
+    request_budget: Option<u64>,
+    /// The kind of forge to fetch events from (default: github)
+    forge: Option<Forge>,
+    /// Host name of the GitLab or Gitea instance (see `--host`)
+    host: Option<String>,
+    /// Forges to collect events from at once, for a single report (instead of `forge`)
+    #[serde(default)]
+    forges: Vec<ForgeConfig>,
+    /// Token for the GitLab API (defaults to the `GITLAB_TOKEN` environment variable)
+    gitlab_token: Option<String>,
+    /// Token for the Gitea API (defaults to the `GITEA_TOKEN` environment variable)
+    gitea_token: Option<String>,
+    /// Per-project link prefixes, keyed by project name
+    #[serde(default)]
+    link_prefixes: HashMap<String, LinkPrefix>,
+    /// Bitbucket repositories (`workspace/repository`) to search for comments and approvals
+    #[serde(default)]
+    bitbucket_repos: Vec<String>,
+    /// Access token for Bitbucket (defaults to the `BITBUCKET_TOKEN` environment variable)
+    bitbucket_token: Option<String>,
+    /// sourcehut ticket trackers (`~owner/name`) to search for tickets
+    #[serde(default)]
+    sourcehut_trackers: Vec<String>,
+    /// sourcehut mailing lists (`~owner/name`) to search for patch reviews
+    #[serde(default)]
+    sourcehut_lists: Vec<String>,
+    /// Personal access token for sourcehut (defaults to the `SRHT_TOKEN` environment variable)
+    sourcehut_token: Option<String>,
+    /// GitHub REST API URL (see `--api-url`)
+    api_url: Option<String>,
+    /// Base URL of item links (see `--web-url`)
+    web_url: Option<String>,
+    /// Rules mapping the API URLs of items to their web URLs, for those that payloads don't give
+    /// the web URL of (before the built-in rules)
+    #[serde(default)]
+    url_rules: Vec<UrlRule>,
+    /// Sent as the `User-Agent` of all requests, instead of `tmog-events@VERSION` (like to include
+    /// a contact address)
+    user_agent: Option<String>,
+    /// Warn when the token expires within this many days (default: 7)
+    token_expiry_warning: Option<i64>,
+    /// The proxy to send all requests through (see `--proxy`)
+    proxy: Option<String>,
+    /// Seconds to wait for a connection to a host (see `--connect-timeout`)
+    connect_timeout: Option<u64>,
+    /// Seconds to wait for data from a response (see `--read-timeout`)
+    read_timeout: Option<u64>,
+    /// Token for the GitHub REST API (defaults to the `GITHUB_TOKEN` environment variable)
+    github_token: Option<String>,
+    /// Passphrase to encrypt the files of the cache with (defaults to the
+    /// `TMOG_EVENTS_CACHE_PASSPHRASE` environment variable)
+    cache_passphrase: Option<String>,
+    /// More tokens for the GitHub REST API, to switch to when the one in use runs out of rate
+    /// limit
+    #[serde(default)]
+    github_tokens: Vec<String>,
+    /// Fail on unknown or malformed events (see `--strict`)
+    #[serde(default)]
+    strict: bool,
+}
+
+/// One of several forges to collect events from (see `forges`)
+#[derive(Debug, Deserialize)]
+struct ForgeConfig {
+    forge: Forge,
+    /// Host name of the instance (defaults to that of the kind of forge)
+    host: Option<String>,
+    /// GitHub REST API URL (defaults to `api_url`)
+    api_url: Option<String>,
+    /// Base URL of item links (defaults to the web URL matching the API URL)
+    web_url: Option<String>,
 }
 
-const REPO_PROJECT: &[&str] = &["djc", "nicoburns", "seanmonstar", "rust-lang", "hyperium"];
-const BIG_QUERY: &str = "https://bigquery.googleapis.com/bigquery/v2";
+const GIB: f64 = (1u64 << 30) as f64;
@@ -0,0 +1,103 @@
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::render::summary;
+use crate::{Activity, Http};
+
+/// A Mastodon account to post summaries (toots) to
+#[derive(Debug, Deserialize)]
+pub struct Mastodon {
+    /// The host name of the instance, like `hachyderm.io`
+    pub instance: String,
+    /// An access token with the `write:statuses` scope (or see `MASTODON_TOKEN`)
+    pub token: Option<String>,
+}
+
+impl Mastodon {
+    /// Post `status` publicly, returning the URL of the post
+    pub async fn post(&self, status: &str, token: &str, http: &Http) -> anyhow::Result<String> {
+        let url = format!("https://{}/api/v1/statuses", self.instance);
+        info!(url, "posting status");
+        let _permit = http.acquire(&url).await?;
+        let status = http
+            .client()
+            .post(&url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "status": status, "visibility": "public" }))
+            .send()
+            .await?
+            .error_for_status()
+            .context("failed to post status")?
+            .json::<Status>()
+            .await?;
+        Ok(status.url)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Status {
+    url: String,
+}
+
+/// Condense `activity` during the period `name` into at most `limit` characters, like
+/// "October 2024: 12 pull requests in 5 projects. Highlights: …"
+///
+/// The highlights are the titles of the items with the most interactions, as many as fit.
+pub fn toot(activity: &Activity, name: &str, limit: usize) -> String {
+    let mut toot = format!("{name}: {}.", summary(activity));
+    let mut sep = " Highlights: ";
+    for (_, _, entry) in activity.highlights(usize::MAX) {
+        let title = entry.title.trim();
+        // Leave room for the closing period
+        if toot.chars().count() + sep.chars().count() + title.chars().count() + 1 > limit {
+            break;
+        }
+        toot.push_str(sep);
+        toot.push_str(title);
+        sep = "; ";
+    }
+    if sep == "; " {
+        toot.push('.');
+    }
+
+    match toot.chars().count() > limit {
+        true => {
+            let toot = toot
+                .chars()
+                .take(limit.saturating_sub(1))
+                .collect::<String>();
+            format!("{}…", toot.trim_end())
+        }
+        false => toot,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind, Role};
+
+    #[test]
+    fn condensed() {
+        let mut activity = Activity::default();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        for (n, title) in [(1, "Add HTTP/3 support"), (2, "Fix a panic")] {
+            let mut entry = Entry::new(title.to_owned(), ItemKind::PullRequest);
+            entry.interactions.insert(Role::Comment, n);
+            let url = format!("https://github.com/hyperium/hyper/pull/{n}");
+            entries.insert(url, entry);
+        }
+
+        assert_eq!(
+            toot(&activity, "March 2024", 500),
+            "March 2024: 2 pull requests in 1 project. \
+             Highlights: Fix a panic; Add HTTP/3 support."
+        );
+        assert_eq!(
+            toot(&activity, "March 2024", 70),
+            "March 2024: 2 pull requests in 1 project. Highlights: Fix a panic."
+        );
+        assert_eq!(toot(&activity, "March 2024", 20), "March 2024: 2 pull…");
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Context;
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::Http;
+
+/// A Matrix room to post reports to, through the client-server API
+#[derive(Debug, Deserialize)]
+pub struct Matrix {
+    /// The base URL of the homeserver, like `https://matrix.org`
+    pub homeserver: String,
+    /// The ID of the room (like `!abc123:matrix.org`, rather than an alias), which the account
+    /// must have joined
+    pub room: String,
+    /// An access token for the account to post as (or see `MATRIX_TOKEN`)
+    pub token: Option<String>,
+}
+
+impl Matrix {
+    /// Post `report` as a text message, returning a link to it
+    pub async fn post(&self, report: &str, token: &str, http: &Http) -> anyhow::Result<String> {
+        // Retrying with the same transaction ID would not post the message twice
+        let txn = format!("tmog-events-{}", Utc::now().timestamp_millis());
+        let mut url = reqwest::Url::parse(&self.homeserver).context("invalid homeserver URL")?;
+        url.path_segments_mut()
+            .map_err(|()| anyhow::Error::msg("invalid homeserver URL"))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3", "rooms", &self.room])
+            .extend(["send", "m.room.message", &txn]);
+
+        info!(%url, "sending message");
+        let _permit = http.acquire(url.as_str()).await?;
+        let sent = http
+            .client()
+            .put(url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "msgtype": "m.text", "body": report }))
+            .send()
+            .await?
+            .error_for_status()
+            .context("failed to send message")?
+            .json::<Sent>()
+            .await?;
+        Ok(format!(
+            "https://matrix.to/#/{}/{}",
+            self.room, sent.event_id
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Sent {
+    event_id: String,
+}
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+/// Add the items in `report` that are missing from the `existing` report, preserving the rest
+///
+/// Both reports are reStructuredText as written by [`Rst`](crate::Rst). Items are bullets with a
+/// link; they are considered new if their link doesn't occur in the `existing` report. New items
+/// are added at the end of the same (sub)section in the existing report, which is created if
+/// needed. Summary lines (on pushes, stars, forks, sponsors and days) replace the line on the
+/// same repository (or user or day) in their section instead, since their counts and links change.
+pub fn merge(existing: &str, report: &str) -> String {
+    let mut lines = existing.lines().map(str::to_owned).collect::<Vec<_>>();
+    let known = lines
+        .iter()
+        .filter_map(|line| link(line))
+        .map(str::to_owned)
+        .collect::<HashSet<_>>();
+
+    let new = report.lines().collect::<Vec<_>>();
+    let headings = headings(&new);
+    for (i, line) in new.iter().enumerate() {
+        let summary = summary(line);
+        if !line.starts_with("* ")
+            || (summary.is_none() && link(line).is_none_or(|link| known.contains(link)))
+        {
+            continue;
+        }
+
+        // The headings of the (sub)sections containing the item, from the top level down
+        let mut path = Vec::<&Heading>::new();
+        for heading in headings.iter().take_while(|heading| heading.line < i) {
+            path.retain(|parent| parent.level < heading.level);
+            path.push(heading);
+        }
+        let item = &new[i..i + 1 + continuation(&new[i + 1..])];
+        insert(&mut lines, &path, item, summary);
+    }
+
+    let mut merged = lines.join("\n");
+    merged.push('\n');
+    merged
+}
+
+/// Insert the `item` (its line and any notes) at the end of the section at `path`, creating
+/// (sub)sections as needed
+///
+/// If the section has a line with the same `summary` already, that one is replaced instead.
+fn insert(
+    lines: &mut Vec<String>,
+    path: &[&Heading],
+    item: &[&str],
+    summary: Option<(&str, &str)>,
+) {
+    let line = item[0];
+    let existing = headings(lines);
+    let (mut start, mut end, mut level) = (0, lines.len(), None);
+    let mut missing = path;
+    while let Some((heading, rest)) = missing.split_first() {
+        let found = existing.iter().find(|candidate| {
+            candidate.line >= start
+                && candidate.line < end
+                && level.is_none_or(|level| candidate.level > level)
+                && candidate.title == heading.title
+        });
+        let Some(found) = found else {
+            break;
+        };
+
+        start = found.line;
+        end = existing
+            .iter()
+            .find(|next| next.line > found.line && next.level <= found.level)
+            .map_or(lines.len(), |next| next.start);
+        level = Some(found.level);
+        missing = rest;
+    }
+
+    if let Some(summary) = summary.filter(|_| missing.is_empty()) {
+        let same = (start..end).find(|&i| self::summary(&lines[i]) == Some(summary));
+        if let Some(i) = same {
+            lines[i] = line.to_owned();
+            return;
+        }
+    }
+
+    // Insert after the last item of the section (keeping any closing remarks at the end), or
+    // after its last non-blank line if there are none
+    let last = match missing.is_empty() {
+        true => (start..end).rev().find(|&i| lines[i].starts_with("* ")),
+        false => None,
+    };
+    let mut at = last.map_or(end, |last| last + 1 + continuation(&lines[last + 1..end]));
+    while last.is_none() && at > start && lines[at - 1].trim().is_empty() {
+        at -= 1;
+    }
+
+    let mut insert = Vec::new();
+    for heading in missing {
+        insert.push(String::new());
+        insert.push(heading.title.clone());
+        insert.push(
+            heading
+                .underline
+                .to_string()
+                .repeat(heading.title.chars().count()),
+        );
+    }
+    // Separate the item from a preceding heading or paragraph (but not from preceding items)
+    if !missing.is_empty() || (at > 0 && !lines[at - 1].starts_with("* ")) {
+        insert.push(String::new());
+    }
+    insert.extend(item.iter().map(|line| (*line).to_owned()));
+    if lines.get(at).is_some_and(|next| !next.trim().is_empty()) {
+        insert.push(String::new());
+    }
+    if at == 0 {
+        let blank = insert.iter().take_while(|line| line.is_empty()).count();
+        insert.drain(..blank);
+    }
+    lines.splice(at..at, insert);
+}
+
+/// The number of `lines` continuing the item before them, like the paragraphs of its notes
+fn continuation(lines: &[impl AsRef<str>]) -> usize {
+    let indented = |line: &str| line.starts_with("  ") && !line.trim().is_empty();
+    let mut len = 0;
+    while let Some(line) = lines.get(len).map(AsRef::as_ref) {
+        let next = lines.get(len + 1).map(AsRef::as_ref);
+        match indented(line) || (line.trim().is_empty() && next.is_some_and(indented)) {
+            true => len += 1,
+            false => break,
+        }
+    }
+    len
+}
+
+/// A section heading in a report
+struct Heading {
+    title: String,
+    underline: char,
+    /// The nesting level, by the order in which underline characters first occur
+    level: usize,
+    /// The index of the line with the title
+    line: usize,
+    /// The index of the first line of the heading (including any overline)
+    start: usize,
+}
+
+/// Find the section headings in `lines`
+fn headings<S: AsRef<str>>(lines: &[S]) -> Vec<Heading> {
+    let mut underlines = Vec::new();
+    let mut headings = Vec::new();
+    for (i, pair) in lines.windows(2).enumerate() {
+        let (title, next) = (pair[0].as_ref().trim_end(), pair[1].as_ref().trim_end());
+        let Some(underline) = next.chars().next() else {
+            continue;
+        };
+        if title.trim().is_empty()
+            || rule(title)
+            || !rule(next)
+            || next.chars().count() < title.chars().count()
+        {
+            continue;
+        }
+
+        let overlined = i > 0 && lines[i - 1].as_ref().trim_end() == next;
+        let level = match underlines.iter().position(|&c| c == (underline, overlined)) {
+            Some(level) => level,
+            None => {
+                underlines.push((underline, overlined));
+                underlines.len() - 1
+            }
+        };
+        headings.push(Heading {
+            title: title.to_owned(),
+            underline,
+            level,
+            line: i,
+            start: if overlined { i - 1 } else { i },
+        });
+    }
+    headings
+}
+
+/// Whether `line` consists of a single repeated punctuation character (as for a heading)
+fn rule(line: &str) -> bool {
+    let mut chars = line.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    first.is_ascii_punctuation() && chars.all(|c| c == first)
+}
+
+/// The kind of summary in `line` and the repository (or user or day) it's about, if any
+fn summary(line: &str) -> Option<(&'static str, &str)> {
+    if let Some(rest) = line.strip_prefix("* `") {
+        let (commits, rest) = rest.split_once(" pushed to ")?;
+        let (repo, _) = rest.split_once(" <")?;
+        return commits
+            .trim_end_matches('s')
+            .ends_with(" commit")
+            .then_some(("pushes", repo));
+    }
+
+    let line = line.strip_prefix("* ")?;
+    if let Some((repo, _)) = line.split_once(" was forked ") {
+        Some(("forks", repo))
+    } else if let Some((user, rest)) = line.split_once(" gained ") {
+        rest.contains(" new sponsor").then_some(("sponsors", user))
+    } else if let Some((day, rest)) = line.split_once(": ") {
+        match rest.starts_with('+') && rest.contains(" star") {
+            true => Some(("stars", day)),
+            false => rest
+                .trim_end_matches('s')
+                .ends_with(" interaction")
+                .then_some(("day", day)),
+        }
+    } else {
+        None
+    }
+}
+
+/// The target of the (first) link in `line`, if any
+fn link(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once(" <")?;
+    let (link, _) = rest.split_once(">`_")?;
+    Some(link)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries() {
+        let existing = "\
+owner/repo
+==========
+
+* `Fix parser <https://github.com/owner/repo/pull/1>`_
+* `1 commit pushed to owner/repo <https://github.com/owner/repo/compare/a...b>`_
+
+Community
+=========
+
+* owner/repo: +1 star
+* owner/repo was forked once: `fork <https://github.com/fork/repo>`_
+";
+        let report = "\
+owner/repo
+==========
+
+* `Fix parser <https://github.com/owner/repo/pull/1>`_
+* `Add feature <https://github.com/owner/repo/pull/2>`_
+* `3 commits pushed to owner/repo <https://github.com/owner/repo/compare/a...c>`_
+
+Community
+=========
+
+* owner/repo: +2 stars
+* owner/repo was forked 2 times: `fork <https://github.com/fork/repo>`_, \
+`other <https://github.com/other/repo>`_
+";
+
+        let merged = merge(existing, report);
+        assert_eq!(merged.matches("Fix parser").count(), 1);
+        assert_eq!(merged.matches("Add feature").count(), 1);
+        assert_eq!(merged.matches("pushed to").count(), 1);
+        assert!(merged.contains("* `3 commits pushed to owner/repo"));
+        assert!(merged.contains("* owner/repo: +2 stars\n"));
+        assert_eq!(merged.matches("was forked").count(), 1);
+        assert!(merged.contains("was forked 2 times"));
+
+        // Merging the same report again changes nothing
+        assert_eq!(merge(&merged, report), merged);
+    }
+
+    #[test]
+    fn notes() {
+        let existing = "\
+owner/repo
+==========
+
+* `Fix parser <https://github.com/owner/repo/pull/1>`_
+
+  backported to 0.14
+";
+        let report = "\
+owner/repo
+==========
+
+* `Fix parser <https://github.com/owner/repo/pull/1>`_
+
+  backported to 0.14
+
+* `Add feature <https://github.com/owner/repo/pull/2>`_
+
+  needs a release
+
+* `Fix docs <https://github.com/owner/repo/pull/3>`_
+";
+
+        let merged = merge(existing, report);
+        assert_eq!(merged, report);
+        assert_eq!(merge(&merged, report), merged);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Counters for monitoring a long-running process, like `tmog-events serve`
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Number of events fetched from sources (not counting those loaded from the cache)
+    events: AtomicU64,
+    /// Number of API requests sent
+    requests: AtomicU64,
+    /// The number of requests left in the current rate limit window, by host
+    rate_limits: Mutex<BTreeMap<String, u64>>,
+    /// When activity was last collected without missing any events
+    synced: Mutex<Option<DateTime<Utc>>>,
+    /// Number of pages of events fetched, and the events on them (to show the progress)
+    pages: AtomicU64,
+    paged: AtomicU64,
+    /// The time of the oldest event reached by a source while paging back (to show the progress)
+    cursor: Mutex<Option<DateTime<Utc>>>,
+    /// How many seconds to wait at least between polls of an events feed, as last asked for by
+    /// GitHub (in `X-Poll-Interval`), and how long `watch --every` actually waits (0 if unknown)
+    requested_poll: AtomicU64,
+    poll: AtomicU64,
+}
+
+impl Metrics {
+    pub(crate) fn ingested(&self, events: usize) {
+        self.events.fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn requested(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn rate_limit(&self, host: &str, remaining: u64) {
+        let mut rate_limits = self.rate_limits.lock().unwrap();
+        rate_limits.insert(host.to_owned(), remaining);
+    }
+
+    pub(crate) fn poll_requested(&self, seconds: u64) {
+        self.requested_poll.store(seconds, Ordering::Relaxed);
+    }
+
+    /// The interval between polls that GitHub last asked for, if any
+    pub fn requested_poll_interval(&self) -> Option<Duration> {
+        let seconds = self.requested_poll.load(Ordering::Relaxed);
+        (seconds > 0).then(|| Duration::from_secs(seconds))
+    }
+
+    /// Record the interval that is actually waited between polls
+    pub fn polling(&self, interval: Duration) {
+        self.poll.store(interval.as_secs(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn synced(&self, time: DateTime<Utc>) {
+        *self.synced.lock().unwrap() = Some(time);
+    }
+
+    pub(crate) fn paged(&self, events: usize) {
+        self.pages.fetch_add(1, Ordering::Relaxed);
+        self.paged.fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    /// Forget the cursor, before collecting another interval
+    pub(crate) fn rewind(&self) {
+        *self.cursor.lock().unwrap() = None;
+    }
+
+    pub(crate) fn reached(&self, time: DateTime<Utc>) {
+        let mut cursor = self.cursor.lock().unwrap();
+        *cursor = Some(cursor.map_or(time, |cursor| cursor.min(time)));
+    }
+
+    /// A summary of the progress of fetching, like
+    /// "12 pages, 1180 events, back to 2024-03-14, 4870 requests left"
+    pub(crate) fn progress(&self) -> String {
+        let pages = self.pages.load(Ordering::Relaxed);
+        let events = self.paged.load(Ordering::Relaxed);
+        let mut progress = format!("{pages} pages, {events} events");
+        if let Some(cursor) = *self.cursor.lock().unwrap() {
+            write!(progress, ", back to {}", cursor.format("%Y-%m-%d")).unwrap();
+        }
+        // The host closest to its limit is the one holding things up
+        if let Some(remaining) = self.rate_limits.lock().unwrap().values().min() {
+            write!(progress, ", {remaining} requests left").unwrap();
+        }
+        progress
+    }
+
+    /// The metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let events = self.events.load(Ordering::Relaxed);
+        describe(
+            &mut out,
+            "events_ingested_total",
+            "counter",
+            "Events fetched from sources",
+        );
+        writeln!(out, "tmog_events_events_ingested_total {events}").unwrap();
+
+        let requests = self.requests.load(Ordering::Relaxed);
+        describe(
+            &mut out,
+            "api_requests_total",
+            "counter",
+            "API requests sent",
+        );
+        writeln!(out, "tmog_events_api_requests_total {requests}").unwrap();
+
+        let help = "Requests left in the rate limit window, as last reported by the host";
+        describe(&mut out, "rate_limit_remaining", "gauge", help);
+        for (host, remaining) in self.rate_limits.lock().unwrap().iter() {
+            writeln!(
+                out,
+                "tmog_events_rate_limit_remaining{{host=\"{host}\"}} {remaining}"
+            )
+            .unwrap();
+        }
+
+        let help = "When activity was last collected without missing events";
+        describe(&mut out, "last_sync_timestamp_seconds", "gauge", help);
+        if let Some(synced) = *self.synced.lock().unwrap() {
+            let time = synced.timestamp();
+            writeln!(out, "tmog_events_last_sync_timestamp_seconds {time}").unwrap();
+        }
+
+        let help = "Seconds to wait at least between polls of events, as last asked for by GitHub";
+        describe(&mut out, "requested_poll_interval_seconds", "gauge", help);
+        if let Some(interval) = self.requested_poll_interval() {
+            let seconds = interval.as_secs();
+            writeln!(out, "tmog_events_requested_poll_interval_seconds {seconds}").unwrap();
+        }
+        let help = "Seconds waited between polls of events";
+        describe(&mut out, "poll_interval_seconds", "gauge", help);
+        match self.poll.load(Ordering::Relaxed) {
+            0 => {}
+            seconds => writeln!(out, "tmog_events_poll_interval_seconds {seconds}").unwrap(),
+        }
+        out
+    }
+}
+
+fn describe(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP tmog_events_{name} {help}").unwrap();
+    writeln!(out, "# TYPE tmog_events_{name} {kind}").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn render() {
+        let metrics = Metrics::default();
+        metrics.ingested(12);
+        metrics.requested();
+        metrics.requested();
+        metrics.rate_limit("api.github.com", 4998);
+        metrics.synced(Utc.with_ymd_and_hms(2024, 4, 1, 12, 0, 0).unwrap());
+        metrics.poll_requested(60);
+        metrics.polling(Duration::from_secs(300));
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("\ntmog_events_events_ingested_total 12\n"));
+        assert!(rendered.contains("\ntmog_events_api_requests_total 2\n"));
+        assert!(
+            rendered.contains("\ntmog_events_rate_limit_remaining{host=\"api.github.com\"} 4998\n")
+        );
+        assert!(rendered.contains("\ntmog_events_last_sync_timestamp_seconds 1711972800\n"));
+        assert!(rendered.contains("\ntmog_events_requested_poll_interval_seconds 60\n"));
+        assert!(rendered.contains("\ntmog_events_poll_interval_seconds 300\n"));
+        assert!(rendered.contains("# TYPE tmog_events_api_requests_total counter\n"));
+    }
+
+    #[test]
+    fn progress() {
+        let metrics = Metrics::default();
+        assert_eq!(metrics.progress(), "0 pages, 0 events");
+        metrics.paged(100);
+        metrics.paged(30);
+        metrics.reached(Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap());
+        metrics.reached(Utc.with_ymd_and_hms(2024, 3, 14, 12, 0, 0).unwrap());
+        metrics.rate_limit("api.github.com", 4870);
+        metrics.rate_limit("gitlab.com", 1990);
+        assert_eq!(
+            metrics.progress(),
+            "2 pages, 130 events, back to 2024-03-14, 1990 requests left"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use std::future::Future;
+use std::io::{self, Write};
+use std::pin::pin;
+use std::time::Duration;
+
+use crate::Metrics;
+
+/// Shows the progress of collecting on the last line of the terminal (on stderr)
+///
+/// The line is redrawn a few times per second from the [`Metrics`] of the collector, and cleared
+/// once collecting is done.
+#[derive(Debug)]
+pub(crate) struct Progress<'a> {
+    metrics: &'a Metrics,
+}
+
+impl<'a> Progress<'a> {
+    pub(crate) fn new(metrics: &'a Metrics) -> Self {
+        metrics.rewind();
+        Self { metrics }
+    }
+
+    /// Drive `future` to completion, showing the progress in the meantime
+    pub(crate) async fn show<T>(self, future: impl Future<Output = T>) -> T {
+        let mut future = pin!(future);
+        let mut ticks = tokio::time::interval(Duration::from_millis(200));
+        let mut drawn = false;
+        let output = loop {
+            tokio::select! {
+                output = &mut future => break output,
+                _ = ticks.tick() => {
+                    self.draw(&self.metrics.progress());
+                    drawn = true;
+                }
+            }
+        };
+        if drawn {
+            self.draw("");
+        }
+        output
+    }
+
+    fn draw(&self, line: &str) {
+        // Return to the start of the line and clear it, for a shorter line to replace a longer one
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[K{line}");
+        let _ = stderr.flush();
+    }
+}
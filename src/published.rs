@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{load_state, save_state, Activity, Cipher, Interval};
+
+/// The items listed in earlier reports, so that later reports can leave them out or mark them
+///
+/// Items are identified by their URL, and remembered with the start of the first period they
+/// were reported for. Reporting the same period again (or an earlier one) isn't affected.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Published {
+    items: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl Published {
+    /// Load the items saved at `path`, if any
+    pub fn load(path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<Self> {
+        load_state(path, cipher)
+    }
+
+    pub fn save(&self, path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<()> {
+        save_state(self, path, cipher)
+    }
+
+    /// Deal with the items in `activity` for `interval` that were reported for an earlier period
+    pub fn apply(&self, activity: &mut Activity, interval: Interval, seen: Seen) {
+        let earlier = |html_url: &str| {
+            self.items
+                .get(html_url)
+                .is_some_and(|start| *start < interval.start)
+        };
+        for entries in activity.projects.values_mut() {
+            match seen {
+                Seen::Suppress => entries.retain(|html_url, _| !earlier(html_url)),
+                Seen::Continued => {
+                    for (html_url, entry) in entries.iter_mut() {
+                        entry.continued = earlier(html_url);
+                    }
+                }
+            }
+        }
+        activity.projects.retain(|_, entries| !entries.is_empty());
+    }
+
+    /// Remember the items in `activity` as reported for `interval`
+    pub fn record(&mut self, activity: &Activity, interval: Interval) {
+        for html_url in activity
+            .projects
+            .values()
+            .flat_map(|entries| entries.keys())
+        {
+            let start = self.items.entry(html_url.clone()).or_insert(interval.start);
+            *start = (*start).min(interval.start);
+        }
+    }
+}
+
+/// What to do with items that were listed in an earlier report (see [`Published`])
+#[derive(Clone, Copy, Debug, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Seen {
+    /// Leave them out
+    Suppress,
+    /// List them, marked as continued
+    Continued,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn seen() {
+        let url = "https://github.com/hyperium/hyper/pull/1";
+        let activity = || {
+            let mut activity = Activity::default();
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(
+                url.to_owned(),
+                Entry::new("Fix a panic".to_owned(), ItemKind::PullRequest),
+            );
+            activity
+        };
+        let (august, september) = (
+            Interval::month("202408").unwrap(),
+            Interval::month("202409").unwrap(),
+        );
+
+        let mut published = Published::default();
+        published.record(&activity(), september);
+        // Reporting the same period again, or an earlier one, lists the item as before
+        for interval in [august, september] {
+            let mut same = activity();
+            published.apply(&mut same, interval, Seen::Suppress);
+            assert!(!same.projects["hyper"][url].continued);
+        }
+
+        let october = Interval::month("202410").unwrap();
+        let mut continued = activity();
+        published.apply(&mut continued, october, Seen::Continued);
+        assert!(continued.projects["hyper"][url].continued);
+        let mut suppressed = activity();
+        published.apply(&mut suppressed, october, Seen::Suppress);
+        assert!(suppressed.projects.is_empty());
+
+        // The first period the item was reported for is kept
+        published.record(&activity(), august);
+        published.record(&activity(), october);
+        assert_eq!(published.items[url], august.start);
+    }
+}
@@ -0,0 +1,523 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Write;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{Activity, ItemKind, Shortcodes};
+
+/// Formats collected activity into a report, such as reStructuredText for a blog post
+pub trait Renderer {
+    /// Write the report for the activity of `users` to `out`
+    fn render(
+        &self,
+        activity: &Activity,
+        users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()>;
+}
+
+/// Rewrites item links for specific projects
+#[derive(Debug, Default)]
+pub struct Links {
+    /// Per-project prefixes, keyed by project name
+    pub prefixes: HashMap<String, LinkPrefix>,
+    /// The web URL item links start with (the default prefix to replace)
+    pub web_url: String,
+    /// The web URLs of the forges items come from, if collected from several (see [`Forges`]),
+    /// whose host is shown with each item
+    ///
+    /// [`Forges`]: crate::Forges
+    pub forges: Vec<String>,
+}
+
+impl Links {
+    /// Apply the link prefix configured for `project` (if any) to `url`
+    pub fn link<'u>(&self, project: &str, url: &'u str) -> Cow<'u, str> {
+        let Some(prefix) = self.prefixes.get(project) else {
+            return Cow::Borrowed(url);
+        };
+
+        let from = match &prefix.from {
+            Some(from) => from,
+            None => self.web_url(url),
+        };
+        match url.strip_prefix(from.as_str()) {
+            Some(rest) => Cow::Owned(format!("{}{rest}", prefix.to)),
+            None => Cow::Borrowed(url),
+        }
+    }
+
+    /// The host of the forge that `url` leads to, if items come from several forges
+    pub fn forge(&self, url: &str) -> Option<&str> {
+        if self.forges.len() < 2 {
+            return None;
+        }
+        let web_url = self
+            .forges
+            .iter()
+            .find(|web_url| url.starts_with(*web_url))?;
+        let host = web_url
+            .split_once("://")
+            .map_or(web_url.as_str(), |(_, host)| host);
+        Some(host.trim_end_matches('/'))
+    }
+
+    /// The web URL of the forge that `url` leads to
+    pub fn web_url(&self, url: &str) -> &String {
+        let forge = self.forges.iter().find(|web_url| url.starts_with(*web_url));
+        forge.unwrap_or(&self.web_url)
+    }
+}
+
+/// Tidies up item titles for the report
+#[derive(Debug, Default)]
+pub struct Titles {
+    /// Truncate titles longer than this many characters, ending them with an ellipsis
+    pub max_len: Option<usize>,
+    /// Strip conventional prefixes, like `fix:`, `chore(deps):` or `[WIP]`
+    pub strip_prefixes: bool,
+    /// Convert or strip emoji shortcodes (otherwise, they are left as is)
+    pub shortcodes: Option<Shortcodes>,
+}
+
+impl Titles {
+    /// Apply the configured normalizations to `title`
+    pub fn normalize<'t>(&self, title: &'t str) -> Cow<'t, str> {
+        match self.shortcodes.map(|shortcodes| emoji(title, shortcodes)) {
+            Some(Cow::Owned(title)) => Cow::Owned(self.tidy(&title).into_owned()),
+            Some(Cow::Borrowed(_)) | None => self.tidy(title),
+        }
+    }
+
+    /// Strip prefixes from and truncate `title` (as configured)
+    fn tidy<'t>(&self, title: &'t str) -> Cow<'t, str> {
+        let mut title = title.trim();
+        if self.strip_prefixes {
+            while let Some(rest) = strip_prefix(title) {
+                title = rest.trim_start();
+            }
+        }
+
+        match self.max_len {
+            Some(max) if title.chars().count() > max => {
+                let end = title
+                    .char_indices()
+                    .nth(max.saturating_sub(1))
+                    .map_or(title.len(), |(i, _)| i);
+                Cow::Owned(format!("{}…", title[..end].trim_end()))
+            }
+            _ => Cow::Borrowed(title),
+        }
+    }
+}
+
+/// Convert or strip the emoji shortcodes in `title`
+fn emoji(title: &str, shortcodes: Shortcodes) -> Cow<'_, str> {
+    let mut converted = String::with_capacity(title.len());
+    let (mut rest, mut changed) = (title, false);
+    while let Some((before, after)) = rest.split_once(':') {
+        let emoji = after
+            .split_once(':')
+            .and_then(|(code, after)| Some((emojis::get_by_shortcode(code)?, after)));
+        let Some((emoji, after)) = emoji else {
+            converted.push_str(before);
+            converted.push(':');
+            rest = after;
+            continue;
+        };
+
+        converted.push_str(before);
+        match shortcodes {
+            Shortcodes::Convert => converted.push_str(emoji.as_str()),
+            // Avoid doubled spaces if the shortcode was surrounded by spaces
+            Shortcodes::Strip if converted.ends_with(' ') || converted.is_empty() => {
+                rest = after.strip_prefix(' ').unwrap_or(after);
+                changed = true;
+                continue;
+            }
+            Shortcodes::Strip => {}
+        }
+        rest = after;
+        changed = true;
+    }
+
+    match changed {
+        true => {
+            converted.push_str(rest);
+            Cow::Owned(converted)
+        }
+        false => Cow::Borrowed(title),
+    }
+}
+
+/// Strip a single tag (like `[WIP]`) or conventional commit type (like `fix(parser)!:`)
+fn strip_prefix(title: &str) -> Option<&str> {
+    if let Some(rest) = title.strip_prefix('[') {
+        return Some(rest.split_once(']')?.1);
+    }
+
+    let (prefix, rest) = title.split_once(':')?;
+    let kind = prefix.trim_end_matches('!');
+    let kind = match kind.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => kind,
+    };
+    CONVENTIONAL
+        .iter()
+        .any(|conventional| kind.eq_ignore_ascii_case(conventional))
+        .then_some(rest)
+}
+
+/// Conventional prefixes of titles (see [`Titles::strip_prefixes`])
+const CONVENTIONAL: &[&str] = &[
+    "build", "chore", "ci", "deps", "docs", "feat", "fix", "perf", "refactor", "revert", "style",
+    "test", "tests", "wip",
+];
+
+/// Replaces the `from` prefix of item links with `to` (for redirects or analytics)
+#[derive(Debug, Deserialize)]
+pub struct LinkPrefix {
+    /// Prefix to replace (defaults to the web URL)
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// The title of a report and the days it covers
+#[derive(Debug)]
+pub struct Header {
+    pub title: String,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+/// A group of items within a project, selected by their labels
+#[derive(Debug, Deserialize)]
+pub struct Category {
+    /// The title of the group (such as "Fixes")
+    pub name: String,
+    /// Items with any of these labels (ignoring case) belong to the category
+    pub labels: Vec<String>,
+}
+
+impl Category {
+    /// The categories of changelogs (used unless `categories` are configured)
+    pub fn changelog() -> Vec<Self> {
+        CHANGELOG
+            .iter()
+            .map(|(name, labels)| Self {
+                name: name.to_string(),
+                labels: labels.iter().map(|label| label.to_string()).collect(),
+            })
+            .collect()
+    }
+}
+
+/// The categories of changelogs, with their labels (see [`Category::changelog()`])
+const CHANGELOG: &[(&str, &[&str])] = &[
+    ("Features", &["enhancement", "feature", "new feature"]),
+    ("Fixes", &["bug", "fix", "bugfix", "regression"]),
+    ("Docs", &["documentation", "docs"]),
+];
+
+/// Overrides for the section of a project, to keep recurring sections in the same shape
+#[derive(Debug, Default, Deserialize)]
+pub struct Section {
+    /// The heading of the section (defaults to the project name)
+    pub heading: Option<String>,
+    /// A sentence (in the markup of the report) to open the section with
+    pub intro: Option<String>,
+    /// Links to items to list first (in this order), if they're in the report
+    #[serde(default)]
+    pub pinned: Vec<String>,
+}
+
+/// Markup languages for reports, with their rules for escaping text and links
+///
+/// Renderers should pass all text taken from events (such as titles and names) through these.
+#[derive(Clone, Copy, Debug)]
+pub enum Markup {
+    /// reStructuredText
+    Rst,
+    Markdown,
+    Html,
+}
+
+impl Markup {
+    /// Escape `text` for use as the text of a link
+    pub fn link_text(self, text: &str) -> Cow<'_, str> {
+        let text = text.trim();
+        match self {
+            // Inline markup is not recognized within references, but backquotes would end the
+            // reference and angle brackets would be mistaken for the URL
+            Self::Rst => escape(text, |c, _| matches!(c, '\\' | '`' | '<' | '>')),
+            Self::Markdown => escape(text, |c, _| {
+                matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>')
+            }),
+            Self::Html => html(text),
+        }
+    }
+
+    /// Escape inline markup in plain `text`
+    pub fn text(self, text: &str) -> Cow<'_, str> {
+        let text = text.trim();
+        match self {
+            Self::Rst => escape(text, |c, next| match c {
+                '\\' | '*' | '`' | '|' => true,
+                // Underscores only start a reference at the end of a word
+                '_' => !next.is_some_and(|next| next.is_alphanumeric()),
+                _ => false,
+            }),
+            Self::Markdown => escape(text, |c, _| {
+                matches!(
+                    c,
+                    '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+                )
+            }),
+            Self::Html => html(text),
+        }
+    }
+
+    /// Make `url` safe for use as the target of a link
+    pub fn url(self, url: &str) -> Cow<'_, str> {
+        let special = |c: char| match self {
+            Self::Rst => c.is_whitespace() || matches!(c, '<' | '>' | '`'),
+            Self::Markdown => c.is_whitespace() || matches!(c, '<' | '>' | '(' | ')'),
+            Self::Html => c.is_whitespace() || matches!(c, '<' | '>' | '"'),
+        };
+        // Ampersands don't need encoding, but do need escaping in HTML
+        let escaped = matches!(self, Self::Html) && url.contains('&');
+        if !url.contains(special) && !escaped {
+            return Cow::Borrowed(url);
+        }
+
+        let mut encoded = String::with_capacity(url.len());
+        for c in url.chars() {
+            match special(c) {
+                true => {
+                    let mut buf = [0; 4];
+                    for byte in c.encode_utf8(&mut buf).bytes() {
+                        encoded.push_str(&format!("%{byte:02X}"));
+                    }
+                }
+                false => encoded.push(c),
+            }
+        }
+        let encoded = match self {
+            Self::Html => html(&encoded).into_owned(),
+            Self::Rst | Self::Markdown => encoded,
+        };
+        Cow::Owned(encoded)
+    }
+}
+
+/// Escape the characters with special meaning in HTML
+fn html(text: &str) -> Cow<'_, str> {
+    replace(text, |c, _| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        '"' => Some("&quot;"),
+        '\'' => Some("&#39;"),
+        _ => None,
+    })
+}
+
+/// Backslash-escape the characters in `text` for which `special` (given the next character)
+/// returns `true`
+fn escape(text: &str, special: impl Fn(char, Option<char>) -> bool) -> Cow<'_, str> {
+    replace(text, |c, next| special(c, next).then_some(""))
+}
+
+/// Apply `replacement` (given the next character) to each character in `text`
+///
+/// An empty replacement backslash-escapes the character. Control characters (such as line
+/// breaks, which would break most markup) are replaced by spaces.
+fn replace<'a>(
+    text: &'a str,
+    replacement: impl Fn(char, Option<char>) -> Option<&'static str>,
+) -> Cow<'a, str> {
+    let mut chars = text.chars().peekable();
+    let mut replaced = String::with_capacity(text.len());
+    let mut changed = false;
+    while let Some(c) = chars.next() {
+        match (c.is_control(), replacement(c, chars.peek().copied())) {
+            (true, _) => replaced.push(' '),
+            (false, Some("")) => {
+                replaced.push('\\');
+                replaced.push(c);
+            }
+            (false, Some(replacement)) => replaced.push_str(replacement),
+            (false, None) => {
+                replaced.push(c);
+                continue;
+            }
+        }
+        changed = true;
+    }
+
+    match changed {
+        true => Cow::Owned(replaced),
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// Names of the kinds of items in the summary (see [`Rst::stats`](crate::Rst::stats))
+pub(crate) const KINDS: [(ItemKind, &str); 5] = [
+    (ItemKind::PullRequest, "pull request"),
+    (ItemKind::Issue, "issue"),
+    (ItemKind::Release, "release"),
+    (ItemKind::Discussion, "discussion"),
+    (ItemKind::Other, "other item"),
+];
+
+/// Format `n` along with `noun`, pluralized as needed (by appending "s")
+pub(crate) fn plural(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    }
+}
+
+/// A line summarizing the number of items of each kind and projects in `activity`, like
+/// "12 pull requests, 3 issues in 5 projects"
+pub(crate) fn summary(activity: &Activity) -> String {
+    let entries = activity
+        .projects
+        .values()
+        .flat_map(|entries| entries.values());
+    let kinds = KINDS
+        .iter()
+        .map(|&(kind, name)| (entries.clone().filter(|e| e.kind == kind).count(), name))
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, name)| plural(n, name))
+        .collect::<Vec<_>>();
+    let projects = plural(activity.projects.len(), "project");
+    match kinds.is_empty() {
+        true => "No items".to_owned(),
+        false => format!("{} in {projects}", kinds.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rst_text() {
+        let rst = Markup::Rst;
+        assert_eq!(rst.text("*bold*"), r"\*bold\*");
+        assert_eq!(rst.text("owner/repo_"), r"owner/repo\_");
+        assert_eq!(rst.text("snake_case"), "snake_case");
+        assert_eq!(rst.text("`code`"), r"\`code\`");
+        assert_eq!(rst.text("a|b"), r"a\|b");
+        assert_eq!(rst.text("line\nbreak"), "line break");
+        assert_eq!(rst.text(""), "");
+    }
+
+    #[test]
+    fn rst_link_text() {
+        let rst = Markup::Rst;
+        assert_eq!(rst.link_text("Vec<T>"), r"Vec\<T\>");
+        assert_eq!(rst.link_text("use `foo`"), r"use \`foo\`");
+        assert_eq!(rst.link_text("*not* bold"), "*not* bold");
+        assert_eq!(rst.link_text("  padded  "), "padded");
+        assert_eq!(rst.link_text(""), "");
+        assert!(matches!(rst.link_text("plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn markdown_and_html() {
+        assert_eq!(Markup::Markdown.link_text("[x] *y*"), r"\[x\] \*y\*");
+        assert_eq!(Markup::Markdown.text("# 1"), r"\# 1");
+        assert_eq!(
+            Markup::Html.text(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn urls() {
+        let url = "https://example.com/a b<c>";
+        assert_eq!(Markup::Rst.url(url), "https://example.com/a%20b%3Cc%3E");
+        assert_eq!(Markup::Markdown.url("https://x/(y)"), "https://x/%28y%29");
+        assert_eq!(
+            Markup::Html.url("https://x/?a&b\""),
+            "https://x/?a&amp;b%22"
+        );
+        assert_eq!(Markup::Html.url("https://x/?a&b"), "https://x/?a&amp;b");
+        assert!(matches!(Markup::Rst.url("https://x/y"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(strip_prefix("fix: parser"), Some(" parser"));
+        assert_eq!(strip_prefix("feat(cli)!: flag"), Some(" flag"));
+        assert_eq!(strip_prefix("[WIP] thing"), Some(" thing"));
+        assert_eq!(strip_prefix("Note: not conventional"), None);
+        assert_eq!(strip_prefix("fix(unclosed: x"), None);
+
+        let titles = Titles {
+            strip_prefixes: true,
+            ..Titles::default()
+        };
+        assert_eq!(titles.normalize("[WIP] chore(deps): bump"), "bump");
+        assert_eq!(titles.normalize("Add a feature"), "Add a feature");
+    }
+
+    #[test]
+    fn forges() {
+        let prefix = LinkPrefix {
+            from: None,
+            to: "https://mirror.example/".to_owned(),
+        };
+        let mut links = Links {
+            prefixes: HashMap::from([("hyper".to_owned(), prefix)]),
+            web_url: "https://github.com/".to_owned(),
+            forges: Vec::new(),
+        };
+        let url = "https://codeberg.org/hyperium/hyper/pulls/1";
+        assert_eq!(links.forge(url), None);
+        assert_eq!(links.link("hyper", url), url);
+
+        links.forges = vec![
+            "https://github.com/".to_owned(),
+            "https://codeberg.org/".to_owned(),
+        ];
+        assert_eq!(links.forge(url), Some("codeberg.org"));
+        assert_eq!(
+            links.link("hyper", url),
+            "https://mirror.example/hyperium/hyper/pulls/1"
+        );
+    }
+
+    #[test]
+    fn truncation() {
+        let titles = Titles {
+            max_len: Some(8),
+            ..Titles::default()
+        };
+        assert_eq!(titles.normalize("short"), "short");
+        assert_eq!(titles.normalize("much too long"), "much to…");
+        assert_eq!(titles.normalize("ééééééééé"), "ééééééé…");
+    }
+
+    #[test]
+    fn emoji() {
+        let convert = Titles {
+            shortcodes: Some(Shortcodes::Convert),
+            ..Titles::default()
+        };
+        assert_eq!(convert.normalize(":tada: Release"), "🎉 Release");
+        assert_eq!(convert.normalize("time: 12:30"), "time: 12:30");
+
+        let strip = Titles {
+            shortcodes: Some(Shortcodes::Strip),
+            ..Titles::default()
+        };
+        assert_eq!(strip.normalize(":tada: Release :rocket:"), "Release");
+        assert_eq!(strip.normalize("a :bug: fix"), "a fix");
+    }
+}
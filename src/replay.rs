@@ -0,0 +1,212 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::Context;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::github::RestEvent;
+use crate::{Cost, EventSource, Fetch, Http, Interval};
+
+/// Replays GitHub events from a dump (see [`Collector::with_dump()`](crate::Collector::with_dump)),
+/// without making any requests
+///
+/// The events for a user are those dumped from their feed (`/users/{user}/events`), which are
+/// processed just like events fetched from the REST API.
+pub struct Replay {
+    events: Vec<Dumped>,
+}
+
+impl Replay {
+    /// Load the dump at `path`
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        let mut events = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line).with_context(|| {
+                format!("invalid event on line {} of {}", i + 1, path.display())
+            })?;
+            events.push(event);
+        }
+        Ok(Self { events })
+    }
+}
+
+impl EventSource for Replay {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec!["https://github.com".to_owned()]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        format!("{user}@replay")
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        // Followed repositories are named `owner/name` (see `Collector::follow()`)
+        let feed = match user.contains('/') {
+            true => format!("/repos/{user}/events"),
+            false => format!("/users/{user}/events"),
+        };
+        let events = self.events.iter().filter(move |dumped| {
+            reqwest::Url::parse(&dumped.url).is_ok_and(|url| url.path().ends_with(&feed))
+        });
+        let events = events.filter_map(move |dumped| {
+            let event = match RestEvent::deserialize(&dumped.event) {
+                Ok(event) => event,
+                Err(error) => {
+                    let (url, event) = (&dumped.url, &dumped.event);
+                    if http.strict {
+                        let error = anyhow::Error::new(error);
+                        return Some(Err(error.context(format!("malformed event: {event}"))));
+                    }
+                    warn!(url, %event, "skipping malformed event: {error}");
+                    return None;
+                }
+            };
+            interval
+                .contains(event.created_at)
+                .then(|| Ok(Fetch::Event(event.normalize())))
+        });
+        Box::pin(futures_util::stream::iter(events))
+    }
+
+    fn estimate<'a>(
+        &'a self,
+        _user: &'a str,
+        _interval: Interval,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async {
+            Ok(Cost {
+                requests: 0,
+                exact: true,
+                bytes_processed: None,
+            })
+        })
+    }
+}
+
+/// A line in a dump
+#[derive(Debug, Deserialize)]
+struct Dumped {
+    /// The page the event was on
+    url: String,
+    event: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::{Collector, Window};
+
+    #[tokio::test]
+    async fn replayed() {
+        let event = |user: &str, feed: &str, day| {
+            let event = serde_json::json!({
+                "type": "WatchEvent",
+                "repo": { "name": "hyperium/hyper" },
+                "public": true,
+                "created_at": format!("2024-03-{day:02}T12:00:00Z"),
+                "payload": { "action": "started" },
+            });
+            Dumped {
+                url: format!("https://api.github.com/users/{user}/{feed}?per_page=100&page=1"),
+                event,
+            }
+        };
+        let replay = Replay {
+            events: vec![
+                event("djc", "events", 1),
+                event("djc", "received_events", 2),
+                event("other", "events", 3),
+                event("djc", "events", 31),
+            ],
+        };
+
+        // The last day is left out, to check that events outside the interval are skipped
+        let mut interval = Interval::month("202403").unwrap();
+        interval.end = Utc.with_ymd_and_hms(2024, 3, 31, 0, 0, 0).unwrap();
+        let http = Http::new(reqwest::Client::new(), 1, 1);
+        let fetched = replay
+            .fetch("djc", interval, &http)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let [Fetch::Event(event)] = &fetched[..] else {
+            panic!("expected a single event, got {fetched:?}");
+        };
+        assert!(event.contains(r#""created_at":"2024-03-01T12:00:00+00:00""#));
+        assert!(event.contains(r#""type":"WatchEvent""#));
+    }
+
+    #[tokio::test]
+    async fn strict() {
+        let event = |kind: &str, public| {
+            serde_json::json!({
+                "type": kind,
+                "repo": { "name": "hyperium/hyper" },
+                "public": public,
+                "created_at": "2024-03-01T12:00:00Z",
+                "payload": {},
+            })
+        };
+        let url = "https://api.github.com/users/djc/events?per_page=100".to_owned();
+        let replay = |event: &serde_json::Value| Replay {
+            events: vec![Dumped {
+                url: url.clone(),
+                event: event.clone(),
+            }],
+        };
+        let (unknown, malformed) = (
+            event("FancyNewEvent", serde_json::json!(true)),
+            event("WatchEvent", serde_json::json!("yes")),
+        );
+
+        let interval = Interval::month("202403").unwrap();
+        let (users, window) = (["djc".to_owned()], Window::default());
+        let collector = |event, strict| {
+            Collector::new(Box::new(replay(event)), reqwest::Client::new(), 1, 1)
+                .with_cache(false)
+                .with_strict(strict)
+        };
+        for event in [&unknown, &malformed] {
+            let activity = collector(event, false)
+                .collect(interval, &users, window)
+                .await;
+            assert!(activity.unwrap().projects.is_empty());
+        }
+
+        let error = collector(&unknown, true)
+            .collect(interval, &users, window)
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unknown event type FancyNewEvent: {"));
+        let error = collector(&malformed, true)
+            .collect(interval, &users, window)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with("malformed event: {"));
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{Datelike, Days};
+
+use crate::{Interval, Locale, Period};
+
+/// A directory of past reports, with a file per period in a directory per year
+///
+/// Monthly reports are stored as `2024/03.rst`, quarterly ones as `2024/q1.rst`, weekly ones as
+/// `2024/w09.rst` (by ISO week) and yearly ones as `2024/year.rst`. The `index.rst` in the
+/// top-level directory links to all of them.
+pub struct Reports {
+    dir: PathBuf,
+}
+
+impl Reports {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The path for the report on the `period` starting at `interval`
+    ///
+    /// Fails if there is a report at that path already, unless `force` is set.
+    pub fn claim(
+        &self,
+        period: Period,
+        interval: Interval,
+        force: bool,
+    ) -> anyhow::Result<PathBuf> {
+        let path = self.path(period, interval);
+        match !force && path.exists() {
+            true => anyhow::bail!(
+                "{} exists already (use --force to overwrite)",
+                path.display()
+            ),
+            false => Ok(path),
+        }
+    }
+
+    fn path(&self, period: Period, interval: Interval) -> PathBuf {
+        let start = interval.date(interval.start);
+        let (year, name) = match period {
+            // The middle of the week decides its ISO week (even if weeks start on Sunday)
+            Period::Week => {
+                let week = (start + Days::new(3)).iso_week();
+                (week.year(), format!("w{:02}", week.week()))
+            }
+            Period::Month => (start.year(), format!("{:02}", start.month())),
+            Period::Quarter => (start.year(), format!("q{}", start.month0() / 3 + 1)),
+            Period::Year => (start.year(), "year".to_owned()),
+        };
+        self.dir.join(year.to_string()).join(format!("{name}.rst"))
+    }
+
+    /// Write `report` to `path` (see [`Reports::claim()`]), creating its directory as needed
+    pub fn write(&self, path: &Path, report: &[u8]) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(path, report).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Regenerate `index.rst`, with a section per year linking to its reports (newest first)
+    pub fn index(&self, locale: Locale) -> anyhow::Result<()> {
+        let mut years = BTreeMap::<i32, Vec<(String, String)>>::new();
+        for year in read_dir(&self.dir)? {
+            let Some(number) = year.to_str().and_then(|year| year.parse().ok()) else {
+                continue;
+            };
+
+            let reports = years.entry(number).or_default();
+            for file in read_dir(&self.dir.join(&year))? {
+                let Some(stem) = file.to_str().and_then(|file| file.strip_suffix(".rst")) else {
+                    continue;
+                };
+                if let Some(name) = name(number, stem, locale) {
+                    reports.push((name, format!("{number}/{stem}.rst")));
+                }
+            }
+            reports.sort_by(|(_, a), (_, b)| b.cmp(a));
+        }
+
+        let title = locale.translate("Reports");
+        let mut index = format!("{title}\n{}\n\n", "=".repeat(title.chars().count()));
+        for (year, reports) in years.iter().rev() {
+            if reports.is_empty() {
+                continue;
+            }
+
+            write!(index, "{year}\n----\n\n")?;
+            for (name, path) in reports {
+                writeln!(index, "* `{name} <{path}>`_")?;
+            }
+            index.push('\n');
+        }
+
+        let path = self.dir.join("index.rst");
+        fs::write(&path, index).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// The name of the period for the report named `stem` in `year` (see [`Reports::claim()`])
+fn name(year: i32, stem: &str, locale: Locale) -> Option<String> {
+    if stem == "year" {
+        return Some(year.to_string());
+    } else if let Some(quarter) = stem.strip_prefix('q') {
+        let quarter = quarter
+            .parse::<u32>()
+            .ok()
+            .filter(|q| (1..=4).contains(q))?;
+        return Some(format!("Q{quarter} {year}"));
+    } else if let Some(week) = stem.strip_prefix('w') {
+        let week = week.parse::<u32>().ok()?;
+        return Some(format!("{} {week}, {year}", locale.translate("Week")));
+    }
+
+    let month = stem.parse::<u32>().ok().filter(|m| (1..=12).contains(m))?;
+    Some(format!("{} {year}", locale.month(month - 1)))
+}
+
+/// The names of the entries in `dir`
+fn read_dir(dir: &Path) -> anyhow::Result<Vec<std::ffi::OsString>> {
+    let entries = fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+    entries
+        .map(|entry| Ok(entry?.file_name()))
+        .collect::<Result<_, std::io::Error>>()
+        .with_context(|| format!("failed to read {}", dir.display()))
+}
@@ -0,0 +1,1530 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+
+use chrono::{DateTime, Weekday};
+use chrono_tz::Tz;
+
+use crate::render::{plural, KINDS};
+use crate::{
+    About, Activity, Category, Comparison, Entry, Header, ItemKind, Links, Locale, Markup,
+    ProjectOrder, Pushes, Renderer, Role, ScoreWeights, Section, Sizes, SmallProjects, Sort,
+    Sponsor, Sponsors, State, Stats, Streaks, Titles, Verdict, Weights, TRIAGE,
+};
+
+/// Renders reports as reStructuredText, with a section per project
+#[derive(Debug, Default)]
+pub struct Rst {
+    /// Annotate each item with the user(s) who touched it
+    pub attribution: bool,
+    /// Annotate each item with the role(s) of the user(s) (e.g. `[author]`) and the number of
+    /// reviews and comments
+    pub roles: bool,
+    /// How to order the projects
+    pub project_order: ProjectOrder,
+    /// Projects with fewer items are considered small
+    pub min_items: usize,
+    /// What to do with small projects (see `min_items`)
+    pub small_projects: SmallProjects,
+    /// How to order the items within each project
+    pub sort: Sort,
+    /// Split the items of each project into groups by their kind
+    pub group_by_kind: bool,
+    /// Split the items of each project into groups by their labels (instead of by kind)
+    pub categories: Vec<Category>,
+    /// Per-project overrides of the heading, intro and order of the sections, keyed by project name
+    pub sections: HashMap<String, Section>,
+    /// Split the report into projects maintained by the users and contributions to others
+    pub split_maintained: bool,
+    /// Open the report with a section listing this many of the most significant items
+    pub highlights: Option<usize>,
+    /// How to rank the items for the highlights
+    pub weights: Weights,
+    /// Move releases (and tags) out of the project sections into a section of their own at the
+    /// top, by date
+    pub releases_section: bool,
+    /// Mark items that have been merged (or completed) or closed, if their state is known
+    pub states: bool,
+    /// Mark pull requests with their size, if their details are known
+    pub sizes: Option<Sizes>,
+    /// Summarize the triage of issues in each project, like "closed 14 issues, labeled 22"
+    pub triage: bool,
+    /// Note who co-authored pushed commits, like "(pairing with alice)"
+    pub pairing: bool,
+    /// Move pull requests that the users merged for others out of the project sections into a
+    /// section of their own (if their states are known)
+    pub merged_section: bool,
+    /// Move items that the users were only mentioned in, requested to review or assigned to (as
+    /// found in their notifications) out of the project sections into a section of their own
+    pub mentions_section: bool,
+    /// How to tidy up item titles
+    pub titles: Titles,
+    /// Prefix the titles of issues, pull requests and discussions with their number
+    pub numbers: bool,
+    /// Add a section with summary statistics at the top or bottom of the report
+    pub stats: Option<Stats>,
+    /// Score the projects in the summary statistics by these weights
+    pub scores: Option<ScoreWeights>,
+    /// Add a section with the number of interactions on each day
+    pub days: bool,
+    /// Add a section with the number of interactions on each day of the week and in each part of
+    /// the day
+    pub times: bool,
+    /// Add a section comparing the activity to that of the previous period
+    pub comparison: Option<Comparison>,
+    /// Append a per-person breakdown to the report
+    pub appendix: bool,
+    /// Add a section on new sponsors
+    pub sponsors: Option<Sponsors>,
+    pub links: Links,
+    /// The language for headings
+    pub locale: Locale,
+    /// Open the report with a title and the days it covers
+    pub header: Option<Header>,
+    /// Close the report with a note on the days with activity and the streaks of them
+    pub streaks: Option<Streaks>,
+    /// Close the report with a note on when (and by what) it was generated
+    pub generated: Option<DateTime<Tz>>,
+}
+
+impl Rst {
+    /// Write a section for each project, optionally restricted to the items touched by `user`
+    /// and to the projects (not) maintained by the users
+    fn projects(
+        &self,
+        out: &mut dyn Write,
+        activity: &Activity,
+        user: Option<&str>,
+        maintained: Option<bool>,
+        underline: char,
+        group_underline: char,
+    ) -> anyhow::Result<()> {
+        let touched = |users: &BTreeSet<String>| user.is_none_or(|user| users.contains(user));
+        let projects = activity
+            .projects
+            .keys()
+            .chain(activity.pushes.keys())
+            .filter(|project| {
+                maintained
+                    .is_none_or(|maintained| activity.maintained.contains(*project) == maintained)
+            })
+            .collect::<BTreeSet<_>>();
+        let mut sections = Vec::new();
+        for project in projects {
+            let mut items = activity
+                .projects
+                .get(project)
+                .into_iter()
+                .flatten()
+                .filter(|(_, entry)| touched(&entry.users))
+                .filter(|(_, entry)| !self.releases_section || entry.kind != ItemKind::Release)
+                .filter(|(_, entry)| !self.merged_section || !merged_for_others(entry))
+                .filter(|(_, entry)| !self.mentions_section || !pulled_in(entry))
+                .collect::<Vec<_>>();
+            match self.sort {
+                Sort::Number => items.sort_by_key(|(html_url, _)| number(html_url)),
+                Sort::Date => items.sort_by_key(|(html_url, entry)| (entry.first, *html_url)),
+            }
+            // Items reordered during review go first, in their order, after any pinned ones
+            items.sort_by_key(|(_, entry)| entry.position.unwrap_or(usize::MAX));
+            if let Some(section) = self.sections.get(project.as_str()) {
+                items.sort_by_key(|(html_url, _)| {
+                    let target = html_url.split('#').next();
+                    let mut pinned = section.pinned.iter();
+                    pinned
+                        .position(|url| Some(url.as_str()) == target)
+                        .unwrap_or(usize::MAX)
+                });
+            }
+            let pushes = activity
+                .pushes
+                .get(project)
+                .into_iter()
+                .flatten()
+                .filter(|(_, pushes)| touched(&pushes.users))
+                .collect::<Vec<_>>();
+            if !items.is_empty() || !pushes.is_empty() {
+                sections.push((project, items, pushes));
+            }
+        }
+
+        // Sorting is stable, so projects with the same number of items remain in order
+        if let ProjectOrder::Count = self.project_order {
+            sections.sort_by_key(|(_, items, pushes)| Reverse(items.len() + pushes.len()));
+        }
+
+        let big =
+            |(_, items, pushes): &(_, Vec<_>, Vec<_>)| items.len() + pushes.len() >= self.min_items;
+        let (sections, small) = sections.into_iter().partition::<Vec<_>, _>(big);
+        for (project, items, pushes) in sections {
+            let section = self.sections.get(project.as_str());
+            let title = section.and_then(|section| section.heading.as_deref());
+            heading(out, title.unwrap_or(project), underline)?;
+            if let Some(about) = activity.about.get(project.as_str()) {
+                describe(out, about)?;
+            }
+            if let Some(intro) = section.and_then(|section| section.intro.as_deref()) {
+                write!(out, "{}\n\n", intro.trim())?;
+            }
+            // Triage isn't attributed to users, so it's left out of their breakdowns
+            match activity.triage.get(project.as_str()) {
+                Some(actions) if self.triage && user.is_none() => triage(out, actions)?,
+                _ => {}
+            }
+            if !self.group_by_kind && self.categories.is_empty() {
+                for (html_url, entry) in items {
+                    write!(out, "* ")?;
+                    self.entry(out, project, html_url, entry, user)?;
+                }
+                for (repo, pushes) in pushes {
+                    self.pushes(out, project, repo, pushes, user)?;
+                }
+                writeln!(out)?;
+                continue;
+            }
+
+            for (title, group) in self.groups(items) {
+                if group.is_empty() {
+                    continue;
+                }
+
+                heading(out, title, group_underline)?;
+                for (html_url, entry) in group {
+                    write!(out, "* ")?;
+                    self.entry(out, project, html_url, entry, user)?;
+                }
+                writeln!(out)?;
+            }
+
+            if !pushes.is_empty() {
+                heading(out, self.locale.translate("Commits"), group_underline)?;
+                for (repo, pushes) in pushes {
+                    self.pushes(out, project, repo, pushes, user)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        if small.is_empty() || self.small_projects == SmallProjects::Drop {
+            return Ok(());
+        }
+
+        heading(out, self.locale.translate("Other"), underline)?;
+        for (project, items, pushes) in small {
+            // Label the items, since they lack the context of a project section
+            for (html_url, entry) in items {
+                write!(out, "* {}: ", MARKUP.text(project))?;
+                self.entry(out, project, html_url, entry, user)?;
+            }
+            for (repo, pushes) in pushes {
+                self.pushes(out, project, repo, pushes, user)?;
+            }
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Write a section with the `n` most significant items across all projects (see `highlights`)
+    fn highlights(&self, out: &mut dyn Write, activity: &Activity, n: usize) -> anyhow::Result<()> {
+        let highlights = activity.ranked(n, &self.weights);
+        if highlights.is_empty() {
+            return Ok(());
+        }
+
+        heading(out, self.locale.translate("Highlights"), '=')?;
+        for (project, html_url, entry) in highlights {
+            write!(out, "* {}: ", MARKUP.text(project))?;
+            self.entry(out, project, html_url, entry, None)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Write a section with the releases across all projects, by date (see `releases_section`)
+    fn releases(&self, out: &mut dyn Write, activity: &Activity) -> anyhow::Result<()> {
+        let mut releases = activity
+            .projects
+            .iter()
+            .flat_map(|(project, entries)| {
+                entries
+                    .iter()
+                    .filter(|(_, entry)| entry.kind == ItemKind::Release)
+                    .map(move |(html_url, entry)| (project, html_url, entry))
+            })
+            .collect::<Vec<_>>();
+        if releases.is_empty() {
+            return Ok(());
+        }
+
+        releases.sort_by_key(|(_, html_url, entry)| (entry.first, *html_url));
+        heading(out, self.locale.translate("Releases"), '=')?;
+        for (project, html_url, entry) in releases {
+            // Label the releases, since they lack the context of a project section
+            write!(out, "* {}: ", MARKUP.text(project))?;
+            self.entry(out, project, html_url, entry, None)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Write a section with the pull requests the users merged for others (see `merged_section`)
+    fn merged(&self, out: &mut dyn Write, activity: &Activity) -> anyhow::Result<()> {
+        let merged = activity
+            .projects
+            .iter()
+            .flat_map(|(project, entries)| {
+                entries
+                    .iter()
+                    .filter(|(_, entry)| merged_for_others(entry))
+                    .map(move |(html_url, entry)| (project, html_url, entry))
+            })
+            .collect::<Vec<_>>();
+        if merged.is_empty() {
+            return Ok(());
+        }
+
+        heading(out, self.locale.translate("Merged pull requests"), '=')?;
+        for (project, html_url, entry) in merged {
+            write!(out, "* {}: ", MARKUP.text(project))?;
+            self.entry(out, project, html_url, entry, None)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Write a section with the items the users were pulled into (see `mentions_section`)
+    fn mentions(&self, out: &mut dyn Write, activity: &Activity) -> anyhow::Result<()> {
+        let pulled_in = activity
+            .projects
+            .iter()
+            .flat_map(|(project, entries)| {
+                entries
+                    .iter()
+                    .filter(|(_, entry)| pulled_in(entry))
+                    .map(move |(html_url, entry)| (project, html_url, entry))
+            })
+            .collect::<Vec<_>>();
+        if pulled_in.is_empty() {
+            return Ok(());
+        }
+
+        heading(out, self.locale.translate("Where I was pulled in"), '=')?;
+        for (project, html_url, entry) in pulled_in {
+            write!(out, "* {}: ", MARKUP.text(project))?;
+            self.entry(out, project, html_url, entry, None)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Split `items` into groups by category (if configured) or otherwise by kind
+    ///
+    /// Items are put in the first category with one of their labels (ignoring case), or "Other"
+    /// if none match.
+    fn groups<'a>(&'a self, items: Vec<Item<'a>>) -> Vec<(&'a str, Vec<Item<'a>>)> {
+        if self.categories.is_empty() {
+            return GROUPS
+                .into_iter()
+                .map(|(kind, title)| {
+                    let group = items.iter().filter(|(_, entry)| entry.kind == kind);
+                    (self.locale.translate(title), group.copied().collect())
+                })
+                .collect();
+        }
+
+        let (mut groups, mut rest) = (Vec::new(), items);
+        for category in &self.categories {
+            let (group, other) = rest.into_iter().partition(|(_, entry)| {
+                let labels = &category.labels;
+                entry.labels.iter().any(|label| {
+                    labels
+                        .iter()
+                        .any(|wanted| wanted.eq_ignore_ascii_case(label))
+                })
+            });
+            groups.push((category.name.as_str(), group));
+            rest = other;
+        }
+        groups.push((self.locale.translate("Other"), rest));
+        groups
+    }
+
+    /// Write the bullet for `entry` (after the bullet marker)
+    fn entry(
+        &self,
+        out: &mut dyn Write,
+        project: &str,
+        html_url: &str,
+        entry: &Entry,
+        user: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let link = self.links.link(project, entry.link(html_url));
+        let title = self.titles.normalize(&entry.title);
+        let (title, link) = (MARKUP.link_text(&title), MARKUP.url(&link));
+        let numbered = matches!(
+            entry.kind,
+            ItemKind::PullRequest | ItemKind::Issue | ItemKind::Discussion
+        );
+        match number(html_url) {
+            (_, Some(number), _) if self.numbers && numbered => {
+                write!(out, "`#{number}: {title} <{link}>`_")?
+            }
+            _ => write!(out, "`{title} <{link}>`_")?,
+        }
+        if let Some(forge) = self.links.forge(html_url) {
+            write!(out, " [{}]", MARKUP.text(forge))?;
+        }
+        match entry.state.filter(|_| self.states) {
+            Some(State::Merged | State::Completed) => write!(out, " ✔")?,
+            Some(State::Closed) => write!(out, " ✘")?,
+            Some(State::Open) | None => {}
+        }
+        // Merging one's own pull request goes without saying
+        if let Some(merger) = entry.merged_by.as_ref().filter(|_| self.states) {
+            if entry.author.as_ref() != Some(merger) {
+                let by = self.locale.translate("merged by");
+                write!(out, " ({by} {})", MARKUP.text(merger))?;
+            }
+        }
+        match (self.sizes, entry.size) {
+            (Some(Sizes::Badge), Some(size)) => write!(out, " [{}]", size.badge())?,
+            (Some(Sizes::Numbers), Some(size)) => write!(
+                out,
+                " (+{} -{}, {})",
+                size.additions,
+                size.deletions,
+                plural(size.changed_files as usize, "file")
+            )?,
+            _ => {}
+        }
+        if entry.first_contribution {
+            write!(out, " 🆕 {}", self.locale.translate("first contribution"))?;
+        }
+        if entry.continued {
+            write!(out, " ({})", self.locale.translate("continued"))?;
+        }
+        if self.roles {
+            roles(out, entry)?;
+        }
+        self.attribution(out, &entry.users, user)?;
+        // Paragraphs within the list item, which need blank lines around them
+        for note in &entry.notes {
+            writeln!(out, "\n  {}\n", MARKUP.text(note))?;
+        }
+        // Excerpts of several lines keep their line breaks as a line block
+        match entry.excerpt.as_deref() {
+            Some(excerpt) if excerpt.contains('\n') => {
+                writeln!(out)?;
+                for line in excerpt.lines() {
+                    writeln!(out, "{}", format!("  | {}", MARKUP.text(line)).trim_end())?;
+                }
+                writeln!(out)?;
+            }
+            Some(excerpt) => writeln!(out, "\n  {}\n", MARKUP.text(excerpt))?,
+            None => {}
+        }
+        // A nested list, which also needs blank lines around it
+        if !entry.assets.is_empty() {
+            writeln!(out)?;
+            for asset in &entry.assets {
+                let downloads = plural(asset.download_count as usize, "download");
+                writeln!(out, "  * {}: {downloads}", MARKUP.text(&asset.name))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn pushes(
+        &self,
+        out: &mut dyn Write,
+        project: &str,
+        repo: &str,
+        pushes: &Pushes,
+        user: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let compare = format!(
+            "{}/{repo}/compare/{}...{}",
+            pushes
+                .web_url
+                .as_ref()
+                .unwrap_or(&self.links.web_url)
+                .trim_end_matches('/'),
+            pushes.base,
+            pushes.head
+        );
+        let link = self.links.link(project, &compare);
+        let commits = match pushes.commits {
+            1 => "1 commit".to_owned(),
+            n => format!("{n} commits"),
+        };
+        let (repo, link) = (MARKUP.link_text(repo), MARKUP.url(&link));
+        write!(out, "* `{commits} pushed to {repo} <{link}>`_")?;
+        if self.pairing && !pushes.co_authors.is_empty() {
+            let co_authors = pushes.co_authors.iter().map(|name| MARKUP.text(name));
+            let co_authors = co_authors.collect::<Vec<_>>().join(", ");
+            write!(out, " (pairing with {co_authors})")?;
+        }
+        self.attribution(out, &pushes.users, user)
+    }
+
+    fn sponsors(
+        &self,
+        out: &mut dyn Write,
+        user: &str,
+        sponsors: &[Sponsor],
+        privacy: Sponsors,
+    ) -> anyhow::Result<()> {
+        let s = if sponsors.len() == 1 { "" } else { "s" };
+        let user = MARKUP.text(user);
+        write!(out, "* {user} gained {} new sponsor{s}", sponsors.len())?;
+        if let Sponsors::Count = privacy {
+            writeln!(out)?;
+            return Ok(());
+        }
+
+        let mut names = sponsors
+            .iter()
+            .filter(|sponsor| sponsor.public)
+            .map(|sponsor| {
+                let (login, html_url) = (
+                    MARKUP.link_text(&sponsor.login),
+                    MARKUP.url(&sponsor.html_url),
+                );
+                format!("`{login} <{html_url}>`_")
+            })
+            .collect::<Vec<_>>();
+        let private = sponsors.len() - names.len();
+        if private > 0 {
+            let s = if private == 1 { "" } else { "s" };
+            names.push(format!("{private} private sponsor{s}"));
+        }
+        writeln!(out, ": {}", names.join(", "))?;
+        Ok(())
+    }
+
+    /// Annotate the current line with `users` (if enabled) and end it
+    fn attribution(
+        &self,
+        out: &mut dyn Write,
+        users: &BTreeSet<String>,
+        user: Option<&str>,
+    ) -> anyhow::Result<()> {
+        // Attribution is redundant within a per-person section
+        if self.attribution && user.is_none() {
+            let users = users
+                .iter()
+                .map(|user| MARKUP.text(user))
+                .collect::<Vec<_>>();
+            write!(out, " ({})", users.join(", "))?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+impl Renderer for Rst {
+    fn render(
+        &self,
+        activity: &Activity,
+        users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        if let Some(header) = &self.header {
+            let title = MARKUP.text(&header.title);
+            let rule = "=".repeat(title.chars().count());
+            writeln!(out, "{rule}\n{title}\n{rule}\n")?;
+            writeln!(out, "{} – {}\n", header.first, header.last)?;
+        }
+
+        if activity.interrupted {
+            let note = "Collecting was interrupted, so this report is partial.";
+            writeln!(out, ".. note:: {}\n", self.locale.translate(note))?;
+        }
+
+        if let Some(Stats::Top) = self.stats {
+            stats(out, activity, self.scores.as_ref(), self.locale)?;
+        }
+
+        if let Some(comparison) = &self.comparison {
+            compare(out, comparison, self.locale)?;
+        }
+
+        if let Some(n) = self.highlights {
+            self.highlights(out, activity, n)?;
+        }
+
+        if self.releases_section {
+            self.releases(out, activity)?;
+        }
+
+        match self.split_maintained {
+            false => self.projects(out, activity, None, None, '=', '-')?,
+            true => {
+                for (maintained, title) in [
+                    (true, "My projects"),
+                    (false, "Contributions to other projects"),
+                ] {
+                    let mut projects = activity.projects.keys().chain(activity.pushes.keys());
+                    if projects.any(|project| activity.maintained.contains(project) == maintained) {
+                        heading(out, self.locale.translate(title), '=')?;
+                        self.projects(out, activity, None, Some(maintained), '-', '~')?;
+                    }
+                }
+            }
+        }
+        if self.merged_section {
+            self.merged(out, activity)?;
+        }
+        if self.mentions_section {
+            self.mentions(out, activity)?;
+        }
+        if !activity.forks.is_empty() || !activity.stars.is_empty() {
+            heading(out, self.locale.translate("Community"), '=')?;
+            for (repo, stars) in &activity.stars {
+                let s = if *stars == 1 { "" } else { "s" };
+                writeln!(out, "* {}: +{stars} star{s}", MARKUP.text(repo))?;
+            }
+            for (repo, forks) in &activity.forks {
+                let times = match forks.len() {
+                    1 => "once".to_owned(),
+                    n => format!("{n} times"),
+                };
+                write!(out, "* {} was forked {times}: ", MARKUP.text(repo))?;
+                for (i, (name, url)) in forks.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    let (name, url) = (MARKUP.link_text(name), MARKUP.url(url));
+                    write!(out, "{sep}`{name} <{url}>`_")?;
+                }
+                writeln!(out)?;
+            }
+            writeln!(out)?;
+        }
+
+        if let Some(privacy) = self.sponsors {
+            if !activity.sponsors.is_empty() {
+                heading(out, self.locale.translate("Sponsors"), '=')?;
+                for (user, sponsors) in &activity.sponsors {
+                    self.sponsors(out, user, sponsors, privacy)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        if !activity.snippets.is_empty() {
+            heading(out, self.locale.translate("Snippets"), '=')?;
+            for (html_url, Entry { title, users, .. }) in &activity.snippets {
+                let title = self.titles.normalize(title);
+                let (title, html_url) = (MARKUP.link_text(&title), MARKUP.url(html_url));
+                write!(out, "* `{title} <{html_url}>`_")?;
+                self.attribution(out, users, None)?;
+            }
+            writeln!(out)?;
+        }
+
+        if self.days && !activity.days.is_empty() {
+            heading(out, self.locale.translate("Activity by day"), '=')?;
+            for (day, n) in &activity.days {
+                let day = day.format("%a %Y-%m-%d");
+                writeln!(out, "* {day}: {}", plural(*n as usize, "interaction"))?;
+            }
+            writeln!(out)?;
+        }
+
+        if self.times && !activity.hours.is_empty() {
+            heading(out, self.locale.translate("Activity by time"), '=')?;
+            times(out, activity)?;
+        }
+
+        if let Some(Stats::Bottom) = self.stats {
+            stats(out, activity, self.scores.as_ref(), self.locale)?;
+        }
+
+        if self.appendix {
+            heading(out, self.locale.translate("Appendix"), '=')?;
+            for user in users {
+                heading(out, user, '-')?;
+                self.projects(out, activity, Some(user), None, '~', '^')?;
+            }
+        }
+
+        if let Some(streaks) = self.streaks {
+            write!(
+                out,
+                "*Active on {} of {}",
+                streaks.active,
+                plural(streaks.days, "day")
+            )?;
+            if streaks.current > 0 {
+                write!(out, ", on a streak of {}", plural(streaks.current, "day"))?;
+            }
+            writeln!(out, " (the longest: {}).*", plural(streaks.longest, "day"))?;
+            if self.generated.is_some() {
+                writeln!(out)?;
+            }
+        }
+
+        if let Some(generated) = self.generated {
+            let (by, on) = (
+                self.locale.translate("Generated by"),
+                self.locale.translate("on"),
+            );
+            let version = env!("CARGO_PKG_VERSION");
+            let time = generated.format("%Y-%m-%d %H:%M %Z");
+            writeln!(out, "*{by} tmog-events {version} {on} {time}.*")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether one of the users who touched `entry` merged it, while someone else opened it
+fn merged_for_others(entry: &Entry) -> bool {
+    entry
+        .merged_by
+        .as_ref()
+        .is_some_and(|merger| entry.users.contains(merger) && entry.author.as_ref() != Some(merger))
+}
+
+/// Whether the users were only mentioned in, requested to review or assigned to the item, rather
+/// than acting on it themselves
+fn pulled_in(entry: &Entry) -> bool {
+    let roles = entry.interactions.keys();
+    roles.clone().all(|role| role.indirect())
+        && roles
+            .clone()
+            .any(|role| matches!(role, Role::Mentioned | Role::Requested | Role::Assigned))
+}
+
+/// Write what a project is about as a paragraph, like "HTTP for Rust (Rust, `hyper.rs`)", noting
+/// whether its repository is archived or a fork
+fn describe(out: &mut dyn Write, about: &About) -> anyhow::Result<()> {
+    let homepage = about.homepage.as_deref().map(|url| {
+        let text = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let text = MARKUP.text(text.trim_end_matches('/'));
+        format!("`{text} <{}>`_", MARKUP.url(url))
+    });
+    let details = about
+        .language
+        .as_deref()
+        .map(|language| MARKUP.text(language).into_owned())
+        .into_iter()
+        .chain(homepage)
+        .collect::<Vec<_>>();
+    let line = match (about.description.as_deref(), details.is_empty()) {
+        (Some(description), true) => MARKUP.text(description).into_owned(),
+        (Some(description), false) => {
+            format!("{} ({})", MARKUP.text(description), details.join(", "))
+        }
+        (None, false) => details.join(", "),
+        (None, true) => String::new(),
+    };
+
+    let upstream = about
+        .upstream
+        .as_ref()
+        .map(|(name, url)| format!("fork of `{} <{}>`_", MARKUP.text(name), MARKUP.url(url)));
+    let notes = about
+        .archived
+        .then(|| "archived".to_owned())
+        .into_iter()
+        .chain(upstream)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let line = match (line.is_empty(), notes.is_empty()) {
+        (false, false) => format!("{line} — {notes}"),
+        (false, true) => line,
+        (true, false) => {
+            let mut notes = notes.chars();
+            let first = notes.next().map(|c| c.to_ascii_uppercase());
+            first.into_iter().chain(notes).collect()
+        }
+        (true, true) => return Ok(()),
+    };
+    write!(out, "{line}\n\n")?;
+    Ok(())
+}
+
+/// Write a line summarizing the triage `actions` in a project, by their number
+fn triage(out: &mut dyn Write, actions: &BTreeMap<String, u64>) -> anyhow::Result<()> {
+    let counts = TRIAGE
+        .iter()
+        .filter_map(|action| Some((action, *actions.get(*action)?)));
+    let summary = counts
+        .enumerate()
+        .map(|(i, (action, n))| match i {
+            0 => format!("{action} {}", plural(n as usize, "issue")),
+            _ => format!("{action} {n}"),
+        })
+        .collect::<Vec<_>>();
+    if !summary.is_empty() {
+        write!(out, "*Triage: {}.*\n\n", summary.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Write tags for the roles in `entry`, followed by the number of reviews (with their comments and
+/// verdicts), comments and review threads
+fn roles(out: &mut dyn Write, entry: &Entry) -> anyhow::Result<()> {
+    let counted = |role: &Role| matches!(role, Role::Review | Role::Comment);
+    let tags = entry
+        .interactions
+        .keys()
+        .filter(|role| !counted(role))
+        .map(Role::to_string)
+        .collect::<Vec<_>>();
+    if !tags.is_empty() {
+        write!(out, " [{}]", tags.join(", "))?;
+    }
+
+    let mut counts = entry
+        .interactions
+        .iter()
+        .filter(|(role, _)| counted(role))
+        .map(|(role, &n)| {
+            let mut count = format!("{n} {role}{}", if n == 1 { "" } else { "s" });
+            if *role == Role::Review && entry.review_comments > 0 {
+                let comments = plural(entry.review_comments as usize, "comment");
+                count = format!("{count} with {comments}");
+            }
+            if *role == Role::Review && !entry.verdicts.is_empty() {
+                let verdicts = entry.verdicts.iter().map(Verdict::to_string);
+                count = format!("{count}: {}", verdicts.collect::<Vec<_>>().join("/"));
+            }
+            count
+        })
+        .collect::<Vec<_>>();
+    if !entry.threads.is_empty() {
+        counts.push(plural(entry.threads.len(), "thread"));
+    }
+    if !counts.is_empty() {
+        write!(out, " ({})", counts.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Write a section summarizing the activity: the number of items (by kind) and projects, the
+/// number of reviews, comments and pushed commits, and the busiest project
+fn stats(
+    out: &mut dyn Write,
+    activity: &Activity,
+    scores: Option<&ScoreWeights>,
+    locale: Locale,
+) -> anyhow::Result<()> {
+    let entries = activity
+        .projects
+        .values()
+        .flat_map(|entries| entries.values());
+    let pushes = activity.pushes.values().flat_map(|pushes| pushes.values());
+    let projects = activity
+        .projects
+        .keys()
+        .chain(activity.pushes.keys())
+        .collect::<BTreeSet<_>>();
+    let size = |project: &str| {
+        let items = activity
+            .projects
+            .get(project)
+            .map_or(0, |entries| entries.len());
+        items
+            + activity
+                .pushes
+                .get(project)
+                .map_or(0, |pushes| pushes.len())
+    };
+
+    heading(out, locale.translate("Summary"), '=')?;
+    let items = entries.clone().count();
+    writeln!(
+        out,
+        "* {} in {}",
+        plural(items, "item"),
+        plural(projects.len(), "project")
+    )?;
+
+    let kinds = KINDS
+        .iter()
+        .map(|&(kind, name)| {
+            (
+                entries.clone().filter(|entry| entry.kind == kind).count(),
+                name,
+            )
+        })
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, name)| plural(n, name))
+        .collect::<Vec<_>>();
+    if !kinds.is_empty() {
+        writeln!(out, "* {}", kinds.join(", "))?;
+    }
+
+    let interactions = [(Role::Review, "review"), (Role::Comment, "comment")]
+        .into_iter()
+        .map(|(role, name)| {
+            let n = entries
+                .clone()
+                .filter_map(|entry| entry.interactions.get(&role));
+            (n.sum::<u64>() as usize, name)
+        })
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, name)| plural(n, name))
+        .collect::<Vec<_>>();
+    if !interactions.is_empty() {
+        writeln!(out, "* {}", interactions.join(", "))?;
+    }
+
+    // Reviewing and commenting by project, as reviewing shows up as few items per project
+    let mut discussed = activity
+        .projects
+        .iter()
+        .map(|(project, entries)| {
+            let count = |role| {
+                let counts = entries
+                    .values()
+                    .filter_map(|entry| entry.interactions.get(&role));
+                counts.sum::<u64>() as usize
+            };
+            (project, count(Role::Review), count(Role::Comment))
+        })
+        .filter(|(_, reviews, comments)| reviews + comments > 0)
+        .collect::<Vec<_>>();
+    discussed.sort_by_key(|(_, reviews, comments)| Reverse(reviews + comments));
+    if discussed.len() > 1 {
+        let projects = discussed
+            .iter()
+            .map(|(project, reviews, comments)| {
+                let counts = [(*reviews, "review"), (*comments, "comment")]
+                    .into_iter()
+                    .filter(|(n, _)| *n > 0)
+                    .map(|(n, name)| plural(n, name));
+                let counts = counts.collect::<Vec<_>>().join(", ");
+                format!("{} ({counts})", MARKUP.text(project))
+            })
+            .collect::<Vec<_>>();
+        writeln!(out, "* By project: {}", projects.join(", "))?;
+    }
+
+    let commits = pushes.map(|pushes| pushes.commits).sum::<u64>() as usize;
+    if commits > 0 {
+        writeln!(out, "* {} pushed directly", plural(commits, "commit"))?;
+    }
+
+    // The feed-based count can exceed the calendar's, which leaves out some repositories
+    if let Some(calendar) = activity.calendar.filter(|&calendar| calendar > 0) {
+        let captured = activity.contributions() * 100 / calendar;
+        writeln!(
+            out,
+            "* The events feed captured {captured}% of the {} counted by the contribution \
+             calendar",
+            plural(calendar as usize, "contribution")
+        )?;
+    }
+
+    // Ties are broken by name (`max_by_key()` yields the last maximum)
+    if let Some(busiest) = projects.iter().rev().max_by_key(|project| size(project)) {
+        let items = plural(size(busiest), "item");
+        writeln!(out, "* Busiest project: {} ({items})", MARKUP.text(busiest))?;
+    }
+
+    if let Some(weights) = scores {
+        let mut scores = activity.scores(weights).into_iter().collect::<Vec<_>>();
+        let total = scores.iter().map(|(_, score)| score).sum::<u64>();
+        // Stable, so projects with the same score stay in order of name
+        scores.sort_by_key(|(_, score)| Reverse(*score));
+        let projects = scores
+            .iter()
+            .map(|(project, score)| format!("{} {score}", MARKUP.text(project)))
+            .collect::<Vec<_>>();
+        writeln!(out, "* Score: {total} ({})", projects.join(", "))?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Write a section comparing the activity to that of the previous period
+fn compare(out: &mut dyn Write, comparison: &Comparison, locale: Locale) -> anyhow::Result<()> {
+    let title = format!("{} {}", locale.translate("Compared to"), comparison.period);
+    heading(out, &title, '=')?;
+    let (before, after) = comparison.items();
+    let delta = after as i64 - before as i64;
+    writeln!(out, "* {} ({delta:+})", plural(after, "item"))?;
+
+    let names = |projects: Vec<&str>| {
+        let projects = projects.into_iter().map(|project| MARKUP.text(project));
+        projects.collect::<Vec<_>>().join(", ")
+    };
+    let new = comparison.new_projects().collect::<Vec<_>>();
+    if !new.is_empty() {
+        writeln!(out, "* New projects: {}", names(new))?;
+    }
+    let quiet = comparison.quiet_projects().collect::<Vec<_>>();
+    if !quiet.is_empty() {
+        writeln!(out, "* Gone quiet: {}", names(quiet))?;
+    }
+
+    let changed = comparison
+        .projects
+        .iter()
+        .filter(|(_, &(before, after))| before > 0 && after > 0 && before != after)
+        .map(|(project, &(before, after))| format!("{} ({before} → {after})", MARKUP.text(project)))
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        writeln!(out, "* Changed: {}", changed.join(", "))?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Split an item link into the part before the item number and the number (if any)
+///
+/// Sorting by the result groups items by repository and kind, and orders them numerically.
+fn number(html_url: &str) -> (&str, Option<u64>, &str) {
+    let path = html_url.split('#').next().unwrap_or(html_url);
+    match path.rsplit_once('/') {
+        Some((prefix, number)) => (prefix, number.parse().ok(), html_url),
+        None => (html_url, None, html_url),
+    }
+}
+
+/// Escapes text and links for reStructuredText
+const MARKUP: Markup = Markup::Rst;
+
+/// An item link and its entry
+type Item<'a> = (&'a String, &'a Entry);
+
+/// The order and titles of the groups of items within a project (see [`Rst::group_by_kind`])
+const GROUPS: [(ItemKind, &str); 5] = [
+    (ItemKind::PullRequest, "Pull requests"),
+    (ItemKind::Issue, "Issues"),
+    (ItemKind::Release, "Releases"),
+    (ItemKind::Discussion, "Discussions"),
+    (ItemKind::Other, "Other"),
+];
+
+/// List the interactions per day of the week, then per block of hours (in the configured timezone)
+fn times(out: &mut dyn Write, activity: &Activity) -> anyhow::Result<()> {
+    for (&weekday, n) in &activity.weekdays {
+        let Ok(weekday) = Weekday::try_from(weekday as u8) else {
+            continue;
+        };
+        writeln!(out, "* {weekday}: {}", plural(*n as usize, "interaction"))?;
+    }
+
+    let mut blocks = BTreeMap::<u32, u64>::new();
+    for (hour, n) in &activity.hours {
+        *blocks.entry(hour - hour % HOURS).or_default() += n;
+    }
+    for (start, n) in blocks {
+        let hours = format!("{start:02}:00–{:02}:00", start + HOURS);
+        writeln!(out, "* {hours}: {}", plural(n as usize, "interaction"))?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// The number of hours in the blocks that the time of day is divided into
+const HOURS: u32 = 3;
+
+fn heading(out: &mut dyn Write, title: &str, underline: char) -> anyhow::Result<()> {
+    let title = MARKUP.text(title);
+    writeln!(out, "{}", title)?;
+    for _ in 0..title.chars().count() {
+        write!(out, "{underline}")?;
+    }
+    write!(out, "\n\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::{Asset, Size};
+
+    #[test]
+    fn links() {
+        let mut activity = Activity::default();
+        let url = "https://github.com/owner/repo_/pull/1";
+        let mut entry = Entry::new(
+            "Support `Vec<T>` in *all* places".to_owned(),
+            ItemKind::PullRequest,
+        );
+        entry.users.insert("djc".to_owned());
+        entry.notes.push("backported to *0.14*".to_owned());
+        activity
+            .projects
+            .entry("owner/repo_".to_owned())
+            .or_default()
+            .insert(url.to_owned(), entry);
+        activity.stars.insert("owner/repo_".to_owned(), 2);
+        let forks = activity.forks.entry("owner/repo_".to_owned()).or_default();
+        forks.insert("*fork*".to_owned(), "https://github.com/a/b_".to_owned());
+        let mut out = Vec::new();
+        let rst = Rst {
+            numbers: true,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with("owner/repo\\_\n============\n\n"));
+        assert!(report.contains(
+            "\n* `#1: Support \\`Vec\\<T\\>\\` in *all* places \
+             <https://github.com/owner/repo_/pull/1>`_\n\n  backported to \\*0.14\\*\n\n"
+        ));
+        assert!(report.contains("\n* owner/repo\\_: +2 stars\n"));
+        assert!(report
+            .contains("\n* owner/repo\\_ was forked once: `*fork* <https://github.com/a/b_>`_\n"));
+    }
+
+    #[test]
+    fn about() {
+        let mut activity = Activity::default();
+        let entry = Entry::new("Fix typo".to_owned(), ItemKind::PullRequest);
+        let url = "https://github.com/hyperium/hyper/pull/1";
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(url.to_owned(), entry);
+        let about = About {
+            description: Some("An HTTP library for *Rust*".to_owned()),
+            language: Some("Rust".to_owned()),
+            homepage: Some("https://hyper.rs/".to_owned()),
+            ..About::default()
+        };
+        activity.about.insert("hyper".to_owned(), about);
+        let mut out = Vec::new();
+        Rst::default()
+            .render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with(
+            "hyper\n=====\n\nAn HTTP library for \\*Rust\\* \
+             (Rust, `hyper.rs <https://hyper.rs/>`_)\n\n"
+        ));
+
+        let about = About {
+            archived: true,
+            upstream: Some((
+                "hyperium/hyper".to_owned(),
+                "https://github.com/hyperium/hyper".to_owned(),
+            )),
+            ..About::default()
+        };
+        activity.about.insert("hyper".to_owned(), about);
+        let mut out = Vec::new();
+        Rst::default()
+            .render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with(
+            "hyper\n=====\n\nArchived, fork of \
+             `hyperium/hyper <https://github.com/hyperium/hyper>`_\n\n"
+        ));
+    }
+
+    #[test]
+    fn sections() {
+        let mut activity = Activity::default();
+        for number in [1, 2, 3] {
+            let entry = Entry::new(format!("PR {number}"), ItemKind::PullRequest);
+            let url = format!("https://github.com/hyperium/hyper/pull/{number}");
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(url, entry);
+        }
+        let section = Section {
+            heading: Some("hyper & h2".to_owned()),
+            intro: Some("Work on the HTTP stack.".to_owned()),
+            pinned: vec!["https://github.com/hyperium/hyper/pull/3".to_owned()],
+        };
+        let rst = Rst {
+            sort: Sort::Number,
+            sections: HashMap::from([("hyper".to_owned(), section)]),
+            ..Rst::default()
+        };
+        let mut out = Vec::new();
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert_eq!(
+            report,
+            "hyper & h2\n==========\n\nWork on the HTTP stack.\n\n\
+             * `PR 3 <https://github.com/hyperium/hyper/pull/3>`_\n\
+             * `PR 1 <https://github.com/hyperium/hyper/pull/1>`_\n\
+             * `PR 2 <https://github.com/hyperium/hyper/pull/2>`_\n\n"
+        );
+    }
+
+    #[test]
+    fn highlights() {
+        let mut activity = Activity::default();
+        for (url, kind, state, comments) in [
+            ("issues/1", ItemKind::Issue, None, 5),
+            ("pull/2", ItemKind::PullRequest, Some(State::Merged), 0),
+            ("pull/3", ItemKind::PullRequest, Some(State::Closed), 9),
+            ("releases/tag/v1", ItemKind::Release, None, 0),
+        ] {
+            let mut entry = Entry::new(url.to_owned(), kind);
+            entry.state = state;
+            entry.interactions.insert(Role::Comment, comments);
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(format!("https://github.com/hyperium/hyper/{url}"), entry);
+        }
+
+        let mut out = Vec::new();
+        let rst = Rst {
+            highlights: Some(3),
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with(
+            "Highlights\n==========\n\n\
+             * hyper: `pull/2 <https://github.com/hyperium/hyper/pull/2>`_\n\
+             * hyper: `releases/tag/v1 <https://github.com/hyperium/hyper/releases/tag/v1>`_\n\
+             * hyper: `pull/3 <https://github.com/hyperium/hyper/pull/3>`_\n\n"
+        ));
+    }
+
+    #[test]
+    fn assets() {
+        let mut activity = Activity::default();
+        let mut entry = Entry::new("Hyper 1.0".to_owned(), ItemKind::Release);
+        entry.excerpt = Some("The first stable release.".to_owned());
+        entry.assets = vec![
+            Asset {
+                name: "hyper_*.tar.gz".to_owned(),
+                download_count: 12,
+            },
+            Asset {
+                name: "hyper.zip".to_owned(),
+                download_count: 1,
+            },
+        ];
+        let url = "https://github.com/hyperium/hyper/releases/tag/v1";
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(url.to_owned(), entry);
+
+        let mut out = Vec::new();
+        Rst::default()
+            .render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains(
+            "/tag/v1>`_\n\n  The first stable release.\n\n\n  \
+             * hyper\\_\\*.tar.gz: 12 downloads\n  * hyper.zip: 1 download\n\n"
+        ));
+    }
+
+    #[test]
+    fn releases_section() {
+        let mut activity = Activity::default();
+        for (project, url, title, day) in [
+            (
+                "hyper",
+                "https://github.com/hyperium/hyper/pull/1",
+                "Fix typo",
+                1,
+            ),
+            (
+                "hyper",
+                "https://github.com/hyperium/hyper/releases/tag/v1",
+                "Hyper 1.0",
+                12,
+            ),
+            (
+                "rustls",
+                "https://github.com/rustls/rustls/releases/tag/v0.23",
+                "0.23",
+                3,
+            ),
+        ] {
+            let kind = match url.contains("/releases/") {
+                true => ItemKind::Release,
+                false => ItemKind::PullRequest,
+            };
+            let mut entry = Entry::new(title.to_owned(), kind);
+            entry.first = Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).single();
+            let entries = activity.projects.entry(project.to_owned()).or_default();
+            entries.insert(url.to_owned(), entry);
+        }
+
+        let mut out = Vec::new();
+        let rst = Rst {
+            releases_section: true,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with(
+            "Releases\n========\n\n\
+             * rustls: `0.23 <https://github.com/rustls/rustls/releases/tag/v0.23>`_\n\
+             * hyper: `Hyper 1.0 <https://github.com/hyperium/hyper/releases/tag/v1>`_\n\n\
+             hyper\n=====\n\n\
+             * `Fix typo <https://github.com/hyperium/hyper/pull/1>`_\n\n"
+        ));
+    }
+
+    #[test]
+    fn merged_section() {
+        let mut activity = Activity::default();
+        for (number, author) in [(1, "seanmonstar"), (2, "djc")] {
+            let mut entry = Entry::new(format!("PR {number}"), ItemKind::PullRequest);
+            entry.users.insert("djc".to_owned());
+            entry.author = Some(author.to_owned());
+            entry.state = Some(State::Merged);
+            entry.merged_by = Some("djc".to_owned());
+            let url = format!("https://github.com/hyperium/hyper/pull/{number}");
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(url, entry);
+        }
+
+        let mut out = Vec::new();
+        let rst = Rst {
+            states: true,
+            merged_section: true,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert_eq!(
+            report,
+            "hyper\n=====\n\n\
+             * `PR 2 <https://github.com/hyperium/hyper/pull/2>`_ ✔\n\n\
+             Merged pull requests\n====================\n\n\
+             * hyper: `PR 1 <https://github.com/hyperium/hyper/pull/1>`_ ✔ (merged by djc)\n\n"
+        );
+    }
+
+    #[test]
+    fn mentions_section() {
+        let mut activity = Activity::default();
+        for (number, roles) in [
+            (1, &[Role::Mentioned][..]),
+            (2, &[Role::Mentioned, Role::Comment]),
+            (3, &[Role::Found]),
+        ] {
+            let mut entry = Entry::new(format!("Issue {number}"), ItemKind::Issue);
+            entry.users.insert("djc".to_owned());
+            entry
+                .interactions
+                .extend(roles.iter().map(|role| (*role, 1)));
+            let url = format!("https://github.com/hyperium/hyper/issues/{number}");
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(url, entry);
+        }
+
+        let mut out = Vec::new();
+        let rst = Rst {
+            mentions_section: true,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert_eq!(
+            report,
+            "hyper\n=====\n\n\
+             * `Issue 2 <https://github.com/hyperium/hyper/issues/2>`_\n\
+             * `Issue 3 <https://github.com/hyperium/hyper/issues/3>`_\n\n\
+             Where I was pulled in\n=====================\n\n\
+             * hyper: `Issue 1 <https://github.com/hyperium/hyper/issues/1>`_\n\n"
+        );
+    }
+
+    #[test]
+    fn triage() {
+        let mut activity = Activity::default();
+        let entry = Entry::new("Panic".to_owned(), ItemKind::Issue);
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert(
+            "https://github.com/hyperium/hyper/issues/1".to_owned(),
+            entry,
+        );
+        let actions = [("assigned", 5), ("closed", 14), ("labeled", 22)];
+        let actions = actions.map(|(action, n)| (action.to_owned(), n));
+        activity.triage.insert("hyper".to_owned(), actions.into());
+
+        let mut out = Vec::new();
+        let rst = Rst {
+            triage: true,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with(
+            "hyper\n=====\n\n*Triage: closed 14 issues, labeled 22, assigned 5.*\n\n* `Panic"
+        ));
+    }
+
+    #[test]
+    fn sizes() {
+        let mut activity = Activity::default();
+        for (number, additions, changed_files) in [(1, 12, 1), (2, 300, 4)] {
+            let mut entry = Entry::new(format!("PR {number}"), ItemKind::PullRequest);
+            entry.size = Some(Size {
+                additions,
+                deletions: 30,
+                changed_files,
+            });
+            let url = format!("https://github.com/hyperium/hyper/pull/{number}");
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            entries.insert(url, entry);
+        }
+
+        let render = |sizes| {
+            let mut out = Vec::new();
+            let rst = Rst {
+                sizes: Some(sizes),
+                ..Rst::default()
+            };
+            rst.render(&activity, &["djc".to_owned()], &mut out)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            render(Sizes::Badge),
+            "hyper\n=====\n\n\
+             * `PR 1 <https://github.com/hyperium/hyper/pull/1>`_ [S]\n\
+             * `PR 2 <https://github.com/hyperium/hyper/pull/2>`_ [L]\n\n"
+        );
+        assert!(render(Sizes::Numbers).contains("/pull/2>`_ (+300 -30, 4 files)\n"));
+    }
+
+    #[test]
+    fn discussed() {
+        let mut activity = Activity::default();
+        for (project, url, reviews, comments) in [
+            ("hyper", "https://github.com/hyperium/hyper/pull/1", 1, 0),
+            ("hyper", "https://github.com/hyperium/hyper/pull/2", 0, 1),
+            ("rustls", "https://github.com/rustls/rustls/pull/3", 2, 3),
+        ] {
+            let mut entry = Entry::new("PR".to_owned(), ItemKind::PullRequest);
+            entry.interactions.insert(Role::Review, reviews);
+            entry.interactions.insert(Role::Comment, comments);
+            let entries = activity.projects.entry(project.to_owned()).or_default();
+            entries.insert(url.to_owned(), entry);
+        }
+
+        let mut out = Vec::new();
+        stats(&mut out, &activity, None, Locale::En).unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("\n* 3 reviews, 4 comments\n"));
+        assert!(report.contains(
+            "\n* By project: rustls (2 reviews, 3 comments), hyper (1 review, 1 comment)\n"
+        ));
+        assert!(!report.contains("Score"));
+
+        let mut out = Vec::new();
+        let weights = ScoreWeights::default();
+        stats(&mut out, &activity, Some(&weights), Locale::En).unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("\n* Score: 13 (rustls 9, hyper 4)\n"));
+    }
+
+    #[test]
+    fn calendar() {
+        let mut activity = Activity::default();
+        let mut entry = Entry::new("PR".to_owned(), ItemKind::PullRequest);
+        entry.interactions.insert(Role::Author, 1);
+        entry.interactions.insert(Role::Review, 2);
+        entry.interactions.insert(Role::Comment, 5);
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        entries.insert("https://github.com/hyperium/hyper/pull/1".to_owned(), entry);
+        activity.calendar = Some(4);
+
+        let mut out = Vec::new();
+        stats(&mut out, &activity, None, Locale::En).unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains(
+            "\n* The events feed captured 75% of the 4 contributions counted by the contribution \
+             calendar\n"
+        ));
+    }
+
+    #[test]
+    fn streaks() {
+        let rst = Rst {
+            streaks: Some(Streaks {
+                active: 12,
+                days: 31,
+                current: 1,
+                longest: 5,
+            }),
+            ..Rst::default()
+        };
+        let mut out = Vec::new();
+        rst.render(&Activity::default(), &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report
+            .ends_with("*Active on 12 of 31 days, on a streak of 1 day (the longest: 5 days).*\n"));
+    }
+
+    #[test]
+    fn times() {
+        let activity = Activity {
+            weekdays: BTreeMap::from([(0, 3), (5, 1)]),
+            hours: BTreeMap::from([(9, 2), (10, 1), (23, 1)]),
+            ..Activity::default()
+        };
+        let mut out = Vec::new();
+        super::times(&mut out, &activity).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "* Mon: 3 interactions\n\
+             * Sat: 1 interaction\n\
+             * 09:00–12:00: 3 interactions\n\
+             * 21:00–24:00: 1 interaction\n\n"
+        );
+    }
+
+    #[test]
+    fn first_contribution() {
+        let mut activity = Activity::default();
+        let mut entry = Entry::new("Fix typo".to_owned(), ItemKind::PullRequest);
+        entry.first_contribution = true;
+        let url = "https://github.com/owner/repo/pull/1";
+        let entries = activity
+            .projects
+            .entry("owner/repo".to_owned())
+            .or_default();
+        entries.insert(url.to_owned(), entry);
+        let mut out = Vec::new();
+        let rst = Rst {
+            locale: Locale::Nl,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("<https://github.com/owner/repo/pull/1>`_ 🆕 eerste bijdrage\n"));
+    }
+
+    #[test]
+    fn interrupted() {
+        let activity = Activity {
+            interrupted: true,
+            ..Activity::default()
+        };
+        let mut out = Vec::new();
+        let rst = Rst {
+            locale: Locale::Nl,
+            ..Rst::default()
+        };
+        rst.render(&activity, &["djc".to_owned()], &mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with(
+            ".. note:: Het verzamelen is onderbroken, dus dit verslag is onvolledig.\n\n"
+        ));
+    }
+
+    #[test]
+    fn headings() {
+        let mut out = Vec::new();
+        heading(&mut out, "Één ✔", '-').unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Één ✔\n-----\n\n");
+    }
+}
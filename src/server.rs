@@ -0,0 +1,222 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+
+use crate::Format;
+
+/// A minimal HTTP/1.1 server for viewing reports rendered on demand
+///
+/// Requests are handled one at a time, which is plenty for a personal report viewer, and means
+/// that reports for the same period are never collected concurrently.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+}
+
+impl Server {
+    pub async fn bind(addr: SocketAddr) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("failed to listen on {addr}"))?;
+        Ok(Self { listener })
+    }
+
+    pub fn local_addr(&self) -> anyhow::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Wait for the next `GET` request (other requests are answered with an error)
+    pub async fn accept(&self) -> anyhow::Result<Request> {
+        loop {
+            let (mut stream, peer) = self.listener.accept().await?;
+            let head = match tokio::time::timeout(TIMEOUT, read_head(&mut stream)).await {
+                Ok(Ok(head)) => head,
+                Ok(Err(error)) => {
+                    debug!(%peer, "failed to read request: {error:#}");
+                    continue;
+                }
+                Err(_) => {
+                    debug!(%peer, "timed out reading request");
+                    continue;
+                }
+            };
+
+            let mut words = head.lines().next().unwrap_or_default().split(' ');
+            let (method, target) = (words.next(), words.next());
+            let response = match (method, target) {
+                (Some("GET"), Some(target)) => {
+                    let path = target.split(['?', '#']).next().unwrap_or_default();
+                    return Ok(Request {
+                        stream,
+                        path: path.to_owned(),
+                    });
+                }
+                (Some(_), Some(_)) => Response::text(405, "only GET requests are supported"),
+                _ => Response::text(400, "malformed request"),
+            };
+            if let Err(error) = write(&mut stream, response).await {
+                debug!(%peer, "failed to respond: {error:#}");
+            }
+        }
+    }
+}
+
+/// A `GET` request waiting for its response
+#[derive(Debug)]
+pub struct Request {
+    stream: TcpStream,
+    path: String,
+}
+
+impl Request {
+    /// The requested path, without the query string
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The report for a month requested as `/report/2024/03`, as a day in the month
+    ///
+    /// Reports are rendered as HTML, unless the path has the extension of another format (as in
+    /// `/report/2024/03.json`).
+    pub fn report(&self) -> Option<(NaiveDate, Format)> {
+        let (year, month) = self.path.strip_prefix("/report/")?.split_once('/')?;
+        let (month, format) = match month.split_once('.') {
+            Some((month, extension)) => (month, Format::from_extension(extension)?),
+            None => (month, Format::Html),
+        };
+        if year.len() != 4 || month.len() != 2 {
+            return None;
+        }
+        let date = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)?;
+        Some((date, format))
+    }
+
+    pub async fn respond(mut self, response: Response) {
+        if let Err(error) = write(&mut self.stream, response).await {
+            warn!(path = self.path, "failed to respond: {error:#}");
+        }
+    }
+}
+
+/// A response to a [`Request`]
+#[derive(Debug)]
+pub struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    pub fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    pub fn not_found() -> Self {
+        Self::text(404, "not found")
+    }
+
+    /// A response for a failure to produce the response
+    pub fn error(error: &anyhow::Error) -> Self {
+        Self::text(500, &format!("{error:#}"))
+    }
+
+    fn text(status: u16, text: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{text}\n").into_bytes(),
+        }
+    }
+}
+
+/// Read the request line and headers (any body is ignored)
+async fn read_head(stream: &mut TcpStream) -> anyhow::Result<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+    while !buf.windows(4).any(|window| window == b"\r\n\r\n") {
+        if buf.len() > MAX_HEAD {
+            anyhow::bail!("request head too large");
+        }
+        match stream.read(&mut chunk).await? {
+            0 => anyhow::bail!("connection closed"),
+            n => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+    Ok(String::from_utf8(buf)?)
+}
+
+async fn write(stream: &mut TcpStream, response: Response) -> anyhow::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+const MAX_HEAD: usize = 8 * 1024;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports() {
+        let server = Server::bind(([127, 0, 0, 1], 0).into()).await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            for path in [
+                "/report/2024/03?x",
+                "/report/2024/03.json",
+                "/report/2024/3",
+                "/",
+            ] {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+                stream.write_all(request.as_bytes()).await.unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await.unwrap();
+                assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+                assert!(response.ends_with("\r\n\r\nhi"));
+            }
+        });
+
+        let march = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let expected = [
+            Some((march, Format::Html)),
+            Some((march, Format::Json)),
+            None,
+            None,
+        ];
+        for expected in expected {
+            let request = server.accept().await.unwrap();
+            assert_eq!(request.report(), expected);
+            request
+                .respond(Response::ok("text/plain", b"hi".to_vec()))
+                .await;
+        }
+        client.await.unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{load_state, save_state, Activity, Cipher};
+
+/// The items (and snippets) in the activity of a run, to tell which items are new on the next
+///
+/// Items are identified by their URL, so that items that carry over into the next period (like a
+/// pull request that's still being reviewed) aren't new in it.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Snapshot {
+    items: BTreeSet<String>,
+}
+
+impl Snapshot {
+    /// The items in `activity`
+    pub fn of(activity: &Activity) -> Self {
+        let items = activity
+            .projects
+            .values()
+            .flat_map(|entries| entries.keys());
+        Self {
+            items: items.chain(activity.snippets.keys()).cloned().collect(),
+        }
+    }
+
+    /// Load the items saved at `path`, if any
+    pub fn load(path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<Self> {
+        load_state(path, cipher)
+    }
+
+    pub fn save(&self, path: &Path, cipher: Option<&Cipher>) -> anyhow::Result<()> {
+        save_state(self, path, cipher)
+    }
+
+    /// Reduce `activity` to the items that are new since the snapshot
+    ///
+    /// Everything else (like pushes, stars and triage) is left out, since it can't be told apart.
+    pub fn since(&self, activity: &mut Activity) {
+        for entries in activity.projects.values_mut() {
+            entries.retain(|html_url, _| !self.items.contains(html_url));
+        }
+        activity.projects.retain(|_, entries| !entries.is_empty());
+        activity
+            .snippets
+            .retain(|html_url, _| !self.items.contains(html_url));
+        activity.pushes.clear();
+        activity.forks.clear();
+        activity.stars.clear();
+        activity.sponsors.clear();
+        activity.triage.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn since() {
+        let activity = |numbers: &[u32]| {
+            let mut activity = Activity::default();
+            let entries = activity.projects.entry("hyper".to_owned()).or_default();
+            for number in numbers {
+                let url = format!("https://github.com/hyperium/hyper/pull/{number}");
+                let entry = Entry::new(format!("PR {number}"), ItemKind::PullRequest);
+                entries.insert(url, entry);
+            }
+            activity.stars.insert("djc/instant-acme".to_owned(), 3);
+            activity
+        };
+
+        let snapshot = Snapshot::of(&activity(&[1, 2]));
+        let mut later = activity(&[1, 2, 3]);
+        snapshot.since(&mut later);
+        let urls = later.projects["hyper"].keys().collect::<Vec<_>>();
+        assert_eq!(urls, ["https://github.com/hyperium/hyper/pull/3"]);
+        assert!(later.stars.is_empty());
+
+        let mut same = activity(&[2]);
+        snapshot.since(&mut same);
+        assert!(same.projects.is_empty());
+    }
+}
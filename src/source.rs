@@ -0,0 +1,997 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, SemaphorePermit};
+use tracing::{info_span, warn, Instrument};
+
+use crate::json::Elements;
+use crate::{Event, Locale, Metrics, Pass, State};
+
+/// A source of events, such as the activity feed of a forge
+///
+/// Implementations normalize their events into the shape of GitHub event payloads (see
+/// [`Event`]), so that the rest of the pipeline can treat all sources alike.
+pub trait EventSource: Send + Sync {
+    /// Short name of the source, used in coverage reports
+    fn name(&self) -> &str;
+
+    /// Item links from this source are expected to start with one of these
+    fn web_urls(&self) -> Vec<String>;
+
+    /// Identifies the events for `user` from this source in cache file names
+    fn cache_key(&self, user: &str) -> String;
+
+    /// Fetch the events for `user` within `interval`
+    ///
+    /// Requests should be made through `http`, which bounds the number of concurrent requests.
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>>;
+
+    /// Continue fetching the events for `user` within `interval` from a `cursor` yielded by an
+    /// earlier [`EventSource::fetch()`] (see [`Fetch::Cursor`])
+    ///
+    /// Sources that cannot resume yield `None`, so that the events are fetched from the start.
+    fn resume<'a>(
+        &'a self,
+        _user: &'a str,
+        _interval: Interval,
+        _cursor: String,
+        _http: &'a Http,
+    ) -> Option<BoxStream<'a, anyhow::Result<Fetch>>> {
+        None
+    }
+
+    /// Fetch events by others on repositories owned by `user` within `interval`, such as forks
+    ///
+    /// These are only fetched if requested. Sources that cannot provide them yield nothing.
+    fn received<'a>(
+        &'a self,
+        _user: &'a str,
+        _interval: Interval,
+        _http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(futures_util::stream::empty())
+    }
+
+    /// Fetch snippets (such as gists) created or updated by `user` within `interval`
+    ///
+    /// These are only fetched if requested. Sources that cannot provide them yield nothing.
+    fn snippets<'a>(
+        &'a self,
+        _user: &'a str,
+        _interval: Interval,
+        _http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(futures_util::stream::empty())
+    }
+
+    /// Fetch items within `interval` that `user` was notified about for being involved, such as
+    /// review requests and mentions, which may not show up among their events
+    ///
+    /// These are only fetched if requested. The events have the `NotificationEvent` type, with
+    /// the reason for the notification as their `action`. Sources that cannot provide them yield
+    /// nothing.
+    fn notifications<'a>(
+        &'a self,
+        _user: &'a str,
+        _interval: Interval,
+        _http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(futures_util::stream::empty())
+    }
+
+    /// Search for issues and pull requests updated within `interval` that involve `user`, to
+    /// find those that are missing from their events
+    ///
+    /// These are only fetched if requested. The events have the `SearchEvent` type. Sources that
+    /// cannot search yield nothing.
+    fn search<'a>(
+        &'a self,
+        _user: &'a str,
+        _interval: Interval,
+        _http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(futures_util::stream::empty())
+    }
+
+    /// Look up the current state and labels of the issue or pull request at `html_url`
+    ///
+    /// This is only done if requested. Sources that cannot provide them (or don't recognize the
+    /// link) yield `None`.
+    fn details<'a>(
+        &'a self,
+        _html_url: &'a str,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Details>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Look up the files uploaded to the release at `html_url`, with their current number of
+    /// downloads
+    ///
+    /// This is only done if requested. Sources that cannot provide them (or don't recognize the
+    /// link) yield `None`.
+    fn assets<'a>(
+        &'a self,
+        _html_url: &'a str,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<Asset>>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Look up everyone who took part in the issue or pull request at `html_url` (its author,
+    /// commenters, reviewers and the like), by login
+    ///
+    /// This is only done if requested (see [`Collector::with_self_activity_excluded()`]).
+    /// Sources that cannot provide them (or don't recognize the link) yield `None`.
+    ///
+    /// [`Collector::with_self_activity_excluded()`]: crate::Collector::with_self_activity_excluded
+    fn participants<'a>(
+        &'a self,
+        _html_url: &'a str,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Look up the current name (`owner/name`) of `repo`, which may have been renamed (or
+    /// transferred) since
+    ///
+    /// This is only done if requested. Sources that cannot resolve repositories yield `None`.
+    fn current_name<'a>(
+        &'a self,
+        _repo: &'a str,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Look up what `repo` (`owner/name`) is about, to describe the project it belongs to
+    ///
+    /// This is only done if requested. Sources that cannot describe repositories yield `None`.
+    fn about<'a>(
+        &'a self,
+        _repo: &'a str,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<About>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Look up the organizations `user` is (publicly) a member of, to treat as personal scopes
+    /// (see [`Collector::add_member_scopes()`])
+    ///
+    /// Sources that cannot tell yield `None`.
+    ///
+    /// [`Collector::add_member_scopes()`]: crate::Collector::add_member_scopes
+    fn organizations<'a>(
+        &'a self,
+        _user: &'a str,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Check whether the credentials allow fetching the activity of `users` (including private
+    /// activity, if `private`), and until when
+    ///
+    /// This should make (at most) a single cheap request. Sources that cannot tell yield the
+    /// default [`Access`].
+    fn check_access<'a>(
+        &'a self,
+        _users: &'a [String],
+        _private: bool,
+        _http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Access>> {
+        Box::pin(async { Ok(Access::default()) })
+    }
+
+    /// Estimate the cost of fetching the events for `user` within `interval`
+    ///
+    /// This should make (at most) a single cheap request to probe the source.
+    fn estimate<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>>;
+
+    /// The requests that fetching the events of `pass` for `user` within `interval` would make,
+    /// without making any
+    ///
+    /// Sources that cannot tell plan nothing.
+    fn plan(&self, _user: &str, _interval: Interval, _pass: Pass, _http: &Http) -> Vec<Planned> {
+        Vec::new()
+    }
+}
+
+/// What the credentials for a source allow (see [`EventSource::check_access()`])
+#[derive(Debug, Default)]
+pub struct Access {
+    /// A warning for each thing that the credentials don't allow
+    pub warnings: Vec<String>,
+    /// When the token expires (if it does)
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// An item yielded by [`EventSource::fetch()`]
+#[derive(Debug)]
+pub enum Fetch {
+    /// A normalized event payload
+    Event(String),
+    /// Not all events within the interval could be retrieved
+    Incomplete { total: Option<u64> },
+    /// All events up to here were yielded, and fetching can resume from this cursor (such as the
+    /// link to the next page) if it's interrupted (see [`EventSource::resume()`])
+    Cursor(String),
+}
+
+impl Fetch {
+    /// Normalize an `event` produced by a non-GitHub source
+    pub fn event(event: &Event) -> anyhow::Result<Self> {
+        Ok(Self::Event(serde_json::to_string(event)?))
+    }
+}
+
+/// The current details of an issue or pull request (see [`EventSource::details()`])
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Details {
+    pub state: State,
+    pub labels: Vec<String>,
+    /// Who merged the pull request, if it was merged
+    pub merged_by: Option<String>,
+    /// The size of the changes, if it's a pull request
+    pub size: Option<Size>,
+}
+
+/// The size of the changes of a pull request (see [`Details`])
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct Size {
+    /// Lines added
+    pub additions: u64,
+    /// Lines removed
+    pub deletions: u64,
+    pub changed_files: u64,
+}
+
+impl Size {
+    /// A badge for the number of lines changed: `S` (up to 50), `M` (up to 250), `L` (up to
+    /// 1000) or `XL`
+    pub fn badge(&self) -> &'static str {
+        match self.additions + self.deletions {
+            0..=50 => "S",
+            51..=250 => "M",
+            251..=1000 => "L",
+            _ => "XL",
+        }
+    }
+}
+
+/// What a repository is about (see [`EventSource::about()`])
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+pub struct About {
+    pub description: Option<String>,
+    /// The primary language (like `Rust`)
+    pub language: Option<String>,
+    pub homepage: Option<String>,
+    /// Whether the repository is archived (read-only)
+    #[serde(default)]
+    pub archived: bool,
+    /// The repository this one was forked from, if it's a fork, with its link
+    #[serde(default)]
+    pub upstream: Option<(String, String)>,
+}
+
+/// A file uploaded to a release (see [`EventSource::assets()`])
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct Asset {
+    pub name: String,
+    pub download_count: u64,
+}
+
+/// Cost of fetching events, as determined by probing a source
+#[derive(Debug)]
+pub struct Cost {
+    /// Expected number of API requests
+    pub requests: u64,
+    /// Whether `requests` is exact (otherwise, it is an approximation)
+    pub exact: bool,
+    /// Number of bytes the BigQuery query will process (which determines its cost)
+    pub bytes_processed: Option<u64>,
+}
+
+/// Requests to an endpoint that collecting would make (see [`EventSource::plan()`])
+#[derive(Debug)]
+pub struct Planned {
+    /// The first request (like `GET https://api.github.com/users/djc/events?per_page=100`)
+    pub request: String,
+    /// The number of requests to the endpoint (following its pages), if it's known beforehand
+    pub requests: Option<u64>,
+    /// Whether fewer requests may do, since paging stops at the start of the interval
+    pub at_most: bool,
+    /// The rate limit that the requests count against (like `core` or `search` for GitHub)
+    pub rate_limit: Option<&'static str>,
+}
+
+/// A span of time from `start` (inclusive) to `end` (exclusive)
+#[derive(Clone, Copy, Debug)]
+pub struct Interval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// The timezone in which the interval's boundaries and days are defined
+    pub timezone: Tz,
+}
+
+impl Interval {
+    /// The interval for the given `YYYYMM` month
+    pub fn month(month: &str) -> anyhow::Result<Self> {
+        Period::Month.interval(parse_date(month)?, 0, &Calendar::default())
+    }
+
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        time >= self.start && time < self.end
+    }
+
+    /// The day `time` falls on in the interval's timezone
+    pub fn date(&self, time: DateTime<Utc>) -> NaiveDate {
+        time.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// The first and the last day of the interval
+    pub fn days(&self) -> (NaiveDate, NaiveDate) {
+        let last = self.date(self.end - chrono::Duration::seconds(1));
+        (self.date(self.start), last)
+    }
+
+    /// Identifies the interval in cache file names: `YYYYMM` for calendar months, or the first
+    /// and last day (`YYYYMMDD-YYYYMMDD`) otherwise, followed by the timezone (unless UTC)
+    pub fn key(&self) -> String {
+        let (start, last) = self.days();
+        let month = start.checked_add_months(Months::new(1));
+        let mut key = match start.day() == 1 && month == last.succ_opt() {
+            true => start.format("%Y%m").to_string(),
+            false => format!("{}-{}", start.format("%Y%m%d"), last.format("%Y%m%d")),
+        };
+
+        if self.timezone != Tz::UTC {
+            key.push('+');
+            key.push_str(&self.timezone.name().replace('/', "_"));
+        }
+        key
+    }
+
+    /// The interval from the start of the day `start` until the start of the day `end` (in
+    /// `timezone`)
+    fn between(start: NaiveDate, end: NaiveDate, timezone: Tz) -> anyhow::Result<Self> {
+        let midnight = |date: NaiveDate| {
+            // Midnight may be skipped by a DST transition, in which case the day starts later
+            (0..24)
+                .find_map(|hour| {
+                    let time = date.and_hms_opt(hour, 0, 0)?;
+                    timezone.from_local_datetime(&time).earliest()
+                })
+                .map(|time| time.with_timezone(&Utc))
+                .with_context(|| format!("{date} does not exist in {timezone}"))
+        };
+
+        Ok(Self {
+            start: midnight(start)?,
+            end: midnight(end)?,
+            timezone,
+        })
+    }
+}
+
+/// The length of a reporting period
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    /// Weeks (starting on the day configured in the [`Calendar`])
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    /// The interval of the period containing `date`, moved back by `offset` periods
+    pub fn interval(
+        self,
+        date: NaiveDate,
+        offset: u32,
+        calendar: &Calendar,
+    ) -> anyhow::Result<Interval> {
+        let (start, months): (_, u32) = match self {
+            Self::Week => {
+                let start = calendar
+                    .week_start
+                    .of(date)
+                    .checked_sub_days(Days::new(7 * u64::from(offset)))
+                    .context("period out of range")?;
+                let end = start
+                    .checked_add_days(Days::new(7))
+                    .context("period out of range")?;
+                return Interval::between(start, end, calendar.timezone);
+            }
+            Self::Month => (date.with_day(1), 1),
+            Self::Quarter => (
+                date.with_day(1)
+                    .and_then(|d| d.with_month0(d.month0() / 3 * 3)),
+                3,
+            ),
+            Self::Year => (date.with_day(1).and_then(|d| d.with_month(1)), 12),
+        };
+
+        let start = start
+            .zip(months.checked_mul(offset))
+            .and_then(|(start, back)| start.checked_sub_months(Months::new(back)))
+            .context("period out of range")?;
+        let end = start
+            .checked_add_months(Months::new(months))
+            .context("period out of range")?;
+        Interval::between(start, end, calendar.timezone)
+    }
+
+    /// A human-readable name for the period `interval` (like "October 2024" or "Q4 2024")
+    pub fn name(self, interval: Interval, locale: Locale) -> String {
+        let start = interval.date(interval.start);
+        match self {
+            Self::Week => {
+                let week = locale.translate("the week of");
+                format!("{week} {}", start.format("%Y-%m-%d"))
+            }
+            Self::Month => format!("{} {}", locale.month(start.month0()), start.year()),
+            Self::Quarter => format!("Q{} {}", start.month0() / 3 + 1, start.year()),
+            Self::Year => start.year().to_string(),
+        }
+    }
+}
+
+/// How periods map onto the calendar
+#[derive(Clone, Copy, Debug)]
+pub struct Calendar {
+    pub week_start: WeekStart,
+    /// Periods start and end at midnight in this timezone
+    pub timezone: Tz,
+}
+
+impl Calendar {
+    /// Today's date in the calendar's timezone
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            week_start: WeekStart::default(),
+            timezone: Tz::UTC,
+        }
+    }
+}
+
+/// The first day of the week
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    /// ISO weeks
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    /// The first day of the week containing `date`
+    pub fn of(self, date: NaiveDate) -> NaiveDate {
+        let days = match self {
+            Self::Monday => date.weekday().num_days_from_monday(),
+            Self::Sunday => date.weekday().num_days_from_sunday(),
+        };
+        date - Days::new(days.into())
+    }
+}
+
+/// Parse `date` as a month (`YYYYMM`, yielding its first day) or a day (`YYYYMMDD`), ignoring
+/// dashes
+pub fn parse_date(date: &str) -> anyhow::Result<NaiveDate> {
+    let digits = date.replace('-', "");
+    let day = match digits.len() {
+        6 => format!("{digits}01"),
+        _ => digits,
+    };
+    NaiveDate::parse_from_str(&day, "%Y%m%d")
+        .with_context(|| format!("invalid date {date:?} (expected YYYYMM or YYYYMMDD)"))
+}
+
+/// HTTP client shared by all sources, which bounds the number of in-flight requests
+pub struct Http {
+    client: reqwest::Client,
+    throttle: Throttle,
+    metrics: Metrics,
+    /// Where to write the raw payloads of events as they're fetched (see [`Http::events()`])
+    pub(crate) dump: Option<Mutex<BufWriter<File>>>,
+    /// Fail on malformed events, instead of skipping them
+    pub(crate) strict: bool,
+    /// How many pages of events to fetch ahead (for sources that number their pages)
+    pub(crate) prefetch: usize,
+    /// How many events to ask for per page of a feed (for sources that support it)
+    pub(crate) per_page: usize,
+    /// How many pages of events to fetch at most from each feed
+    pub(crate) max_pages: Option<usize>,
+    /// How many requests to send at most (see [`Http::acquire()`])
+    pub(crate) budget: Option<u64>,
+}
+
+impl Http {
+    /// At most `concurrency` requests will be in flight at any time, and at most `per_host`
+    /// requests will be sent to the same host concurrently.
+    pub fn new(client: reqwest::Client, concurrency: usize, per_host: usize) -> Self {
+        Self {
+            client,
+            throttle: Throttle::new(concurrency, per_host),
+            metrics: Metrics::default(),
+            dump: None,
+            strict: false,
+            prefetch: 1,
+            per_page: 100,
+            max_pages: None,
+            budget: None,
+        }
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Wait until a request to `url` may be issued
+    ///
+    /// The request may proceed for as long as the returned permit is held. Once the request
+    /// budget is used up (see [`Collector::with_request_budget()`]), this fails with
+    /// [`Exhausted`] instead.
+    ///
+    /// [`Collector::with_request_budget()`]: crate::Collector::with_request_budget
+    pub async fn acquire(&self, url: &str) -> anyhow::Result<Permit<'_>> {
+        let permit = self.throttle.acquire(url).await?;
+        // Checked while holding the permit, so that requests waiting for one don't all pass
+        if let Some(budget) = self.budget {
+            if self.metrics.requests() >= budget {
+                return Err(Exhausted { budget }.into());
+            }
+        }
+        self.metrics.requested();
+        Ok(permit)
+    }
+
+    /// Record the rate limit reported in the headers of `rsp` (if any), and the interval between
+    /// polls that GitHub asks for in responses with events (`X-Poll-Interval`)
+    pub fn observe(&self, rsp: &reqwest::Response) {
+        let headers = rsp.headers();
+        // GitHub uses the `X-` prefix, GitLab doesn't
+        let remaining = headers
+            .get("x-ratelimit-remaining")
+            .or_else(|| headers.get("ratelimit-remaining"))
+            .and_then(|value| value.to_str().ok()?.parse().ok());
+        if let (Some(host), Some(remaining)) = (rsp.url().host_str(), remaining) {
+            self.metrics.rate_limit(host, remaining);
+        }
+        let poll = headers.get("x-poll-interval");
+        if let Some(seconds) = poll.and_then(|value| value.to_str().ok()?.parse().ok()) {
+            self.metrics.poll_requested(seconds);
+        }
+    }
+
+    /// Whether no more pages should be fetched from a feed after `pages` pages (see
+    /// [`Collector::with_max_pages()`](crate::Collector::with_max_pages))
+    pub fn capped(&self, pages: usize) -> bool {
+        self.max_pages.is_some_and(|max| pages >= max)
+    }
+
+    /// Send `request`, failing on error responses
+    ///
+    /// When rate limited (see [`retry_after()`]), as with GitHub's secondary rate limits, the
+    /// request is retried after waiting as long as the response says, if that's no longer than
+    /// [`MAX_RETRY_WAIT`]. Requests that time out (see [`reqwest::ClientBuilder::read_timeout()`])
+    /// are retried after a short wait. Either happens up to [`MAX_RETRIES`] times.
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut retries = 0;
+        loop {
+            // Requests with a streaming body can't be retried
+            let Some(attempt) = request.try_clone() else {
+                let rsp = request.send().await?;
+                self.observe(&rsp);
+                return Ok(rsp.error_for_status()?);
+            };
+
+            let rsp = match attempt.send().await {
+                Ok(rsp) => rsp,
+                Err(error) if error.is_timeout() && retries < MAX_RETRIES => {
+                    retries += 1;
+                    let wait = Duration::from_secs(1 << retries);
+                    let url = error.url().map(|url| url.as_str());
+                    warn!(
+                        url,
+                        retries,
+                        "request timed out, retrying in {}s",
+                        wait.as_secs()
+                    );
+                    tokio::time::sleep(wait).await;
+                    self.metrics.requested();
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            };
+            self.observe(&rsp);
+            let (url, status) = (rsp.url().to_string(), rsp.status());
+            let wait = retry_after(status, rsp.headers(), Utc::now());
+            let error = match rsp.error_for_status_ref() {
+                Ok(_) => return Ok(rsp),
+                Err(error) => error,
+            };
+
+            match wait {
+                Some(wait) if retries < MAX_RETRIES && wait <= MAX_RETRY_WAIT => {
+                    retries += 1;
+                    warn!(
+                        url,
+                        status = status.as_u16(),
+                        retries,
+                        "rate limited, retrying in {}s",
+                        wait.as_secs()
+                    );
+                    tokio::time::sleep(wait).await;
+                    self.metrics.requested();
+                }
+                Some(wait) => return Err(RateLimited { wait, error }.into()),
+                None if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN => {
+                    let hint = denied_hint(status, rsp.headers());
+                    // The body explains why, like `Bad credentials`
+                    let body = rsp.text().await.unwrap_or_default();
+                    let message = match serde_json::from_str::<Value>(&body) {
+                        Ok(json) => json["message"].as_str().map(str::to_owned),
+                        Err(_) => Some(body.trim().to_owned()).filter(|body| !body.is_empty()),
+                    };
+                    return Err(Denied {
+                        message,
+                        hint,
+                        error,
+                    }
+                    .into());
+                }
+                None => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Send `request` for the page of events at `url`, returning its events (see
+    /// [`Http::events()`]) along with the response headers (for the links to other pages)
+    pub async fn page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<(Vec<T>, HeaderMap)> {
+        let page = async {
+            let _permit = self.acquire(url).await?;
+            let rsp = self.send(request).await?;
+            let headers = rsp.headers().clone();
+            let events = self.events(rsp).await?;
+            self.metrics.paged(events.len());
+            Ok((events, headers))
+        };
+        page.instrument(info_span!("page", url)).await
+    }
+
+    /// Deserialize the page of events in `rsp`, as the response body arrives
+    ///
+    /// Events that fail to deserialize are skipped with a warning, unless in strict mode (see
+    /// [`Collector::with_strict()`](crate::Collector::with_strict)). If a dump is configured (see
+    /// [`Collector::with_dump()`](crate::Collector::with_dump)), the raw payload of each event is
+    /// written to it first, as a JSON object with the `url` of the page and the `event`.
+    pub async fn events<T: DeserializeOwned>(
+        &self,
+        mut rsp: reqwest::Response,
+    ) -> anyhow::Result<Vec<T>> {
+        let url = rsp.url().to_string();
+        let (mut elements, mut parsed) = (Elements::default(), Vec::new());
+        while let Some(chunk) = rsp.chunk().await? {
+            for event in elements.push(&chunk)? {
+                if let Some(event) = self.event(&url, event)? {
+                    parsed.push(event);
+                }
+            }
+        }
+        elements.finish()?;
+        if let Some(dump) = &self.dump {
+            dump.lock()
+                .unwrap()
+                .flush()
+                .context("failed to write dump")?;
+        }
+        Ok(parsed)
+    }
+
+    /// Deserialize a single `event` from the page at `url` (see [`Http::events()`])
+    fn event<T: DeserializeOwned>(&self, url: &str, event: Value) -> anyhow::Result<Option<T>> {
+        if let Some(dump) = &self.dump {
+            let mut dump = dump.lock().unwrap();
+            let line = serde_json::json!({ "url": url, "event": event });
+            serde_json::to_writer(&mut *dump, &line)?;
+            dump.write_all(b"\n")?;
+        }
+
+        match T::deserialize(&event) {
+            Ok(event) => Ok(Some(event)),
+            Err(error) if self.strict => {
+                Err(error).with_context(|| format!("malformed event: {event}"))
+            }
+            Err(error) => {
+                warn!(url, %event, "skipping malformed event: {error}");
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+/// How long to wait before retrying a request that got a response with `status` and `headers`, if
+/// it was rate limited
+///
+/// This follows GitHub's advice: wait for as long as `Retry-After` says, or until the rate limit
+/// is reset if none are left, or otherwise a minute for a 429 response. Other 403 responses are
+/// for lack of permission, which retrying doesn't help with.
+fn retry_after(status: StatusCode, headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let header = |name| headers.get(name)?.to_str().ok();
+    let until = |time: DateTime<Utc>| (time - now).to_std().unwrap_or_default();
+    if let Some(value) = header("retry-after") {
+        return match value.trim().parse() {
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(_) => Some(until(DateTime::parse_from_rfc2822(value).ok()?.to_utc())),
+        };
+    }
+
+    if header("x-ratelimit-remaining") == Some("0") {
+        let reset = header("x-ratelimit-reset")?.parse().ok()?;
+        return Some(until(DateTime::from_timestamp(reset, 0)?));
+    }
+
+    (status == StatusCode::TOO_MANY_REQUESTS).then_some(Duration::from_secs(60))
+}
+
+/// A request was still rate limited after retrying it (see [`Http::send()`])
+#[derive(Debug, thiserror::Error)]
+#[error("rate limited for another {}s, giving up", wait.as_secs())]
+pub struct RateLimited {
+    /// How long the rate limit lasts
+    pub wait: Duration,
+    #[source]
+    error: reqwest::Error,
+}
+
+/// A request wasn't sent, because the request budget was used up (see [`Http::acquire()`])
+#[derive(Debug, thiserror::Error)]
+#[error("request budget of {budget} used up")]
+pub struct Exhausted {
+    pub budget: u64,
+}
+
+/// The details of an item can't be looked up, because it's no longer there (see
+/// [`EventSource::details()`])
+///
+/// The item is still reported as its events tell of it, with a note on what became of it.
+#[derive(Debug, thiserror::Error)]
+pub enum Gone {
+    /// The item was deleted, or moved to a repository that can't be seen
+    #[error("item since moved or deleted")]
+    Item,
+    /// The repository was deleted (or made private)
+    #[error("repository since deleted")]
+    Repository,
+}
+
+/// A request was refused for lack of (valid) credentials or permission (see [`Http::send()`])
+#[derive(Debug, thiserror::Error)]
+pub struct Denied {
+    /// The reason given by the forge (if any)
+    pub message: Option<String>,
+    /// The likely cause, and how to fix it (see [`denied_hint()`])
+    pub hint: String,
+    #[source]
+    error: reqwest::Error,
+}
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request denied")?;
+        if let Some(message) = &self.message {
+            write!(f, ": {message}")?;
+        }
+        write!(f, "\n{}", self.hint)
+    }
+}
+
+/// Explain a 401 or 403 response (that isn't for a rate limit) with `headers`
+///
+/// GitHub says which scopes (`X-Accepted-OAuth-Scopes`) or permissions
+/// (`X-Accepted-GitHub-Permissions`) are needed, and whether the organization requires
+/// authorizing the token for single sign-on (`X-GitHub-SSO`).
+fn denied_hint(status: StatusCode, headers: &HeaderMap) -> String {
+    let header = |name| {
+        let value = headers.get(name)?.to_str().ok()?.trim();
+        Some(value).filter(|value| !value.is_empty())
+    };
+    if status == StatusCode::UNAUTHORIZED {
+        return "The token is invalid or has expired: create a new one, and configure it (like \
+                `github_token`) or set its environment variable (like GITHUB_TOKEN)"
+            .to_owned();
+    }
+
+    if let Some(sso) = header("x-github-sso") {
+        let url = sso.split_once("url=").map(|(_, url)| url);
+        return match url {
+            Some(url) => {
+                format!("The organization requires single sign-on: authorize the token at <{url}>")
+            }
+            None => "The organization requires single sign-on: authorize the token for it in the \
+                     token settings"
+                .to_owned(),
+        };
+    }
+
+    if let Some(scopes) = header("x-accepted-oauth-scopes") {
+        let has = header("x-oauth-scopes").unwrap_or("none");
+        return format!("The token lacks a scope: it needs one of `{scopes}`, but has `{has}`");
+    }
+    if let Some(permissions) = header("x-accepted-github-permissions") {
+        return format!("The token lacks a permission: it needs `{permissions}`");
+    }
+    "The token lacks permission for this resource (like a scope, or access to the organization)"
+        .to_owned()
+}
+
+/// How many times to retry a request that was rate limited or timed out (see [`Http::send()`])
+const MAX_RETRIES: usize = 3;
+/// The longest to wait before retrying a request that was rate limited (see [`Http::send()`])
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(5 * 60);
+
+/// Bounds the number of in-flight requests, both overall and per host
+struct Throttle {
+    global: Semaphore,
+    per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl Throttle {
+    fn new(global: usize, per_host: usize) -> Self {
+        Self {
+            global: Semaphore::new(global.max(1)),
+            per_host: per_host.max(1),
+            hosts: Mutex::default(),
+        }
+    }
+
+    async fn acquire(&self, url: &str) -> anyhow::Result<Permit<'_>> {
+        let host = reqwest::Url::parse(url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_owned();
+        let host = self
+            .hosts
+            .lock()
+            .unwrap()
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.per_host)))
+            .clone();
+
+        let host = host.acquire_owned().await?;
+        let global = self.global.acquire().await?;
+        Ok(Permit {
+            _global: global,
+            _host: host,
+        })
+    }
+}
+
+/// Permission to have a request in flight (see [`Http::acquire()`])
+pub struct Permit<'a> {
+    _global: SemaphorePermit<'a>,
+    _host: OwnedSemaphorePermit,
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn rate_limited() {
+        let now = Utc.with_ymd_and_hms(2024, 10, 1, 12, 0, 0).unwrap();
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, HeaderValue::from_static(value));
+            }
+            headers
+        };
+
+        let secondary = headers(&[("retry-after", "30")]);
+        let wait = retry_after(StatusCode::FORBIDDEN, &secondary, now);
+        assert_eq!(wait, Some(Duration::from_secs(30)));
+        let date = headers(&[("retry-after", "Tue, 01 Oct 2024 12:02:00 GMT")]);
+        let wait = retry_after(StatusCode::TOO_MANY_REQUESTS, &date, now);
+        assert_eq!(wait, Some(Duration::from_secs(120)));
+        assert_eq!(retry_after(StatusCode::BAD_GATEWAY, &secondary, now), None);
+
+        // 2024-10-01T12:10:00Z
+        let primary = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1727784600"),
+        ]);
+        let wait = retry_after(StatusCode::FORBIDDEN, &primary, now);
+        assert_eq!(wait, Some(Duration::from_secs(600)));
+
+        let forbidden = headers(&[("x-ratelimit-remaining", "4000")]);
+        assert_eq!(retry_after(StatusCode::FORBIDDEN, &forbidden, now), None);
+        let wait = retry_after(StatusCode::TOO_MANY_REQUESTS, &forbidden, now);
+        assert_eq!(wait, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn denied() {
+        let sso = "required; url=https://github.com/orgs/hyperium/sso?authorization_request=abc";
+        let mut headers = HeaderMap::new();
+        headers.insert("x-github-sso", HeaderValue::from_static(sso));
+        let hint = denied_hint(StatusCode::FORBIDDEN, &headers);
+        assert!(hint.ends_with("<https://github.com/orgs/hyperium/sso?authorization_request=abc>"));
+        assert!(denied_hint(StatusCode::UNAUTHORIZED, &headers).contains("expired"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-accepted-oauth-scopes", HeaderValue::from_static("repo"));
+        headers.insert("x-oauth-scopes", HeaderValue::from_static("read:user"));
+        assert_eq!(
+            denied_hint(StatusCode::FORBIDDEN, &headers),
+            "The token lacks a scope: it needs one of `repo`, but has `read:user`"
+        );
+    }
+
+    #[tokio::test]
+    async fn budget() {
+        let mut http = Http::new(reqwest::Client::new(), 1, 1);
+        http.budget = Some(1);
+        let url = "https://api.github.com/users/djc/events";
+        drop(http.acquire(url).await.unwrap());
+        let error = http.acquire(url).await.err().unwrap();
+        assert!(error.is::<Exhausted>());
+        assert_eq!(http.metrics.requests(), 1);
+    }
+}
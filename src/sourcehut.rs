@@ -0,0 +1,407 @@
+use async_stream::try_stream;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{Cost, Event, EventSource, Fetch, Http, Interval, ItemMeta};
+
+/// sourcehut (todo.sr.ht and lists.sr.ht)
+///
+/// sourcehut has no per-user activity feed, so the given `trackers` (on todo.sr.ht) and `lists`
+/// (on lists.sr.ht) are searched for tickets and patchsets the user submitted or commented on.
+/// Both are formatted as `~owner/name`.
+pub struct Sourcehut {
+    trackers: Vec<String>,
+    lists: Vec<String>,
+    token: Option<String>,
+}
+
+impl Sourcehut {
+    pub fn new(trackers: Vec<String>, lists: Vec<String>, token: Option<String>) -> Self {
+        Self {
+            trackers,
+            lists,
+            token,
+        }
+    }
+}
+
+impl EventSource for Sourcehut {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    fn web_urls(&self) -> Vec<String> {
+        vec![TODO_URL.to_owned(), LISTS_URL.to_owned()]
+    }
+
+    fn cache_key(&self, user: &str) -> String {
+        format!("{user}@sr.ht")
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        user: &'a str,
+        interval: Interval,
+        http: &'a Http,
+    ) -> BoxStream<'a, anyhow::Result<Fetch>> {
+        Box::pin(try_stream! {
+            let user = user.trim_start_matches('~');
+            let token = self.token.as_deref();
+
+            for tracker in &self.trackers {
+                let (owner, name) = split(tracker)?;
+                let mut cursor = None;
+                loop {
+                    let variables = Variables {
+                        owner,
+                        name,
+                        cursor: cursor.take(),
+                    };
+                    let data =
+                        graphql::<TrackerData>(TODO, TICKETS, &variables, token, http).await?;
+                    let tickets = data
+                        .user
+                        .and_then(|user| user.tracker)
+                        .ok_or_else(|| anyhow::Error::msg(format!("tracker {tracker} not found")))?;
+
+                    for ticket in tickets.tickets.results {
+                        if ticket.updated < interval.start {
+                            continue;
+                        }
+
+                        let submitted =
+                            ticket.submitter.is(user) && interval.contains(ticket.created);
+                        let commented = ticket.events.results.iter().any(|event| {
+                            interval.contains(event.created)
+                                && event.changes.iter().any(|change| {
+                                    change.author.as_ref().is_some_and(|author| author.is(user))
+                                })
+                        });
+
+                        if submitted || commented {
+                            let (kind, action) = match submitted {
+                                true => ("IssuesEvent", "opened"),
+                                false => ("IssueCommentEvent", "created"),
+                            };
+                            yield Fetch::event(&Event {
+                                kind: Some(kind.to_owned()),
+                                action: Some(action.to_owned()),
+                                issue: Some(ItemMeta {
+                                    html_url: format!("{TODO_URL}/~{owner}/{name}/{}", ticket.id),
+                                    title: ticket.subject,
+                                    created_at: Some(ticket.created),
+                                    closed_at: None,
+                                    merged_at: None,
+                                    node_id: None,
+                                    updated_at: None,
+                                    body: None,
+                                    user: None,
+                                    author_association: None,
+                                    pull_request: None,
+                                }),
+                                pull_request: None,
+                                ..Default::default()
+                            })?;
+                        }
+                    }
+
+                    match tickets.tickets.cursor {
+                        Some(next) => cursor = Some(next),
+                        None => break,
+                    }
+                }
+            }
+
+            for list in &self.lists {
+                let (owner, name) = split(list)?;
+                let mut cursor = None;
+                'pages: loop {
+                    let variables = Variables {
+                        owner,
+                        name,
+                        cursor: cursor.take(),
+                    };
+                    let data = graphql::<ListData>(LISTS, THREADS, &variables, token, http).await?;
+                    let threads = data.user.and_then(|user| user.list).ok_or_else(|| {
+                        anyhow::Error::msg(format!("mailing list {list} not found"))
+                    })?;
+
+                    // Threads are ordered by their most recent activity
+                    for thread in threads.threads.results {
+                        if thread.updated < interval.start {
+                            break 'pages;
+                        }
+
+                        let Some(patchset) = thread.root.patchset else {
+                            continue;
+                        };
+
+                        let sent = |email: &Email| {
+                            email.sender.is(user)
+                                && email.date.is_some_and(|date| interval.contains(date))
+                        };
+                        // Replies on the mailing list are how patches are reviewed
+                        let (kind, action) = match sent(&thread.root.email) {
+                            true => ("PullRequestEvent", "opened"),
+                            false if thread.descendants.results.iter().any(sent) => {
+                                ("PullRequestReviewEvent", "submitted")
+                            }
+                            false => continue,
+                        };
+
+                        yield Fetch::event(&Event {
+                            kind: Some(kind.to_owned()),
+                            action: Some(action.to_owned()),
+                            issue: None,
+                            pull_request: Some(ItemMeta {
+                                html_url: format!(
+                                    "{LISTS_URL}/~{owner}/{name}/patches/{}",
+                                    patchset.id
+                                ),
+                                title: thread.subject,
+                                created_at: Some(thread.created),
+                                closed_at: None,
+                                merged_at: None,
+                                node_id: None,
+                                updated_at: None,
+                                body: None,
+                                user: None,
+                                author_association: None,
+                                pull_request: None,
+                            }),
+                            ..Default::default()
+                        })?;
+                    }
+
+                    match threads.threads.cursor {
+                        Some(next) => cursor = Some(next),
+                        None => break,
+                    }
+                }
+            }
+        })
+    }
+
+    /// Estimate the number of requests without probing the GraphQL APIs
+    ///
+    /// Each tracker and list needs at least one request, more for busy ones.
+    fn estimate<'a>(
+        &'a self,
+        _: &'a str,
+        _: Interval,
+        _: &'a Http,
+    ) -> BoxFuture<'a, anyhow::Result<Cost>> {
+        Box::pin(async move {
+            Ok(Cost {
+                requests: (self.trackers.len() + self.lists.len()) as u64,
+                exact: false,
+                bytes_processed: None,
+            })
+        })
+    }
+}
+
+async fn graphql<T: DeserializeOwned>(
+    url: &str,
+    query: &str,
+    variables: &Variables<'_>,
+    token: Option<&str>,
+    http: &Http,
+) -> anyhow::Result<T> {
+    debug!(
+        url,
+        owner = variables.owner,
+        name = variables.name,
+        "querying"
+    );
+    let _permit = http.acquire(url).await?;
+    let mut req = http.client().post(url).json(&Query { query, variables });
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+
+    let rsp = req
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Response<T>>()
+        .await?;
+    match (rsp.data, rsp.errors) {
+        (Some(data), None) => Ok(data),
+        (_, Some(errors)) if !errors.is_empty() => {
+            let messages = errors.into_iter().map(|e| e.message).collect::<Vec<_>>();
+            anyhow::bail!("GraphQL query failed: {}", messages.join("; "))
+        }
+        (Some(data), _) => Ok(data),
+        (None, _) => anyhow::bail!("GraphQL query returned no data"),
+    }
+}
+
+/// Split a `~owner/name` reference into its owner and name
+fn split(reference: &str) -> anyhow::Result<(&str, &str)> {
+    reference
+        .trim_start_matches('~')
+        .split_once('/')
+        .ok_or_else(|| anyhow::Error::msg(format!("expected `~owner/name`, got {reference:?}")))
+}
+
+#[derive(Debug, Serialize)]
+struct Query<'a> {
+    query: &'a str,
+    variables: &'a Variables<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Variables<'a> {
+    owner: &'a str,
+    name: &'a str,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cursor<T> {
+    results: Vec<T>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackerData {
+    user: Option<TrackerUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackerUser {
+    tracker: Option<Tracker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tracker {
+    tickets: Cursor<Ticket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Ticket {
+    id: u64,
+    subject: String,
+    created: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    submitter: Entity,
+    events: Cursor<TicketEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketEvent {
+    created: DateTime<Utc>,
+    changes: Vec<Change>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Change {
+    /// Only set for comments
+    author: Option<Entity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListData {
+    user: Option<ListUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListUser {
+    list: Option<MailingList>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MailingList {
+    threads: Cursor<Thread>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Thread {
+    subject: String,
+    created: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    root: Root,
+    descendants: Cursor<Email>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Root {
+    #[serde(flatten)]
+    email: Email,
+    patchset: Option<Patchset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Email {
+    sender: Entity,
+    date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Patchset {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entity {
+    canonical_name: String,
+}
+
+impl Entity {
+    fn is(&self, user: &str) -> bool {
+        self.canonical_name.trim_start_matches('~') == user
+    }
+}
+
+const TICKETS: &str = "query($owner: String!, $name: String!, $cursor: Cursor) {
+  user(username: $owner) {
+    tracker(name: $name) {
+      tickets(cursor: $cursor) {
+        results {
+          id subject created updated
+          submitter { canonicalName }
+          events { results { created changes { ... on Comment { author { canonicalName } } } } }
+        }
+        cursor
+      }
+    }
+  }
+}";
+
+const THREADS: &str = "query($owner: String!, $name: String!, $cursor: Cursor) {
+  user(username: $owner) {
+    list(name: $name) {
+      threads(cursor: $cursor) {
+        results {
+          subject created updated
+          root { sender { canonicalName } date patchset { id } }
+          descendants { results { sender { canonicalName } date } }
+        }
+        cursor
+      }
+    }
+  }
+}";
+
+const SOURCE: &str = "sourcehut";
+const TODO_URL: &str = "https://todo.sr.ht";
+const LISTS_URL: &str = "https://lists.sr.ht";
+const TODO: &str = "https://todo.sr.ht/query";
+const LISTS: &str = "https://lists.sr.ht/query";
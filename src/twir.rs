@@ -0,0 +1,71 @@
+use std::io::Write;
+
+use crate::html::projects;
+use crate::{Activity, Links, Markup, Renderer, Titles};
+
+/// Renders items as submissions for This Week in Rust's "Updates from the Rust Community"
+///
+/// Each item is a Markdown bullet with the project name in bold, followed by a link to the item
+/// with its title as the description, like `* **hyper**: [Fix a panic](https://…)`.
+#[derive(Debug)]
+pub struct Twir<'a> {
+    pub titles: &'a Titles,
+    pub links: &'a Links,
+}
+
+impl Renderer for Twir<'_> {
+    fn render(
+        &self,
+        activity: &Activity,
+        _users: &[String],
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        for project in projects(activity) {
+            for (html_url, entry) in activity.entries(project) {
+                let link = self.links.link(project, entry.link(html_url));
+                let title = self.titles.normalize(&entry.title);
+                writeln!(
+                    out,
+                    "* **{}**: [{}]({})",
+                    MARKDOWN.text(project),
+                    MARKDOWN.link_text(&title),
+                    MARKDOWN.url(&link)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const MARKDOWN: Markup = Markup::Markdown;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind};
+
+    #[test]
+    fn submissions() {
+        let mut activity = Activity::default();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        for (n, title) in [(1, "Fix a panic in [`Body`]"), (2, "Release v1.4.1")] {
+            let entry = Entry::new(title.to_owned(), ItemKind::PullRequest);
+            entries.insert(format!("https://github.com/hyperium/hyper/pull/{n}"), entry);
+        }
+        // Pushes aren't submitted
+        activity.pushes.entry("rustls".to_owned()).or_default();
+
+        let (titles, links) = (Titles::default(), Links::default());
+        let twir = Twir {
+            titles: &titles,
+            links: &links,
+        };
+        let mut out = Vec::new();
+        twir.render(&activity, &[], &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "* **hyper**: [Fix a panic in \\[\\`Body\\`\\]](https://github.com/hyperium/hyper/pull/1)\n\
+             * **hyper**: [Release v1.4.1](https://github.com/hyperium/hyper/pull/2)\n"
+        );
+    }
+}
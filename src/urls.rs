@@ -0,0 +1,157 @@
+use serde::Deserialize;
+
+/// Maps the API URLs of items to their web URLs and back, by rules of path templates
+///
+/// Rules are tried in order, so that configured rules (see [`GitHub::with_url_rules()`]) take
+/// precedence over the built-in ones.
+///
+/// [`GitHub::with_url_rules()`]: crate::GitHub::with_url_rules
+#[derive(Clone, Debug)]
+pub(crate) struct UrlRules {
+    rules: Vec<UrlRule>,
+}
+
+impl UrlRules {
+    /// The rules for GitHub (and GitHub Enterprise Server), after those in `rules`
+    pub(crate) fn github(mut rules: Vec<UrlRule>) -> Self {
+        rules.extend([
+            UrlRule::new(
+                "/repos/{owner}/{repo}/pulls/{number}",
+                "/{owner}/{repo}/pull/{number}",
+            ),
+            UrlRule::new(
+                "/repos/{owner}/{repo}/issues/{number}",
+                "/{owner}/{repo}/issues/{number}",
+            ),
+        ]);
+        Self { rules }
+    }
+
+    /// The web URL (under `web_url`) of the item at `url` (under `api_url`), if a rule matches
+    pub(crate) fn web(&self, url: &str, api_url: &str, web_url: &str) -> Option<String> {
+        let path = strip_base(url, api_url)?;
+        let path = self
+            .rules
+            .iter()
+            .find_map(|rule| fill(&rule.web, &matches(&rule.api, path)?))?;
+        Some(format!("{}{path}", web_url.trim_end_matches('/')))
+    }
+
+    /// The API URL (under `api_url`) of the item at `url` (under `web_url`), if a rule matches
+    pub(crate) fn api(&self, url: &str, web_url: &str, api_url: &str) -> Option<String> {
+        let path = strip_base(url, web_url)?;
+        let path = self
+            .rules
+            .iter()
+            .find_map(|rule| fill(&rule.api, &matches(&rule.web, path)?))?;
+        Some(format!("{}{path}", api_url.trim_end_matches('/')))
+    }
+}
+
+/// How the API URL of an item maps to its web URL
+///
+/// Both are paths (relative to the API and web URLs) of segments that are either literal or a
+/// `{placeholder}`, which takes the value of the corresponding segment of the other path.
+#[derive(Clone, Debug, Deserialize)]
+pub struct UrlRule {
+    /// Like `/repos/{owner}/{repo}/pulls/{number}`
+    pub api: String,
+    /// Like `/{owner}/{repo}/pull/{number}`
+    pub web: String,
+}
+
+impl UrlRule {
+    fn new(api: &str, web: &str) -> Self {
+        Self {
+            api: api.to_owned(),
+            web: web.to_owned(),
+        }
+    }
+}
+
+/// The path of `url` below `base`, starting with a slash
+fn strip_base<'a>(url: &'a str, base: &str) -> Option<&'a str> {
+    let path = url.strip_prefix(base.trim_end_matches('/'))?;
+    path.starts_with('/').then_some(path)
+}
+
+/// The values of the placeholders in `template` if `path` matches it
+fn matches<'a, 't>(template: &'t str, path: &'a str) -> Option<Vec<(&'t str, &'a str)>> {
+    let (mut template, mut path) = (template.split('/'), path.split('/'));
+    let mut values = Vec::new();
+    loop {
+        match (template.next(), path.next()) {
+            (None, None) => return Some(values),
+            (Some(segment), Some(value)) => match placeholder(segment) {
+                Some(name) if !value.is_empty() => values.push((name, value)),
+                None if segment == value => {}
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+}
+
+/// Fill in the placeholders of `template`, if all of them have `values`
+fn fill(template: &str, values: &[(&str, &str)]) -> Option<String> {
+    let segments = template
+        .split('/')
+        .map(|segment| match placeholder(segment) {
+            Some(name) => values
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| *value),
+            None => Some(segment),
+        });
+    Some(segments.collect::<Option<Vec<_>>>()?.join("/"))
+}
+
+fn placeholder(segment: &str) -> Option<&str> {
+    segment.strip_prefix('{')?.strip_suffix('}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewritten() {
+        let (api_url, web_url) = (
+            "https://github.example.com/api/v3",
+            "https://github.example.com",
+        );
+        let rules = UrlRules::github(vec![UrlRule::new(
+            "/repos/{owner}/{repo}/discussions/{number}",
+            "/{owner}/{repo}/discussions/{number}",
+        )]);
+        let web = |path: &str| rules.web(&format!("{api_url}{path}"), api_url, web_url);
+        assert_eq!(
+            web("/repos/djc/askama/pulls/12").as_deref(),
+            Some("https://github.example.com/djc/askama/pull/12")
+        );
+        assert_eq!(
+            web("/repos/djc/askama/discussions/3").as_deref(),
+            Some("https://github.example.com/djc/askama/discussions/3")
+        );
+        assert_eq!(web("/repos/djc/askama/releases/1"), None);
+        assert_eq!(web("/repos/djc/askama/pulls/12/files"), None);
+        assert_eq!(
+            rules.web("https://api.github.com/repos/a/b/pulls/1", api_url, web_url),
+            None
+        );
+
+        let api = |url: &str| rules.api(url, web_url, api_url);
+        assert_eq!(
+            api("https://github.example.com/djc/askama/pull/12").as_deref(),
+            Some("https://github.example.com/api/v3/repos/djc/askama/pulls/12")
+        );
+        assert_eq!(
+            api("https://github.example.com/djc/askama/commit/abc"),
+            None
+        );
+        assert_eq!(
+            api("https://github.example.com.evil/djc/askama/pull/12"),
+            None
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::render::summary;
+use crate::{Activity, Http, Markup};
+
+/// An incoming webhook of a chat service to post a summary of the report to
+#[derive(Debug, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    /// The chat service, which determines the message format
+    #[serde(default)]
+    pub chat: Chat,
+    /// List only this many of the most active items (instead of all of them)
+    pub top: Option<usize>,
+}
+
+impl Webhook {
+    /// A message summarizing `activity` during the period `name`
+    pub fn message(&self, activity: &Activity, name: &str) -> String {
+        let mut message = match self.chat {
+            Chat::Slack => format!("*{}*\n{}\n", self.chat.text(name), summary(activity)),
+            Chat::Discord => format!("**{}**\n{}\n", self.chat.text(name), summary(activity)),
+        };
+
+        let items = activity.highlights(self.top.unwrap_or(usize::MAX));
+        for (project, html_url, entry) in items {
+            let (title, project) = (self.chat.text(&entry.title), self.chat.text(project));
+            let line = match self.chat {
+                Chat::Slack => format!("• <{html_url}|{title}> ({project})\n"),
+                Chat::Discord => format!("- [{title}](<{html_url}>) ({project})\n"),
+            };
+            // Leave room for the ellipsis
+            if message.chars().count() + line.chars().count() + 1 > self.chat.limit() {
+                message.push('…');
+                break;
+            }
+            message.push_str(&line);
+        }
+        message
+    }
+
+    /// Post `message` (see [`Webhook::message()`]) to the webhook
+    pub async fn post(&self, message: &str, http: &Http) -> anyhow::Result<()> {
+        let body = match self.chat {
+            Chat::Slack => serde_json::json!({
+                "text": message,
+                "blocks": [{
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": message },
+                }],
+            }),
+            Chat::Discord => serde_json::json!({ "content": message }),
+        };
+
+        info!("posting to webhook");
+        let _permit = http.acquire(&self.url).await?;
+        http.client()
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()
+            .context("webhook rejected the message")?;
+        Ok(())
+    }
+}
+
+/// Chat services with incoming webhooks
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chat {
+    /// Slack, with the message as a block of mrkdwn
+    #[default]
+    Slack,
+    /// Discord, with the message as Markdown
+    Discord,
+}
+
+impl Chat {
+    /// Escape `text` for the message format
+    fn text(self, text: &str) -> String {
+        match self {
+            Self::Slack => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;"),
+            Self::Discord => Markup::Markdown.link_text(text).into_owned(),
+        }
+    }
+
+    /// The maximum length of a message (in characters)
+    fn limit(self) -> usize {
+        match self {
+            // Slack's limit for the text of a section block
+            Self::Slack => 3000,
+            Self::Discord => 2000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entry, ItemKind, Role};
+
+    #[test]
+    fn messages() {
+        let mut activity = Activity::default();
+        let entries = activity.projects.entry("hyper".to_owned()).or_default();
+        for (n, interactions) in [(1, 1), (2, 3)] {
+            let mut entry = Entry::new(format!("Fix <bug> #{n}"), ItemKind::PullRequest);
+            entry.interactions.insert(Role::Comment, interactions);
+            let url = format!("https://github.com/hyperium/hyper/pull/{n}");
+            entries.insert(url, entry);
+        }
+
+        let mut webhook = Webhook {
+            url: String::new(),
+            chat: Chat::Slack,
+            top: Some(1),
+        };
+        assert_eq!(
+            webhook.message(&activity, "March 2024"),
+            "*March 2024*\n2 pull requests in 1 project\n\
+             • <https://github.com/hyperium/hyper/pull/2|Fix &lt;bug&gt; #2> (hyper)\n"
+        );
+
+        webhook.chat = Chat::Discord;
+        webhook.top = None;
+        let message = webhook.message(&activity, "March 2024");
+        assert!(
+            message.contains("- [Fix \\<bug\\> #2](<https://github.com/hyperium/hyper/pull/2>)")
+        );
+        assert!(message.ends_with("/pull/1>) (hyper)\n"));
+    }
+}
@@ -0,0 +1,154 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Write `value` as a YAML front matter document, with the same structure as its JSON
+///
+/// Strings are always double-quoted (with the escapes of JSON, which YAML shares), so that none
+/// of them are read as another type, like `yes` or `2024-03-01`.
+pub fn to_yaml(value: &impl Serialize) -> anyhow::Result<String> {
+    let mut out = String::from("---\n");
+    match serde_json::to_value(value)? {
+        value @ (Value::Object(_) | Value::Array(_)) if !is_empty(&value) => {
+            write(&mut out, &value, 0)
+        }
+        value => {
+            out.push_str(&scalar(&value));
+            out.push('\n');
+        }
+    }
+    out.push_str("---\n");
+    Ok(out)
+}
+
+/// Write a non-empty object or array in block style, at `indent`
+fn write(out: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                out.push_str(&pad);
+                out.push_str(&self::key(key));
+                out.push(':');
+                nested(out, value, indent + 2);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                out.push_str(&pad);
+                out.push('-');
+                match is_empty(value) {
+                    // The first line of the value goes after the dash
+                    false => {
+                        let mut item = String::new();
+                        write(&mut item, value, indent + 2);
+                        out.push(' ');
+                        out.push_str(&item[indent + 2..]);
+                    }
+                    true => {
+                        out.push(' ');
+                        out.push_str(&scalar(value));
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        _ => unreachable!("only objects and arrays are written in block style"),
+    }
+}
+
+/// Write the `value` of a key, on the next lines at `indent` if it's a non-empty collection
+fn nested(out: &mut String, value: &Value, indent: usize) {
+    match is_empty(value) {
+        false => {
+            out.push('\n');
+            write(out, value, indent);
+        }
+        true => {
+            out.push(' ');
+            out.push_str(&scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+/// Whether `value` is written on a single line (as a scalar, or an empty collection)
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        _ => true,
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_owned(),
+        Value::Array(_) => "[]".to_owned(),
+        // Like `null`, `true`, `1.5` or `"text"`
+        value => value.to_string(),
+    }
+}
+
+/// Leave `key` unquoted if it can't be read as anything but a string
+fn key(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !matches!(
+            key.to_ascii_lowercase().as_str(),
+            "y" | "n" | "yes" | "no" | "on" | "off" | "true" | "false" | "null"
+        );
+    match plain {
+        true => key.to_owned(),
+        false => Value::from(key).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_matter() {
+        let value = serde_json::json!({
+            "projects": {
+                "hyper": {
+                    "https://github.com/hyperium/hyper/pull/1": {
+                        "title": "Fix \"panic\"",
+                        "users": ["djc"],
+                        "state": null,
+                    },
+                },
+            },
+            "days": { "2024-03-01": 2 },
+            "coverage": [{ "source": "github", "complete": true }, { "source": "gitlab" }],
+            "nested": [[1, 2], []],
+            "on": {},
+        });
+        assert_eq!(
+            to_yaml(&value).unwrap(),
+            "---\n\
+             coverage:\n  \
+               - complete: true\n    \
+                 source: \"github\"\n  \
+               - source: \"gitlab\"\n\
+             days:\n  \
+               \"2024-03-01\": 2\n\
+             nested:\n  \
+               - - 1\n    \
+                 - 2\n  \
+               - []\n\
+             \"on\": {}\n\
+             projects:\n  \
+               hyper:\n    \
+                 \"https://github.com/hyperium/hyper/pull/1\":\n      \
+                   state: null\n      \
+                   title: \"Fix \\\"panic\\\"\"\n      \
+                   users:\n        \
+                     - \"djc\"\n\
+             ---\n"
+        );
+        assert_eq!(to_yaml(&Vec::<u8>::new()).unwrap(), "---\n[]\n---\n");
+    }
+}